@@ -0,0 +1,33 @@
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use dptree::{di::DependencyMap, prelude::*};
+
+// A single-use tree, as built in tests.
+fn tree() -> Handler<'static, DependencyMap, i32> {
+    dptree::entry()
+        .branch(dptree::filter(|x: i32| x < 0).endpoint(|| async { -1 }))
+        .branch(dptree::filter(|x: i32| x > 0).endpoint(|| async { 1 }))
+        .endpoint(|| async { 0 })
+}
+
+fn bench_dispatch_owned(c: &mut Criterion) {
+    let rt = tokio::runtime::Builder::new_current_thread().build().unwrap();
+
+    c.bench_function("single_use/dispatch", |b| {
+        b.iter_batched(
+            || (tree(), dptree::deps![1]),
+            |(handler, deps)| rt.block_on(handler.dispatch(deps)),
+            BatchSize::SmallInput,
+        );
+    });
+
+    c.bench_function("single_use/dispatch_owned", |b| {
+        b.iter_batched(
+            || (tree(), dptree::deps![1]),
+            |(handler, deps)| rt.block_on(handler.dispatch_owned(deps)),
+            BatchSize::SmallInput,
+        );
+    });
+}
+
+criterion_group!(benches, bench_dispatch_owned);
+criterion_main!(benches);
@@ -0,0 +1,30 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use dptree::{di::DependencyMap, prelude::*};
+
+// A long chain of trivial handlers, so that the time is spent on executing the
+// chain itself rather than on the handlers.
+fn chain() -> Handler<'static, DependencyMap, usize> {
+    let mut handler = dptree::entry();
+    for _ in 0..100 {
+        handler = handler.chain(dptree::from_fn(|event, cont| cont(event)));
+    }
+    handler.endpoint(|| async { 0 })
+}
+
+fn bench_dispatch(c: &mut Criterion) {
+    let rt = tokio::runtime::Builder::new_current_thread().build().unwrap();
+    let deps = dptree::deps![];
+
+    let handler = chain();
+    c.bench_function("chain/dispatch", |b| {
+        b.iter(|| rt.block_on(handler.dispatch(deps.clone())));
+    });
+
+    let frozen = handler.freeze();
+    c.bench_function("chain/dispatch_frozen", |b| {
+        b.iter(|| rt.block_on(frozen.dispatch(deps.clone())));
+    });
+}
+
+criterion_group!(benches, bench_dispatch);
+criterion_main!(benches);
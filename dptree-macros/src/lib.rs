@@ -0,0 +1,71 @@
+//! Procedural macros for [`dptree`](https://docs.rs/dptree/).
+//!
+//! Do not depend on this crate directly; enable the `macros` feature of
+//! `dptree` instead.
+
+use proc_macro::TokenStream;
+use quote::{format_ident, quote};
+use syn::{parse_macro_input, spanned::Spanned, Error, FnArg, ItemFn, ReturnType};
+
+/// Generates an endpoint constructor for an async function.
+///
+/// For `async fn name(..) -> T`, the macro keeps the function as is and
+/// generates `fn name_handler() -> dptree::Endpoint<'static, DependencyMap,
+/// T>`, which returns `dptree::endpoint(name)`. The parameters of the function
+/// are injected from the dependency map, as with [`dptree::endpoint`].
+///
+/// The generated constructor has the same visibility as the function.
+///
+/// [`dptree::endpoint`]: https://docs.rs/dptree/latest/dptree/fn.endpoint.html
+#[proc_macro_attribute]
+pub fn endpoint(args: TokenStream, item: TokenStream) -> TokenStream {
+    let item = parse_macro_input!(item as ItemFn);
+
+    match expand_endpoint(args.into(), &item) {
+        Ok(constructor) => quote! { #item #constructor }.into(),
+        Err(error) => {
+            let error = error.to_compile_error();
+            quote! { #item #error }.into()
+        }
+    }
+}
+
+fn expand_endpoint(
+    args: proc_macro2::TokenStream,
+    item: &ItemFn,
+) -> syn::Result<proc_macro2::TokenStream> {
+    if !args.is_empty() {
+        return Err(Error::new(args.span(), "`#[dptree::endpoint]` does not accept arguments"));
+    }
+
+    let sig = &item.sig;
+    if sig.asyncness.is_none() {
+        return Err(Error::new(sig.fn_token.span(), "`#[dptree::endpoint]` requires an async fn"));
+    }
+    if !sig.generics.params.is_empty() {
+        return Err(Error::new(
+            sig.generics.span(),
+            "`#[dptree::endpoint]` does not support generic functions",
+        ));
+    }
+    if let Some(receiver) = sig.inputs.iter().find(|arg| matches!(arg, FnArg::Receiver(_))) {
+        return Err(Error::new(receiver.span(), "`#[dptree::endpoint]` does not support methods"));
+    }
+
+    let vis = &item.vis;
+    let name = &sig.ident;
+    let constructor = format_ident!("{}_handler", name);
+    let output = match &sig.output {
+        ReturnType::Default => quote! { () },
+        ReturnType::Type(_, ty) => quote! { #ty },
+    };
+    let doc = format!("Returns an endpoint that calls [`{}`].", name);
+
+    Ok(quote! {
+        #[doc = #doc]
+        #[must_use]
+        #vis fn #constructor() -> ::dptree::Endpoint<'static, ::dptree::di::DependencyMap, #output> {
+            ::dptree::endpoint(#name)
+        }
+    })
+}
@@ -0,0 +1,55 @@
+use std::{ops::ControlFlow, sync::Arc};
+
+use dptree::deps;
+
+struct Config {
+    greeting: &'static str,
+}
+
+#[dptree::endpoint]
+async fn greet(config: Arc<Config>, name: &'static str) -> String {
+    format!("{}, {}!", config.greeting, name)
+}
+
+#[dptree::endpoint]
+async fn nothing() {}
+
+mod inner {
+    #[dptree::endpoint]
+    pub(crate) async fn answer() -> i32 {
+        42
+    }
+}
+
+#[tokio::test]
+async fn test_endpoint() {
+    let config = Arc::new(Config { greeting: "Hello" });
+    let handler = greet_handler();
+
+    assert_eq!(
+        handler.dispatch(deps![config, "world"]).await,
+        ControlFlow::Break("Hello, world!".to_owned())
+    );
+}
+
+#[tokio::test]
+async fn test_endpoint_in_chain() {
+    let handler = dptree::entry()
+        .branch(dptree::filter(|x: i32| x > 0).chain(inner::answer_handler()))
+        .branch(dptree::endpoint(|| async { 0 }));
+
+    assert_eq!(handler.dispatch(deps![1]).await, ControlFlow::Break(42));
+    assert_eq!(handler.dispatch(deps![-1]).await, ControlFlow::Break(0));
+}
+
+#[tokio::test]
+async fn test_endpoint_unit() {
+    assert_eq!(nothing_handler().dispatch(deps![]).await, ControlFlow::Break(()));
+}
+
+#[tokio::test]
+async fn test_original_fn_is_kept() {
+    let config = Arc::new(Config { greeting: "Hi" });
+
+    assert_eq!(greet(config, "there").await, "Hi, there!");
+}
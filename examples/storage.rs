@@ -21,14 +21,14 @@ async fn main() {
 
     let str_num_handler = assert_num_string_handler(10u32, "Hello");
 
-    str_num_handler.dispatch(store.clone()).await;
+    let _ = str_num_handler.dispatch(store.clone()).await;
 
     // This will cause a panic because we do not store `Ipv4Addr` in out store.
     let handle = tokio::spawn(async move {
         let ip_handler: Endpoint<_, _> = dptree::endpoint(|ip: Ipv4Addr| async move {
             assert_eq!(ip, Ipv4Addr::new(0, 0, 0, 0));
         });
-        ip_handler.dispatch(store.clone()).await;
+        let _ = ip_handler.dispatch(store.clone()).await;
     });
     let result = handle.await;
     assert!(result.is_err())
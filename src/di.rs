@@ -12,13 +12,12 @@
 //! [this discussion on StackOverflow]: https://stackoverflow.com/questions/130794/what-is-dependency-injection
 use futures::future::{ready, BoxFuture};
 
-use std::{
+use alloc::{boxed::Box, collections::BTreeMap, sync::Arc, vec, vec::Vec};
+use core::{
     any::{Any, TypeId},
-    collections::HashMap,
-    fmt::{Debug, Formatter, Write},
+    fmt::{Debug, Formatter},
     future::Future,
     ops::Deref,
-    sync::Arc,
 };
 
 /// A DI container from which we can extract a value of a given type.
@@ -27,9 +26,9 @@ use std::{
 /// cannot return a value of specified type:
 ///
 /// 1. Do not implement [`DependencySupplier`] for the type. It often requires
-/// some type-level manipulations.
+///    some type-level manipulations.
 /// 2. Runtime panic. Be careful in this case: check whether you add your type
-/// to the container.
+///    to the container.
 ///
 /// A concrete solution is left to a particular implementation.
 pub trait DependencySupplier<Value> {
@@ -86,20 +85,42 @@ pub trait DependencySupplier<Value> {
 /// ```
 #[derive(Default, Clone)]
 pub struct DependencyMap {
-    map: HashMap<TypeId, Dependency>,
+    map: BTreeMap<TypeId, Dependency>,
 }
 
 #[derive(Clone)]
 struct Dependency {
     type_name: &'static str,
     inner: Arc<dyn Any + Send + Sync>,
+    // Whether `inner` is a `Lazy<T>` rather than a `T`.
+    lazy: bool,
+}
+
+/// A function computing a value of type `T` from the container, see
+/// [`DependencyMap::insert_lazy`].
+type Lazy<T> = Box<dyn Fn(&DependencyMap) -> T + Send + Sync>;
+
+impl Dependency {
+    /// Returns the value of type `T`, computing it from `container` if it is
+    /// lazy.
+    fn resolve<T: Send + Sync + 'static>(&self, container: &DependencyMap) -> Arc<T> {
+        if self.lazy {
+            let f = self.inner.downcast_ref::<Lazy<T>>().expect("Values are stored by TypeId");
+            Arc::new(f(container))
+        } else {
+            Arc::clone(&self.inner).downcast().expect("Values are stored by TypeId")
+        }
+    }
+
+    /// Returns the value of type `T`, or `None` if it is lazy.
+    fn into_value<T: Send + Sync + 'static>(self) -> Option<Arc<T>> {
+        (!self.lazy).then(|| self.inner.downcast().expect("Values are stored by TypeId"))
+    }
 }
 
 impl PartialEq for DependencyMap {
     fn eq(&self, other: &Self) -> bool {
-        let keys1 = self.map.keys();
-        let keys2 = other.map.keys();
-        keys1.zip(keys2).map(|(k1, k2)| k1 == k2).all(|x| x)
+        self.map.keys().eq(other.map.keys())
     }
 }
 
@@ -111,14 +132,103 @@ impl DependencyMap {
     /// Inserts a value into the container.
     ///
     /// If the container do not has this type present, `None` is returned.
-    /// Otherwise, the value is updated, and the old value is returned.
+    /// Otherwise, the value is updated, and the old value is returned. A value
+    /// inserted by [`DependencyMap::insert_lazy`] is not computed here, so
+    /// `None` is returned for it as well.
     pub fn insert<T: Send + Sync + 'static>(&mut self, item: T) -> Option<Arc<T>> {
+        self.insert_arc(Arc::new(item))
+    }
+
+    /// Inserts a shared value into the container.
+    ///
+    /// This is the same as [`DependencyMap::insert`], except that `item` is
+    /// stored as is instead of being wrapped into another [`Arc`], so
+    /// [`DependencySupplier::get`] returns a clone of `item` itself.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dptree::di::{DependencyMap, DependencySupplier};
+    /// use std::sync::Arc;
+    ///
+    /// let config = Arc::new("config");
+    /// let mut container = DependencyMap::new();
+    /// container.insert_arc(Arc::clone(&config));
+    ///
+    /// let got: Arc<&str> = container.get();
+    /// assert!(Arc::ptr_eq(&got, &config));
+    /// ```
+    pub fn insert_arc<T: Send + Sync + 'static>(&mut self, item: Arc<T>) -> Option<Arc<T>> {
         self.map
             .insert(
                 TypeId::of::<T>(),
-                Dependency { type_name: std::any::type_name::<T>(), inner: Arc::new(item) },
+                Dependency { type_name: core::any::type_name::<T>(), inner: item, lazy: false },
             )
-            .map(|dep| dep.inner.downcast().expect("Values are stored by TypeId"))
+            .and_then(Dependency::into_value)
+    }
+
+    /// Inserts a value computed by `f` each time it is requested.
+    ///
+    /// `f` receives the container the value is requested from, so it can be
+    /// computed from other values. This is useful for values that are
+    /// expensive to compute, or that may change between requests, such as the
+    /// ones read from the environment. If `f` panics, so does the request.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dptree::di::{DependencyMap, DependencySupplier};
+    /// use std::sync::Arc;
+    ///
+    /// let mut container = DependencyMap::new().with(2i32);
+    /// container.insert_lazy(|container: &DependencyMap| {
+    ///     let n: Arc<i32> = container.get();
+    ///     format!("{n} apples")
+    /// });
+    ///
+    /// assert!(container.contains::<String>());
+    /// assert_eq!(container.get(), Arc::new("2 apples".to_owned()));
+    ///
+    /// container.insert(3i32);
+    /// assert_eq!(container.get(), Arc::new("3 apples".to_owned()));
+    /// ```
+    pub fn insert_lazy<T, F>(&mut self, f: F)
+    where
+        T: Send + Sync + 'static,
+        F: Fn(&DependencyMap) -> T + Send + Sync + 'static,
+    {
+        let f: Lazy<T> = Box::new(f);
+        self.map.insert(
+            TypeId::of::<T>(),
+            Dependency { type_name: core::any::type_name::<T>(), inner: Arc::new(f), lazy: true },
+        );
+    }
+
+    /// Inserts a value into the container and returns the container.
+    ///
+    /// This is the same as [`DependencyMap::insert`], but allows constructing
+    /// a container fluently. If the container already has a value of this type,
+    /// the value is replaced.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use dptree::prelude::*;
+    ///
+    /// let container = DependencyMap::new().with(2i32).with("apples");
+    ///
+    /// let handler: Handler<_, _> =
+    ///     dptree::endpoint(|n: i32, what: &'static str| async move { format!("{n} {what}") });
+    ///
+    /// assert_eq!(handler.dispatch(container).await, ControlFlow::Break("2 apples".to_owned()));
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn with<T: Send + Sync + 'static>(mut self, item: T) -> Self {
+        self.insert(item);
+        self
     }
 
     /// Inserts all dependencies from another container into itself.
@@ -126,29 +236,67 @@ impl DependencyMap {
         self.map.extend(container.map);
     }
 
+    /// Returns `true` if the container has a value of type `T`.
+    pub fn contains<T: Send + Sync + 'static>(&self) -> bool {
+        self.map.contains_key(&TypeId::of::<T>())
+    }
+
     /// Removes a value from the container.
     ///
     /// If the container do not has this type present, `None` is returned.
-    /// Otherwise, the value is removed and returned.
+    /// Otherwise, the value is removed and returned. A value inserted by
+    /// [`DependencyMap::insert_lazy`] is removed without being computed, so
+    /// `None` is returned for it.
     pub fn remove<T: Send + Sync + 'static>(&mut self) -> Option<Arc<T>> {
-        self.map
-            .remove(&TypeId::of::<T>())
-            .map(|dep| dep.inner.downcast().expect("Values are stored by TypeId"))
+        self.map.remove(&TypeId::of::<T>()).and_then(Dependency::into_value)
     }
 
-    fn available_types(&self) -> String {
-        let mut list = String::new();
+    /// Replaces the value of type `T` with the one from `other`, or removes it
+    /// if `other` has no such value.
+    pub(crate) fn restore<T: Send + Sync + 'static>(&mut self, other: &Self) {
+        let id = TypeId::of::<T>();
+        match other.map.get(&id) {
+            Some(dep) => self.map.insert(id, dep.clone()),
+            None => self.map.remove(&id),
+        };
+    }
 
-        for dep in self.map.values() {
-            writeln!(list, "    {}", dep.type_name).unwrap();
+    /// Returns a value of type `T`, or an error if there is no such value.
+    ///
+    /// This is the non-panicking version of [`DependencySupplier::get`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dptree::di::{DependencyMap, MissingDependency};
+    /// use std::sync::Arc;
+    ///
+    /// let container = DependencyMap::new().with(5i32);
+    ///
+    /// assert_eq!(container.try_get::<i32>(), Ok(Arc::new(5)));
+    /// assert_eq!(
+    ///     container.try_get::<bool>(),
+    ///     Err(MissingDependency { requested: "bool", available: vec!["i32"] })
+    /// );
+    /// ```
+    pub fn try_get<T: Send + Sync + 'static>(&self) -> Result<Arc<T>, MissingDependency> {
+        match self.map.get(&TypeId::of::<T>()) {
+            Some(dep) => Ok(dep.resolve(self)),
+            None => Err(MissingDependency {
+                requested: core::any::type_name::<T>(),
+                available: self.type_names().collect(),
+            }),
         }
+    }
 
-        list
+    /// Returns the type names of all values in the container.
+    pub(crate) fn type_names(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.map.values().map(|dep| dep.type_name)
     }
 }
 
 impl Debug for DependencyMap {
-    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), core::fmt::Error> {
         f.debug_struct("DependencyMap").finish()
     }
 }
@@ -158,22 +306,34 @@ where
     V: Send + Sync + 'static,
 {
     fn get(&self) -> Arc<V> {
-        self.map
-            .get(&TypeId::of::<V>())
-            .unwrap_or_else(|| {
-                panic!(
-                    "{} was requested, but not provided. Available types:\n{}",
-                    std::any::type_name::<V>(),
-                    self.available_types()
-                )
-            })
-            .clone()
-            .inner
-            .downcast::<V>()
-            .expect("Checked by .unwrap_or_else()")
+        self.try_get().unwrap_or_else(|error| panic!("{}", error))
+    }
+}
+
+/// An error returned by [`DependencyMap::try_get`] when a value of the
+/// requested type is absent.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MissingDependency {
+    /// The name of the requested type.
+    pub requested: &'static str,
+
+    /// The names of the types available in the container.
+    pub available: Vec<&'static str>,
+}
+
+impl core::fmt::Display for MissingDependency {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        writeln!(f, "{} was requested, but not provided. Available types:", self.requested)?;
+        for type_name in &self.available {
+            writeln!(f, "    {}", type_name)?;
+        }
+        Ok(())
     }
 }
 
+#[cfg(feature = "std")]
+impl std::error::Error for MissingDependency {}
+
 impl<V, S> DependencySupplier<V> for Arc<S>
 where
     S: DependencySupplier<V>,
@@ -188,11 +348,22 @@ where
 /// The function must follow some rules, to be usable with DI:
 ///
 /// 1. For each function parameter of type `T`, `Input` must satisfy
-/// `DependencySupplier<T>`.
+///    `DependencySupplier<T>`.
 /// 2. The function must be of 0-9 arguments.
 /// 3. The function must return [`Future`].
 pub trait Injectable<Input, Output, FnArgs> {
     fn inject<'a>(&'a self, container: &'a Input) -> CompiledFn<'a, Output>;
+
+    /// Returns the type names of the values this function takes from a
+    /// container.
+    ///
+    /// The default implementation returns an empty list.
+    fn dependencies() -> Vec<&'static str>
+    where
+        Self: Sized,
+    {
+        Vec::new()
+    }
 }
 
 /// A function with all dependencies satisfied.
@@ -201,6 +372,13 @@ pub type CompiledFn<'a, Output> = Arc<dyn Fn() -> BoxFuture<'a, Output> + Send +
 /// Turns a synchronous function into a type that implements [`Injectable`].
 pub struct Asyncify<F>(pub F);
 
+/// Turns a function that accepts `&Input` as its first parameter into a type
+/// that implements [`Injectable`].
+///
+/// The first parameter receives the whole container, the rest are injected
+/// as usual. Combine it with [`Asyncify`] for synchronous functions.
+pub struct WithInput<F>(pub F);
+
 macro_rules! impl_into_di {
     ($($generic:ident),*) => {
         impl<Func, Input, Output, Fut, $($generic),*> Injectable<Input, Output, ($($generic,)*)> for Func
@@ -215,11 +393,15 @@ macro_rules! impl_into_di {
             #[allow(unused_variables)]
             fn inject<'a>(&'a self, container: &'a Input) -> CompiledFn<'a, Output> {
                 Arc::new(move || {
-                    $(let $generic = std::borrow::Borrow::<$generic>::borrow(&container.get()).clone();)*
+                    $(let $generic = core::borrow::Borrow::<$generic>::borrow(&container.get()).clone();)*
                     let fut = self( $( $generic ),* );
                     Box::pin(fut)
                 })
             }
+
+            fn dependencies() -> Vec<&'static str> {
+                vec![$(core::any::type_name::<$generic>()),*]
+            }
         }
 
         impl<Func, Input, Output, $($generic),*> Injectable<Input, Output, ($($generic,)*)> for Asyncify<Func>
@@ -235,11 +417,63 @@ macro_rules! impl_into_di {
             fn inject<'a>(&'a self, container: &'a Input) -> CompiledFn<'a, Output> {
                 let Asyncify(this) = self;
                 Arc::new(move || {
-                    $(let $generic = std::borrow::Borrow::<$generic>::borrow(&container.get()).clone();)*
+                    $(let $generic = core::borrow::Borrow::<$generic>::borrow(&container.get()).clone();)*
                     let out = this( $( $generic ),* );
                     Box::pin(ready(out))
                 })
             }
+
+            fn dependencies() -> Vec<&'static str> {
+                vec![$(core::any::type_name::<$generic>()),*]
+            }
+        }
+
+        impl<Func, Input, Output, Fut, $($generic),*> Injectable<Input, Output, ($($generic,)*)> for WithInput<Func>
+        where
+            Input: $(DependencySupplier<$generic> +)*,
+            Input: Send + Sync,
+            Func: Fn(&Input, $($generic),*) -> Fut + Send + Sync + 'static,
+            Fut: Future<Output = Output> + Send + 'static,
+            $($generic: Clone + Send + Sync),*
+        {
+            #[allow(non_snake_case)]
+            #[allow(unused_variables)]
+            fn inject<'a>(&'a self, container: &'a Input) -> CompiledFn<'a, Output> {
+                let WithInput(this) = self;
+                Arc::new(move || {
+                    $(let $generic = core::borrow::Borrow::<$generic>::borrow(&container.get()).clone();)*
+                    let fut = this(container, $( $generic ),* );
+                    Box::pin(fut)
+                })
+            }
+
+            fn dependencies() -> Vec<&'static str> {
+                vec![$(core::any::type_name::<$generic>()),*]
+            }
+        }
+
+        impl<Func, Input, Output, $($generic),*> Injectable<Input, Output, ($($generic,)*)> for Asyncify<WithInput<Func>>
+        where
+            Input: $(DependencySupplier<$generic> +)*,
+            Input: Send + Sync,
+            Func: Fn(&Input, $($generic),*) -> Output + Send + Sync + 'static,
+            Output: Send + 'static,
+            $($generic: Clone + Send + Sync),*
+        {
+            #[allow(non_snake_case)]
+            #[allow(unused_variables)]
+            fn inject<'a>(&'a self, container: &'a Input) -> CompiledFn<'a, Output> {
+                let Asyncify(WithInput(this)) = self;
+                Arc::new(move || {
+                    $(let $generic = core::borrow::Borrow::<$generic>::borrow(&container.get()).clone();)*
+                    let out = this(container, $( $generic ),* );
+                    Box::pin(ready(out))
+                })
+            }
+
+            fn dependencies() -> Vec<&'static str> {
+                vec![$(core::any::type_name::<$generic>()),*]
+            }
         }
     };
 }
@@ -272,16 +506,134 @@ impl_into_di!(A, B, C, D, E, F, G, H, I);
 /// assert!(str == "abc");
 /// assert!(b == true);
 /// ```
+///
+/// A dependency can also be registered as a trait object by writing
+/// `dep as dyn Trait`. The value is then stored as `Arc<dyn Trait>`, so
+/// handlers can request it as `Arc<dyn Trait>` without knowing its concrete
+/// type. Since dependencies must be `Send + Sync`, the trait object must be
+/// too: either make `Send + Sync` supertraits of `Trait` or write
+/// `dep as dyn Trait + Send + Sync`. The dependency before `as` must be either
+/// an identifier or a parenthesized expression.
+///
+/// ```
+/// # #[tokio::main]
+/// # async fn main() {
+/// use dptree::prelude::*;
+/// use std::{ops::ControlFlow, sync::Arc};
+///
+/// trait Storage: Send + Sync {
+///     fn get(&self) -> i32;
+/// }
+///
+/// struct InMemoryStorage(i32);
+///
+/// impl Storage for InMemoryStorage {
+///     fn get(&self) -> i32 {
+///         self.0
+///     }
+/// }
+///
+/// let storage = InMemoryStorage(42);
+/// let deps = dptree::deps!["config", storage as dyn Storage];
+///
+/// let handler: Handler<_, i32> =
+///     dptree::endpoint(|storage: Arc<dyn Storage>| async move { storage.get() });
+///
+/// assert_eq!(handler.dispatch(deps).await, ControlFlow::Break(42));
+/// # }
+/// ```
 #[macro_export]
 macro_rules! deps {
-    ($($dep:expr),*) => {
+    (@insert $map:ident;) => {};
+    (@insert $map:ident; $dep:tt as dyn $($rest:tt)*) => {
+        $crate::deps!(@insert_dyn $map; $dep; []; $($rest)*)
+    };
+    (@insert $map:ident; $dep:expr $(, $($rest:tt)*)?) => {
+        $map.insert($dep);
+        $crate::deps!(@insert $map; $($($rest)*)?);
+    };
+
+    // Accumulates the trait object bounds up to the next comma.
+    (@insert_dyn $map:ident; $dep:tt; [$($bounds:tt)+]; $(, $($rest:tt)*)?) => {
+        $map.insert($crate::di::__private::Arc::new($dep) as $crate::di::__private::Arc<dyn $($bounds)+>);
+        $crate::deps!(@insert $map; $($($rest)*)?);
+    };
+    (@insert_dyn $map:ident; $dep:tt; [$($bounds:tt)*]; $next:tt $($rest:tt)*) => {
+        $crate::deps!(@insert_dyn $map; $dep; [$($bounds)* $next]; $($rest)*)
+    };
+
+    ($($deps:tt)*) => {
         {
             // In the case if this macro receives zero arguments.
             #[allow(unused_mut)]
             let mut map = $crate::di::DependencyMap::new();
-            $(map.insert($dep);)*
+            $crate::deps!(@insert map; $($deps)*);
             map
         }
+    };
+}
+
+#[doc(hidden)]
+pub mod __private {
+    pub use alloc::sync::Arc;
+}
+
+/// A nameable function that can be stored in a DI container.
+///
+/// Closure types cannot be named, so they cannot be requested from a
+/// container. Wrap a closure into [`NamedFn`] to register it as a dependency,
+/// e.g., to inject a strategy into a handler.
+///
+/// # Examples
+///
+/// ```
+/// # #[tokio::main]
+/// # async fn main() {
+/// use dptree::{di::NamedFn, prelude::*};
+///
+/// let shout = NamedFn::new(|s: String| s.to_uppercase());
+///
+/// let handler: Handler<_, _> =
+///     dptree::endpoint(|text: String, transform: NamedFn<String, String>| async move {
+///         transform.call(text)
+///     });
+///
+/// assert_eq!(
+///     handler.dispatch(dptree::deps!["hello".to_owned(), shout]).await,
+///     ControlFlow::Break("HELLO".to_owned())
+/// );
+/// # }
+/// ```
+pub struct NamedFn<I, O> {
+    f: Arc<dyn Fn(I) -> O + Send + Sync>,
+}
+
+impl<I, O> NamedFn<I, O> {
+    /// Wraps `f` into [`NamedFn`].
+    pub fn new<F>(f: F) -> Self
+    where
+        F: Fn(I) -> O + Send + Sync + 'static,
+    {
+        Self { f: Arc::new(f) }
+    }
+
+    /// Calls the wrapped function.
+    pub fn call(&self, input: I) -> O {
+        (self.f)(input)
+    }
+}
+
+// `#[derive(Clone)]` obligates all type parameters to satisfy `Clone` as well,
+// but we do not need it here because of `Arc`.
+impl<I, O> Clone for NamedFn<I, O> {
+    fn clone(&self) -> Self {
+        Self { f: Arc::clone(&self.f) }
+    }
+}
+
+impl<I, O> Debug for NamedFn<I, O> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("NamedFn").finish_non_exhaustive()
     }
 }
 
@@ -297,6 +649,62 @@ impl<T: Send + Sync + 'static> Insert<T> for DependencyMap {
     }
 }
 
+/// Insert some shared value to a container without wrapping it again.
+pub trait InsertArc<Value> {
+    /// Inserts `value` into itself, returning the previous value, if exists.
+    fn insert_arc(&mut self, value: Arc<Value>) -> Option<Arc<Value>>;
+}
+
+impl<T: Send + Sync + 'static> InsertArc<T> for DependencyMap {
+    fn insert_arc(&mut self, value: Arc<T>) -> Option<Arc<T>> {
+        DependencyMap::insert_arc(self, value)
+    }
+}
+
+/// Insert several values of different types into a container at once.
+///
+/// Implemented for tuples of up to nine values for every container
+/// implementing [`Insert`] for each of their types.
+pub trait InsertMany<Values> {
+    /// Inserts each element of `values` into itself, from left to right.
+    ///
+    /// Each element replaces the previous value of its type, so if several
+    /// elements have the same type, the rightmost one remains.
+    fn insert_many(&mut self, values: Values);
+
+    /// Returns the type names of the elements of `Values`, from left to right.
+    fn type_names() -> Vec<&'static str>;
+}
+
+macro_rules! impl_insert_many {
+    ($($generic:ident),*) => {
+        impl<Container, $($generic),*> InsertMany<($($generic,)*)> for Container
+        where
+            $(Container: Insert<$generic>,)*
+        {
+            #[allow(non_snake_case)]
+            fn insert_many(&mut self, values: ($($generic,)*)) {
+                let ($($generic,)*) = values;
+                $(Insert::<$generic>::insert(self, $generic);)*
+            }
+
+            fn type_names() -> Vec<&'static str> {
+                vec![$(core::any::type_name::<$generic>()),*]
+            }
+        }
+    };
+}
+
+impl_insert_many!(A);
+impl_insert_many!(A, B);
+impl_insert_many!(A, B, C);
+impl_insert_many!(A, B, C, D);
+impl_insert_many!(A, B, C, D, E);
+impl_insert_many!(A, B, C, D, E, F);
+impl_insert_many!(A, B, C, D, E, F, G);
+impl_insert_many!(A, B, C, D, E, F, G, H);
+impl_insert_many!(A, B, C, D, E, F, G, H, I);
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -312,4 +720,104 @@ mod tests {
         assert_eq!(map.get(), Arc::new("hello world"));
         assert_eq!(map.get(), Arc::new(true));
     }
+
+    #[test]
+    fn with() {
+        let map = DependencyMap::new().with(1i32).with("abc").with(2i32);
+
+        assert_eq!(map.get(), Arc::new(2i32));
+        assert_eq!(map.get(), Arc::new("abc"));
+    }
+
+    #[test]
+    fn eq() {
+        assert_eq!(deps![1i32, "abc"], deps!["def", 2i32]);
+        assert_ne!(deps![1i32], deps![1i32, "abc"]);
+        assert_ne!(deps![1i32, "abc"], deps![1i32]);
+        assert_ne!(deps![], deps![1i32]);
+    }
+
+    #[test]
+    fn named_fn() {
+        let map = deps![NamedFn::new(|x: i32| x + 1), NamedFn::new(|x: i32| x.to_string())];
+
+        let inc: Arc<NamedFn<i32, i32>> = map.get();
+        let to_string: Arc<NamedFn<i32, String>> = map.get();
+
+        assert_eq!(inc.call(1), 2);
+        assert_eq!(to_string.call(1), "1");
+    }
+
+    #[test]
+    fn deps_trait_objects() {
+        trait Named {
+            fn name(&self) -> String;
+        }
+
+        struct Fixed;
+
+        impl Named for Fixed {
+            fn name(&self) -> String {
+                "fixed".to_owned()
+            }
+        }
+
+        let fixed = Fixed;
+        let map = deps![
+            1i32,
+            fixed as dyn Named + Send + Sync,
+            (|x: i32| x * 2) as dyn Fn(i32) -> i32 + Send + Sync,
+            true,
+        ];
+
+        let named: Arc<Arc<dyn Named + Send + Sync>> = map.get();
+        let double: Arc<Arc<dyn Fn(i32) -> i32 + Send + Sync>> = map.get();
+
+        assert_eq!(named.name(), "fixed");
+        assert_eq!(double(21), 42);
+        assert_eq!(map.get(), Arc::new(1i32));
+        assert_eq!(map.get(), Arc::new(true));
+        assert!(!map.contains::<Fixed>());
+    }
+
+    #[test]
+    fn try_get() {
+        let map = deps![42i32, "hello world"];
+
+        assert_eq!(map.try_get::<i32>(), Ok(Arc::new(42)));
+
+        let error = map.try_get::<bool>().unwrap_err();
+        assert_eq!(error.requested, "bool");
+        assert_eq!(error.available.len(), 2);
+        assert!(error.available.contains(&"i32"));
+        assert!(error.available.contains(&"&str"));
+        assert!(error.to_string().starts_with("bool was requested, but not provided."));
+    }
+
+    #[test]
+    fn insert_lazy() {
+        let mut map = deps![1i32];
+        map.insert_lazy(|map: &DependencyMap| {
+            let x: Arc<i32> = map.get();
+            i64::from(*x) + 1
+        });
+
+        assert!(map.contains::<i64>());
+        assert_eq!(map.get(), Arc::new(2i64));
+        map.insert(5i32);
+        assert_eq!(map.get(), Arc::new(6i64));
+
+        // Lazy values are not computed when replaced or removed.
+        assert_eq!(map.insert(0i64), None);
+        map.insert_lazy(|_: &DependencyMap| 1i64);
+        assert_eq!(map.remove::<i64>(), None);
+        assert!(!map.contains::<i64>());
+    }
+
+    #[test]
+    #[should_panic(expected = "bool was requested, but not provided. Available types:\n    i32\n")]
+    fn get_missing() {
+        let map = deps![42i32];
+        let _: Arc<bool> = map.get();
+    }
 }
@@ -0,0 +1,7 @@
+//! Guides on using `dptree`.
+//!
+//! Each guide is a module that contains no items, only documentation that
+//! walks through a complete example. All examples are compiled and tested as
+//! doctests, so they always reflect the current API.
+
+pub mod command_router;
@@ -0,0 +1,184 @@
+//! Building a typed command router.
+//!
+//! This guide shows how to build a router for text commands, such as the ones
+//! received by a chat bot. The router:
+//!
+//!  - parses incoming text into a `Command` enumeration and dispatches on its
+//!    variants with [`case!`];
+//!  - injects a database handle into the endpoints that need it;
+//!  - falls back to a catch-all endpoint if the text is not a command or the
+//!    command fails.
+//!
+//! ## Commands
+//!
+//! First, define the commands and a function parsing them. Parsing is a
+//! fallible projection, so [`filter_map`] fits it perfectly: if the text is
+//! not a command, the router will try the next branch.
+//!
+//! ```
+//! #[derive(Debug, Clone, PartialEq)]
+//! struct Key(String);
+//!
+//! #[derive(Debug, Clone, PartialEq)]
+//! enum Command {
+//!     Get(Key),
+//!     Set(Key, i64),
+//!     Help,
+//! }
+//!
+//! impl Command {
+//!     fn parse(text: &str) -> Option<Self> {
+//!         let mut words = text.split_whitespace();
+//!
+//!         match (words.next()?, words.next(), words.next()) {
+//!             ("/get", Some(key), None) => Some(Self::Get(Key(key.to_owned()))),
+//!             ("/set", Some(key), Some(value)) => {
+//!                 Some(Self::Set(Key(key.to_owned()), value.parse().ok()?))
+//!             }
+//!             ("/help", None, None) => Some(Self::Help),
+//!             _ => None,
+//!         }
+//!     }
+//! }
+//!
+//! assert_eq!(Command::parse("/set x 1"), Some(Command::Set(Key("x".to_owned()), 1)));
+//! assert_eq!(Command::parse("/set x y"), None);
+//! ```
+//!
+//! ## The database
+//!
+//! Endpoints should not depend on a concrete database, so we put it behind a
+//! trait. A trait object is registered in a container with the `as dyn`
+//! syntax of [`deps!`] and requested as `Arc<dyn Database>`.
+//!
+//! Note that each value in the container is identified by its type. Prefer
+//! dedicated types (such as `Command` and `Key` above) to plain `String`s,
+//! unless the meaning of a string is unambiguous: [`case!`] adds the fields of
+//! a variant to the container, so `Command::Get(String)` would replace the
+//! incoming text with the key. In this guide, `String` is always the incoming
+//! text.
+//!
+//! ## The router
+//!
+//! Now we can put everything together. Every endpoint returns a
+//! `Result<String, DbError>`, and [`Handler::short_circuit_on`] turns a
+//! failed command into [`ControlFlow::Continue`], so the fallback branch gets
+//! a chance to reply.
+//!
+//! ```
+//! # #[derive(Debug, Clone, PartialEq)]
+//! # struct Key(String);
+//! #
+//! # #[derive(Debug, Clone, PartialEq)]
+//! # enum Command {
+//! #     Get(Key),
+//! #     Set(Key, i64),
+//! #     Help,
+//! # }
+//! #
+//! # impl Command {
+//! #     fn parse(text: &str) -> Option<Self> {
+//! #         let mut words = text.split_whitespace();
+//! #
+//! #         match (words.next()?, words.next(), words.next()) {
+//! #             ("/get", Some(key), None) => Some(Self::Get(Key(key.to_owned()))),
+//! #             ("/set", Some(key), Some(value)) => Some(Self::Set(Key(key.to_owned()), value.parse().ok()?)),
+//! #             ("/help", None, None) => Some(Self::Help),
+//! #             _ => None,
+//! #         }
+//! #     }
+//! # }
+//! #
+//! use dptree::prelude::*;
+//! use std::{
+//!     collections::HashMap,
+//!     sync::{Arc, Mutex},
+//! };
+//!
+//! #[derive(Debug)]
+//! enum DbError {
+//!     NotFound,
+//! }
+//!
+//! trait Database: Send + Sync {
+//!     fn get(&self, key: &str) -> Result<i64, DbError>;
+//!     fn set(&self, key: String, value: i64);
+//! }
+//!
+//! #[derive(Default)]
+//! struct InMemoryDatabase(Mutex<HashMap<String, i64>>);
+//!
+//! impl Database for InMemoryDatabase {
+//!     fn get(&self, key: &str) -> Result<i64, DbError> {
+//!         self.0.lock().unwrap().get(key).copied().ok_or(DbError::NotFound)
+//!     }
+//!
+//!     fn set(&self, key: String, value: i64) {
+//!         self.0.lock().unwrap().insert(key, value);
+//!     }
+//! }
+//!
+//! type Reply = Result<String, DbError>;
+//!
+//! fn router() -> Handler<'static, DependencyMap, Reply> {
+//!     let commands = dptree::filter_map(|text: String| Command::parse(&text))
+//!         .branch(dptree::case![Command::Get(key)].endpoint(
+//!             |Key(key): Key, db: Arc<dyn Database>| async move {
+//!                 Ok(format!("{} = {}", key, db.get(&key)?))
+//!             },
+//!         ))
+//!         .branch(dptree::case![Command::Set(key, value)].endpoint(
+//!             |(Key(key), value): (Key, i64), db: Arc<dyn Database>| async move {
+//!                 db.set(key, value);
+//!                 Ok("Done.".to_owned())
+//!             },
+//!         ))
+//!         .branch(
+//!             dptree::case![Command::Help]
+//!                 .endpoint(|| async { Ok("Commands: /get <key>, /set <key> <value>.".to_owned()) }),
+//!         );
+//!
+//!     dptree::entry().branch(commands.short_circuit_on(Result::is_ok)).branch(dptree::endpoint(
+//!         |text: String| async move { Ok(format!("Cannot handle {:?}, try /help.", text)) },
+//!     ))
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() {
+//! let router = router();
+//! let db = InMemoryDatabase::default();
+//! let deps = dptree::deps![db as dyn Database];
+//!
+//! let reply = |text: &str| {
+//!     let mut deps = deps.clone();
+//!     deps.insert(text.to_owned());
+//!
+//!     let router = router.clone();
+//!     async move {
+//!         match router.dispatch(deps).await {
+//!             ControlFlow::Break(reply) => reply.unwrap(),
+//!             ControlFlow::Continue(_) => unreachable!("the fallback always breaks"),
+//!         }
+//!     }
+//! };
+//!
+//! assert_eq!(reply("/set x 42").await, "Done.");
+//! assert_eq!(reply("/get x").await, "x = 42");
+//! assert_eq!(reply("/get y").await, "Cannot handle \"/get y\", try /help.");
+//! assert_eq!(reply("hello").await, "Cannot handle \"hello\", try /help.");
+//! # }
+//! ```
+//!
+//! ## Checking dependencies
+//!
+//! If you build the router with the [`description::Tree`] description, you
+//! can check in a test that the endpoints get all their dependencies with
+//! [`Handler::validate_deps`], instead of panicking at runtime.
+//!
+//! [`case!`]: crate::case
+//! [`filter_map`]: crate::filter_map
+//! [`deps!`]: crate::deps
+//! [`Handler::short_circuit_on`]: crate::Handler::short_circuit_on
+//! [`Handler::validate_deps`]: crate::Handler::validate_deps
+//! [`description::Tree`]: crate::description::Tree
+//! [`ControlFlow::Continue`]: core::ops::ControlFlow::Continue
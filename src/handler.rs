@@ -1,16 +1,102 @@
+mod adapt;
+mod builder;
+#[cfg(feature = "std")]
+mod cache;
+mod cancel;
+#[cfg(feature = "std")]
+mod clock;
+mod combine;
+mod config;
 mod core;
+mod count;
+mod ctx;
+#[cfg(feature = "std")]
+mod debounce;
+mod deps;
 pub mod description;
+mod dispatch;
+#[cfg(feature = "std")]
+mod downcast;
+mod either;
 mod endpoint;
 mod filter;
 mod filter_map;
+#[cfg(feature = "std")]
+mod filter_trace;
+mod hooks;
 mod inspect;
+mod layer;
+mod mailbox;
 mod map;
 mod methods;
+mod once;
+mod ops;
+mod pipe;
+#[cfg(feature = "std")]
+mod provide;
+#[cfg(feature = "std")]
+mod rate_limit;
+#[cfg(feature = "std")]
+mod recover;
+#[cfg(feature = "std")]
+mod recursion;
+mod redispatch;
+mod result;
+#[cfg(feature = "std")]
+mod route;
+mod scope;
+#[cfg(feature = "tokio")]
+mod semaphore;
+mod short_circuit;
+#[cfg(feature = "tokio")]
+mod spawn;
+#[cfg(feature = "std")]
+mod stateful;
+#[cfg(feature = "testing")]
+mod testing;
+#[cfg(feature = "tokio")]
+mod timeout;
+mod trace;
+mod tracker;
 
 pub use self::core::*;
+pub use builder::*;
+#[cfg(feature = "std")]
+pub use cache::*;
+pub use cancel::*;
+#[cfg(feature = "std")]
+pub use clock::*;
+pub use combine::*;
+pub use config::*;
+pub use ctx::*;
+#[cfg(feature = "std")]
+pub use debounce::*;
+pub use deps::*;
 pub use description::HandlerDescription;
+#[cfg(feature = "std")]
+pub use downcast::*;
+pub use either::*;
 pub use endpoint::*;
 pub use filter::*;
 pub use filter_map::*;
+#[cfg(feature = "std")]
+pub use filter_trace::*;
 pub use inspect::*;
+pub use layer::*;
+pub use mailbox::*;
 pub use map::*;
+pub use pipe::*;
+#[cfg(feature = "std")]
+pub use provide::*;
+#[cfg(feature = "std")]
+pub use rate_limit::*;
+#[cfg(feature = "std")]
+pub use recursion::*;
+pub use redispatch::*;
+pub use result::*;
+#[cfg(feature = "std")]
+pub use stateful::*;
+#[cfg(feature = "tokio")]
+pub use timeout::*;
+pub use trace::*;
+pub use tracker::*;
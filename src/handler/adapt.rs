@@ -0,0 +1,124 @@
+use crate::{from_fn_with_description, Handler, HandlerDescription};
+use alloc::sync::Arc;
+use core::ops::ControlFlow;
+
+impl<'a, Input, Output, Descr> Handler<'a, Input, Output, Descr>
+where
+    Input: Send + 'a,
+    Output: 'a,
+    Descr: HandlerDescription,
+{
+    /// Returns a handler that adapts its input with `f` to the input of this
+    /// handler.
+    ///
+    /// If `f` returns `Ok(input)`, this handler is dispatched with `input`,
+    /// and the returned handler breaks with its output. If `f` returns
+    /// `Err(outer)`, this handler is skipped and the returned handler returns
+    /// [`ControlFlow::Continue`] with `outer`, which is expected to be the
+    /// original input given back by `f`. If this handler continues, the
+    /// returned handler continues with a copy of the original input.
+    ///
+    /// Since the input types differ, the returned handler is
+    /// [terminal](crate::Handler#terminal-handlers).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use dptree::prelude::*;
+    ///
+    /// #[derive(Debug, Clone, PartialEq)]
+    /// enum Update {
+    ///     Message(String),
+    ///     Ping,
+    /// }
+    ///
+    /// let messages: Handler<String, String> =
+    ///     dptree::from_fn(
+    ///         |text: String, _cont| async move { ControlFlow::Break(text.to_uppercase()) },
+    ///     );
+    ///
+    /// let handler: Handler<Update, String> = dptree::entry()
+    ///     .branch(messages.try_map_input(|update: Update| match update {
+    ///         Update::Message(text) => Ok(text),
+    ///         update => Err(update),
+    ///     }))
+    ///     .branch(dptree::from_fn(|_, _cont| async { ControlFlow::Break("pong".to_owned()) }));
+    ///
+    /// assert_eq!(
+    ///     handler.dispatch(Update::Message("hi".to_owned())).await,
+    ///     ControlFlow::Break("HI".to_owned())
+    /// );
+    /// assert_eq!(handler.dispatch(Update::Ping).await, ControlFlow::Break("pong".to_owned()));
+    /// # }
+    /// ```
+    #[must_use]
+    #[track_caller]
+    pub fn try_map_input<Outer, F>(self, f: F) -> Handler<'a, Outer, Output, Descr>
+    where
+        Outer: Clone + Send + 'a,
+        F: Fn(Outer) -> Result<Input, Outer> + Send + Sync + 'a,
+    {
+        let description = self.wrapper_description();
+        let f = Arc::new(f);
+
+        from_fn_with_description(description, move |outer: Outer, _cont| {
+            let this = self.clone();
+            let f = Arc::clone(&f);
+
+            async move {
+                match f(outer.clone()) {
+                    Ok(input) => match this.dispatch(input).await {
+                        ControlFlow::Break(output) => ControlFlow::Break(output),
+                        ControlFlow::Continue(_) => ControlFlow::Continue(outer),
+                    },
+                    Err(outer) => ControlFlow::Continue(outer),
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{deps, di::DependencyMap};
+
+    #[tokio::test]
+    async fn test_try_map_input() {
+        let numbers: Handler<'static, DependencyMap, String> =
+            crate::filter(|x: i32| x >= 0).endpoint(|x: i32| async move { format!("number {x}") });
+
+        let handler: Handler<'static, &'static str, String> = crate::entry()
+            .branch(numbers.try_map_input(|s: &'static str| match s.parse::<i32>() {
+                Ok(x) => Ok(deps![x]),
+                Err(_) => Err(s),
+            }))
+            .branch(crate::from_fn(|s: &'static str, _cont| async move {
+                ControlFlow::Break(format!("text {s}"))
+            }));
+
+        // The projection succeeds, and the subtree breaks.
+        assert_eq!(handler.dispatch("42").await, ControlFlow::Break("number 42".to_owned()));
+        // The projection succeeds, but the subtree continues.
+        assert_eq!(handler.dispatch("-1").await, ControlFlow::Break("text -1".to_owned()));
+        // The projection fails.
+        assert_eq!(handler.dispatch("abc").await, ControlFlow::Break("text abc".to_owned()));
+    }
+
+    #[tokio::test]
+    async fn test_try_map_input_continue() {
+        let handler: Handler<'static, (i32, bool), ()> =
+            crate::endpoint(|| async {}).try_map_input(|(x, flag): (i32, bool)| {
+                if flag {
+                    Ok(deps![x])
+                } else {
+                    Err((x, flag))
+                }
+            });
+
+        assert_eq!(handler.dispatch((1, true)).await, ControlFlow::Break(()));
+        assert_eq!(handler.dispatch((1, false)).await, ControlFlow::Continue((1, false)));
+    }
+}
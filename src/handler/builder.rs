@@ -0,0 +1,134 @@
+use crate::{description, Handler, HandlerDescription};
+
+/// A builder of a handler with a catch-all branch.
+///
+/// A common mistake is to place a catch-all branch before the specific ones,
+/// making them unreachable. [`HandlerBuilder`] accepts specific branches and
+/// exactly one default branch in any order, and always places the default
+/// branch last when building the handler. [`HandlerBuilder::build`] is
+/// available only after the default branch is set, and the default branch
+/// cannot be set twice.
+///
+/// # Examples
+///
+/// ```
+/// # #[tokio::main]
+/// # async fn main() {
+/// use dptree::{prelude::*, HandlerBuilder};
+///
+/// let handler: Handler<_, _> = HandlerBuilder::new()
+///     .default(dptree::endpoint(|| async { "unknown" }))
+///     .branch(dptree::filter(|x: i32| x > 0).endpoint(|| async { "positive" }))
+///     .branch(dptree::filter(|x: i32| x < 0).endpoint(|| async { "negative" }))
+///     .build();
+///
+/// assert_eq!(handler.dispatch(dptree::deps![1]).await, ControlFlow::Break("positive"));
+/// assert_eq!(handler.dispatch(dptree::deps![-1]).await, ControlFlow::Break("negative"));
+/// assert_eq!(handler.dispatch(dptree::deps![0]).await, ControlFlow::Break("unknown"));
+/// # }
+/// ```
+///
+/// A handler cannot be built without the default branch:
+///
+/// ```compile_fail
+/// use dptree::{prelude::*, HandlerBuilder};
+///
+/// let handler: Handler<DependencyMap, ()> =
+///     HandlerBuilder::new().branch(dptree::endpoint(|| async {})).build();
+/// ```
+#[must_use]
+pub struct HandlerBuilder<'a, Input, Output, Descr = description::Unspecified, Default = ()> {
+    branches: Handler<'a, Input, Output, Descr>,
+    default: Default,
+}
+
+impl<'a, Input, Output, Descr> HandlerBuilder<'a, Input, Output, Descr>
+where
+    Input: Send + 'a,
+    Output: 'a,
+    Descr: HandlerDescription,
+{
+    /// Constructs a builder without branches.
+    #[allow(clippy::new_without_default)]
+    #[track_caller]
+    pub fn new() -> Self {
+        Self { branches: crate::entry(), default: () }
+    }
+
+    /// Sets the default branch, which is tried after all the other branches.
+    #[track_caller]
+    pub fn default(
+        self,
+        default: Handler<'a, Input, Output, Descr>,
+    ) -> HandlerBuilder<'a, Input, Output, Descr, Handler<'a, Input, Output, Descr>> {
+        HandlerBuilder { branches: self.branches, default }
+    }
+}
+
+impl<'a, Input, Output, Descr, Default> HandlerBuilder<'a, Input, Output, Descr, Default>
+where
+    Input: Send + 'a,
+    Output: Send + 'a,
+    Descr: HandlerDescription,
+{
+    /// Adds a specific branch, which is tried after the previously added ones.
+    #[track_caller]
+    pub fn branch(self, next: Handler<'a, Input, Output, Descr>) -> Self {
+        Self { branches: self.branches.branch(next), default: self.default }
+    }
+}
+
+impl<'a, Input, Output, Descr>
+    HandlerBuilder<'a, Input, Output, Descr, Handler<'a, Input, Output, Descr>>
+where
+    Input: Send + 'a,
+    Output: Send + 'a,
+    Descr: HandlerDescription,
+{
+    /// Builds the handler with the default branch placed last.
+    #[must_use]
+    #[track_caller]
+    pub fn build(self) -> Handler<'a, Input, Output, Descr> {
+        self.branches.branch(self.default)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ops::ControlFlow;
+
+    use super::*;
+    use crate::{deps, di::DependencyMap};
+
+    fn specific(x: i32) -> Handler<'static, DependencyMap, &'static str> {
+        crate::filter(move |y: i32| x == y).endpoint(|| async { "specific" })
+    }
+
+    #[tokio::test]
+    async fn test_default_last() {
+        let default_first = HandlerBuilder::new()
+            .default(crate::endpoint(|| async { "default" }))
+            .branch(specific(1))
+            .branch(specific(2))
+            .build();
+        let default_in_the_middle = HandlerBuilder::new()
+            .branch(specific(1))
+            .default(crate::endpoint(|| async { "default" }))
+            .branch(specific(2))
+            .build();
+
+        for handler in [default_first, default_in_the_middle] {
+            assert_eq!(handler.dispatch(deps![1]).await, ControlFlow::Break("specific"));
+            assert_eq!(handler.dispatch(deps![2]).await, ControlFlow::Break("specific"));
+            assert_eq!(handler.dispatch(deps![3]).await, ControlFlow::Break("default"));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_without_specific_branches() {
+        let handler: Handler<DependencyMap, _> =
+            HandlerBuilder::new().default(crate::endpoint(|| async { "default" })).build();
+
+        assert_eq!(handler.dispatch(deps![]).await, ControlFlow::Break("default"));
+    }
+}
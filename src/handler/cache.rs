@@ -0,0 +1,670 @@
+use crate::{
+    di::{Asyncify, Injectable, Insert},
+    from_fn_with_description, Clock, Handler, HandlerDescription,
+};
+use std::{
+    collections::HashMap,
+    hash::Hash,
+    ops::ControlFlow,
+    sync::{Arc, Mutex, PoisonError},
+    time::{Duration, Instant},
+};
+
+/// A cache of projection results shared by handlers.
+///
+/// Cloning a cache is cheap and yields a handle to the same storage, so you can
+/// keep a clone to inspect or clear the cache while a handler uses it. See
+/// [`filter_map_cached`].
+pub struct Cache<Key, Value> {
+    entries: Arc<Mutex<HashMap<Key, Entry<Value>>>>,
+}
+
+struct Entry<Value> {
+    value: Option<Value>,
+    // The time of caching, as told by the clock of `filter_map_cached_ttl`, or
+    // `None` for entries that do not expire.
+    cached_at: Option<Instant>,
+}
+
+impl<Key, Value> Cache<Key, Value>
+where
+    Key: Hash + Eq,
+    Value: Clone,
+{
+    /// Constructs an empty cache.
+    pub fn new() -> Self {
+        Self { entries: Arc::new(Mutex::new(HashMap::new())) }
+    }
+
+    /// Returns the cached result for `key`, if any.
+    ///
+    /// The outer [`Option`] tells whether there is an entry, the inner one is
+    /// the cached result itself.
+    pub fn get(&self, key: &Key) -> Option<Option<Value>> {
+        self.lock().get(key).map(|entry| entry.value.clone())
+    }
+
+    /// Caches `value` for `key`, replacing the previous entry.
+    ///
+    /// The entry does not expire, even if the cache is used by
+    /// [`filter_map_cached_ttl`].
+    pub fn insert(&self, key: Key, value: Option<Value>) {
+        self.lock().insert(key, Entry { value, cached_at: None });
+    }
+
+    /// Removes the entry for `key`, so that the next lookup recomputes it.
+    pub fn remove(&self, key: &Key) {
+        self.lock().remove(key);
+    }
+
+    /// Removes all entries.
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+
+    /// Returns the number of entries.
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Returns `true` if the cache has no entries.
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    // Returns the cached result for `key` unless it has been cached `ttl` or
+    // more before `now`.
+    fn get_fresh(&self, key: &Key, now: Instant, ttl: Duration) -> Option<Option<Value>> {
+        self.lock()
+            .get(key)
+            .filter(|entry| !matches!(entry.cached_at, Some(at) if now.duration_since(at) >= ttl))
+            .map(|entry| entry.value.clone())
+    }
+
+    fn insert_at(&self, key: Key, value: Option<Value>, now: Instant) {
+        self.lock().insert(key, Entry { value, cached_at: Some(now) });
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<Key, Entry<Value>>> {
+        self.entries.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl<Key, Value> Clone for Cache<Key, Value> {
+    fn clone(&self) -> Self {
+        Self { entries: Arc::clone(&self.entries) }
+    }
+}
+
+impl<Key, Value> Default for Cache<Key, Value>
+where
+    Key: Hash + Eq,
+    Value: Clone,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Constructs a [`filter_map`](crate::filter_map) whose results are cached by
+/// a key derived from the input.
+///
+/// On every execution, `key` is computed from the input. If `cache` has an
+/// entry for this key, the cached result is used; otherwise, `proj` is called
+/// and its result is stored in `cache`. A result of `None` is cached as well,
+/// so an input whose key once failed to project keeps being filtered out
+/// without calling `proj` again, until the entry is removed from `cache`.
+///
+/// The cache is not locked while `proj` is running, so concurrent executions
+/// with the same uncached key may call `proj` more than once.
+///
+/// # Examples
+///
+/// ```
+/// # #[tokio::main]
+/// # async fn main() {
+/// use dptree::{di::DependencySupplier, prelude::*, Cache};
+///
+/// let cache = Cache::new();
+/// let handler: Handler<_, _> = dptree::filter_map_cached(
+///     |deps: &DependencyMap| -> u32 { *deps.get() },
+///     |user_id: u32| Some(format!("user #{user_id}")),
+///     cache.clone(),
+/// )
+/// .endpoint(|name: String| async move { name });
+///
+/// assert_eq!(
+///     handler.dispatch(dptree::deps![7u32]).await,
+///     ControlFlow::Break("user #7".to_owned())
+/// );
+/// assert_eq!(cache.get(&7), Some(Some("user #7".to_owned())));
+/// # }
+/// ```
+#[must_use]
+#[track_caller]
+pub fn filter_map_cached<'a, KeyFn, Projection, Key, Input, Output, NewType, Args, Descr>(
+    key: KeyFn,
+    proj: Projection,
+    cache: Cache<Key, NewType>,
+) -> Handler<'a, Input, Output, Descr>
+where
+    KeyFn: Fn(&Input) -> Key + Send + Sync + 'a,
+    Asyncify<Projection>: Injectable<Input, Option<NewType>, Args> + Send + Sync + 'a,
+    Key: Hash + Eq + Send + 'a,
+    Input: Insert<NewType> + Clone + Send + 'a,
+    Output: 'a,
+    NewType: Clone + Send + 'a,
+    Descr: HandlerDescription,
+{
+    let required =
+        <Asyncify<Projection> as Injectable<Input, Option<NewType>, Args>>::dependencies();
+    let provided = [core::any::type_name::<NewType>()];
+    filter_map_cached_with_description(
+        Descr::filter_map().with_dependencies(&required, &provided),
+        key,
+        Asyncify(proj),
+        cache,
+        None,
+    )
+}
+
+/// The asynchronous version of [`filter_map_cached`].
+#[must_use]
+#[track_caller]
+pub fn filter_map_cached_async<'a, KeyFn, Projection, Key, Input, Output, NewType, Args, Descr>(
+    key: KeyFn,
+    proj: Projection,
+    cache: Cache<Key, NewType>,
+) -> Handler<'a, Input, Output, Descr>
+where
+    KeyFn: Fn(&Input) -> Key + Send + Sync + 'a,
+    Projection: Injectable<Input, Option<NewType>, Args> + Send + Sync + 'a,
+    Key: Hash + Eq + Send + 'a,
+    Input: Insert<NewType> + Clone + Send + 'a,
+    Output: 'a,
+    NewType: Clone + Send + 'a,
+    Descr: HandlerDescription,
+{
+    let required = <Projection as Injectable<Input, Option<NewType>, Args>>::dependencies();
+    let provided = [core::any::type_name::<NewType>()];
+    filter_map_cached_with_description(
+        Descr::filter_map_async().with_dependencies(&required, &provided),
+        key,
+        proj,
+        cache,
+        None,
+    )
+}
+
+/// [`filter_map_cached`] whose entries expire after `ttl`.
+///
+/// An entry cached by this handler is reused for `ttl` after it has been
+/// cached, as told by `clock`; after that, `proj` is called again and the
+/// entry is replaced. This applies to cached `None`s as well. Entries inserted
+/// by [`Cache::insert`] do not expire.
+///
+/// # Examples
+///
+/// ```
+/// # #[tokio::main]
+/// # async fn main() {
+/// use dptree::{di::DependencySupplier, prelude::*, Cache, SystemClock};
+/// use std::time::Duration;
+///
+/// let handler: Handler<_, _> = dptree::filter_map_cached_ttl(
+///     |deps: &DependencyMap| -> u32 { *deps.get() },
+///     |user_id: u32| Some(format!("user #{user_id}")),
+///     Duration::from_secs(60),
+///     SystemClock,
+///     Cache::new(),
+/// )
+/// .endpoint(|name: String| async move { name });
+///
+/// assert_eq!(
+///     handler.dispatch(dptree::deps![7u32]).await,
+///     ControlFlow::Break("user #7".to_owned())
+/// );
+/// # }
+/// ```
+#[must_use]
+#[track_caller]
+pub fn filter_map_cached_ttl<'a, KeyFn, Projection, Key, C, Input, Output, NewType, Args, Descr>(
+    key: KeyFn,
+    proj: Projection,
+    ttl: Duration,
+    clock: C,
+    cache: Cache<Key, NewType>,
+) -> Handler<'a, Input, Output, Descr>
+where
+    KeyFn: Fn(&Input) -> Key + Send + Sync + 'a,
+    Asyncify<Projection>: Injectable<Input, Option<NewType>, Args> + Send + Sync + 'a,
+    Key: Hash + Eq + Send + 'a,
+    C: Clock + 'static,
+    Input: Insert<NewType> + Clone + Send + 'a,
+    Output: 'a,
+    NewType: Clone + Send + 'a,
+    Descr: HandlerDescription,
+{
+    let required =
+        <Asyncify<Projection> as Injectable<Input, Option<NewType>, Args>>::dependencies();
+    let provided = [core::any::type_name::<NewType>()];
+    filter_map_cached_with_description(
+        Descr::filter_map().with_dependencies(&required, &provided),
+        key,
+        Asyncify(proj),
+        cache,
+        Some((ttl, Arc::new(clock))),
+    )
+}
+
+/// The asynchronous version of [`filter_map_cached_ttl`].
+#[must_use]
+#[track_caller]
+pub fn filter_map_cached_ttl_async<
+    'a,
+    KeyFn,
+    Projection,
+    Key,
+    C,
+    Input,
+    Output,
+    NewType,
+    Args,
+    Descr,
+>(
+    key: KeyFn,
+    proj: Projection,
+    ttl: Duration,
+    clock: C,
+    cache: Cache<Key, NewType>,
+) -> Handler<'a, Input, Output, Descr>
+where
+    KeyFn: Fn(&Input) -> Key + Send + Sync + 'a,
+    Projection: Injectable<Input, Option<NewType>, Args> + Send + Sync + 'a,
+    Key: Hash + Eq + Send + 'a,
+    C: Clock + 'static,
+    Input: Insert<NewType> + Clone + Send + 'a,
+    Output: 'a,
+    NewType: Clone + Send + 'a,
+    Descr: HandlerDescription,
+{
+    let required = <Projection as Injectable<Input, Option<NewType>, Args>>::dependencies();
+    let provided = [core::any::type_name::<NewType>()];
+    filter_map_cached_with_description(
+        Descr::filter_map_async().with_dependencies(&required, &provided),
+        key,
+        proj,
+        cache,
+        Some((ttl, Arc::new(clock))),
+    )
+}
+
+fn filter_map_cached_with_description<
+    'a,
+    KeyFn,
+    Projection,
+    Key,
+    Input,
+    Output,
+    NewType,
+    Args,
+    Descr,
+>(
+    description: Descr,
+    key: KeyFn,
+    proj: Projection,
+    cache: Cache<Key, NewType>,
+    ttl: Option<(Duration, Arc<dyn Clock>)>,
+) -> Handler<'a, Input, Output, Descr>
+where
+    KeyFn: Fn(&Input) -> Key + Send + Sync + 'a,
+    Projection: Injectable<Input, Option<NewType>, Args> + Send + Sync + 'a,
+    Key: Hash + Eq + Send + 'a,
+    Input: Insert<NewType> + Clone + Send + 'a,
+    Output: 'a,
+    NewType: Clone + Send + 'a,
+{
+    let proj = Arc::new(proj);
+
+    from_fn_with_description(description, move |container: Input, cont| {
+        let proj = Arc::clone(&proj);
+        let cache = cache.clone();
+        let key = key(&container);
+        let ttl = ttl.as_ref().map(|(ttl, clock)| (*ttl, clock.now()));
+
+        async move {
+            let cached = match ttl {
+                Some((ttl, now)) => cache.get_fresh(&key, now, ttl),
+                None => cache.get(&key),
+            };
+            let res = match cached {
+                Some(res) => res,
+                None => {
+                    let proj = proj.inject(&container);
+                    let res = proj().await;
+                    core::mem::drop(proj);
+
+                    match ttl {
+                        Some((_, now)) => cache.insert_at(key, res.clone(), now),
+                        None => cache.insert(key, res.clone()),
+                    }
+                    res
+                }
+            };
+
+            match res {
+                Some(new_type) => {
+                    let mut intermediate = container.clone();
+                    intermediate.insert(new_type);
+                    match cont(intermediate).await {
+                        ControlFlow::Continue(_) => ControlFlow::Continue(container),
+                        ControlFlow::Break(result) => ControlFlow::Break(result),
+                    }
+                }
+                None => ControlFlow::Continue(container),
+            }
+        }
+    })
+}
+
+/// Constructs a [`filter_map`](crate::filter_map) that calls `proj` only on
+/// the first execution.
+///
+/// The result of the first call of `proj` is stored, and all executions,
+/// including the first one, use this result: if it is `Some(v)`, a clone of
+/// `v` is added to the container; if it is `None`, the handler always returns
+/// [`ControlFlow::Continue`]. The state is shared by all clones of the
+/// handler.
+///
+/// Concurrent executions that arrive while `proj` is running wait for its
+/// result, so `proj` is called exactly once.
+///
+/// # Examples
+///
+/// ```
+/// # #[tokio::main]
+/// # async fn main() {
+/// use dptree::prelude::*;
+///
+/// let handler: Handler<_, _> =
+///     dptree::filter_map_once_async(|| async { Some(String::from("connection")) })
+///         .endpoint(|conn: String, x: i32| async move { format!("{conn} {x}") });
+///
+/// assert_eq!(
+///     handler.dispatch(dptree::deps![1]).await,
+///     ControlFlow::Break("connection 1".to_owned())
+/// );
+/// assert_eq!(
+///     handler.dispatch(dptree::deps![2]).await,
+///     ControlFlow::Break("connection 2".to_owned())
+/// );
+/// # }
+/// ```
+#[must_use]
+#[track_caller]
+pub fn filter_map_once<'a, Projection, Input, Output, NewType, Args, Descr>(
+    proj: Projection,
+) -> Handler<'a, Input, Output, Descr>
+where
+    Asyncify<Projection>: Injectable<Input, Option<NewType>, Args> + Send + Sync + 'a,
+    Input: Insert<NewType> + Clone + Send + 'a,
+    Output: 'a,
+    NewType: Clone + Send + 'a,
+    Descr: HandlerDescription,
+{
+    let required =
+        <Asyncify<Projection> as Injectable<Input, Option<NewType>, Args>>::dependencies();
+    let provided = [core::any::type_name::<NewType>()];
+    filter_map_once_with_description(
+        Descr::filter_map().with_dependencies(&required, &provided),
+        Asyncify(proj),
+    )
+}
+
+/// The asynchronous version of [`filter_map_once`].
+#[must_use]
+#[track_caller]
+pub fn filter_map_once_async<'a, Projection, Input, Output, NewType, Args, Descr>(
+    proj: Projection,
+) -> Handler<'a, Input, Output, Descr>
+where
+    Projection: Injectable<Input, Option<NewType>, Args> + Send + Sync + 'a,
+    Input: Insert<NewType> + Clone + Send + 'a,
+    Output: 'a,
+    NewType: Clone + Send + 'a,
+    Descr: HandlerDescription,
+{
+    let required = <Projection as Injectable<Input, Option<NewType>, Args>>::dependencies();
+    let provided = [core::any::type_name::<NewType>()];
+    filter_map_once_with_description(
+        Descr::filter_map_async().with_dependencies(&required, &provided),
+        proj,
+    )
+}
+
+fn filter_map_once_with_description<'a, Projection, Input, Output, NewType, Args, Descr>(
+    description: Descr,
+    proj: Projection,
+) -> Handler<'a, Input, Output, Descr>
+where
+    Projection: Injectable<Input, Option<NewType>, Args> + Send + Sync + 'a,
+    Input: Insert<NewType> + Clone + Send + 'a,
+    Output: 'a,
+    NewType: Clone + Send + 'a,
+{
+    let proj = Arc::new(proj);
+    let result = Arc::new(futures::lock::Mutex::new(None));
+
+    from_fn_with_description(description, move |container: Input, cont| {
+        let proj = Arc::clone(&proj);
+        let result = Arc::clone(&result);
+
+        async move {
+            let res = {
+                // The lock is held while `proj` is running, so that concurrent
+                // executions wait for the first result instead of computing
+                // their own.
+                let mut result = result.lock().await;
+                match &*result {
+                    Some(res) => Option::clone(res),
+                    None => {
+                        let proj = proj.inject(&container);
+                        let res = proj().await;
+                        core::mem::drop(proj);
+
+                        *result = Some(res.clone());
+                        res
+                    }
+                }
+            };
+
+            match res {
+                Some(new_type) => {
+                    let mut intermediate = container.clone();
+                    intermediate.insert(new_type);
+                    match cont(intermediate).await {
+                        ControlFlow::Continue(_) => ControlFlow::Continue(container),
+                        ControlFlow::Break(result) => ControlFlow::Break(result),
+                    }
+                }
+                None => ControlFlow::Continue(container),
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+    use crate::{
+        deps,
+        di::{DependencyMap, DependencySupplier},
+        help_inference,
+    };
+
+    fn user_id(deps: &DependencyMap) -> u32 {
+        *deps.get()
+    }
+
+    #[tokio::test]
+    async fn test_filter_map_cached() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let cache = Cache::new();
+
+        let handler = help_inference(filter_map_cached(
+            user_id,
+            {
+                let calls = Arc::clone(&calls);
+                move |id: u32| {
+                    calls.fetch_add(1, Ordering::Relaxed);
+                    Some(id * 100)
+                }
+            },
+            cache.clone(),
+        ))
+        .endpoint(|x: u32| async move { x });
+
+        assert_eq!(handler.dispatch(deps![1u32]).await, ControlFlow::Break(100));
+        assert_eq!(handler.dispatch(deps![1u32]).await, ControlFlow::Break(100));
+        assert_eq!(handler.dispatch(deps![2u32]).await, ControlFlow::Break(200));
+        assert_eq!(handler.dispatch(deps![1u32]).await, ControlFlow::Break(100));
+        assert_eq!(calls.load(Ordering::Relaxed), 2);
+        assert_eq!(cache.len(), 2);
+
+        cache.remove(&1);
+        assert_eq!(handler.dispatch(deps![1u32]).await, ControlFlow::Break(100));
+        assert_eq!(calls.load(Ordering::Relaxed), 3);
+    }
+
+    #[tokio::test]
+    async fn test_filter_map_cached_none() {
+        let calls = Arc::new(AtomicUsize::new(0));
+
+        let handler = help_inference(filter_map_cached_async(
+            user_id,
+            {
+                let calls = Arc::clone(&calls);
+                move |id: u32| {
+                    calls.fetch_add(1, Ordering::Relaxed);
+                    async move { Some(id).filter(|&id| id != 0) }
+                }
+            },
+            Cache::new(),
+        ))
+        .endpoint(|x: u32| async move { x });
+
+        assert_eq!(handler.dispatch(deps![0u32]).await, ControlFlow::Continue(deps![0u32]));
+        assert_eq!(handler.dispatch(deps![0u32]).await, ControlFlow::Continue(deps![0u32]));
+        assert_eq!(calls.load(Ordering::Relaxed), 1);
+    }
+
+    #[tokio::test]
+    async fn test_filter_map_cached_ttl() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let clock = crate::ManualClock::new();
+
+        let handler = help_inference(filter_map_cached_ttl(
+            user_id,
+            {
+                let calls = Arc::clone(&calls);
+                move |id: u32| Some(id * 100 + calls.fetch_add(1, Ordering::Relaxed) as u32)
+            },
+            Duration::from_secs(10),
+            clock.clone(),
+            Cache::new(),
+        ))
+        .endpoint(|x: u32| async move { x });
+
+        // Reused within the TTL.
+        assert_eq!(handler.dispatch(deps![1u32]).await, ControlFlow::Break(100));
+        clock.advance(Duration::from_secs(9));
+        assert_eq!(handler.dispatch(deps![1u32]).await, ControlFlow::Break(100));
+        assert_eq!(calls.load(Ordering::Relaxed), 1);
+
+        // Recomputed after the TTL has elapsed, and reused again afterwards.
+        clock.advance(Duration::from_secs(1));
+        assert_eq!(handler.dispatch(deps![1u32]).await, ControlFlow::Break(101));
+        clock.advance(Duration::from_secs(5));
+        assert_eq!(handler.dispatch(deps![1u32]).await, ControlFlow::Break(101));
+        assert_eq!(calls.load(Ordering::Relaxed), 2);
+    }
+
+    #[tokio::test]
+    async fn test_filter_map_cached_ttl_async() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let clock = crate::ManualClock::new();
+        let cache = Cache::new();
+        cache.insert(2, Some(0));
+
+        let handler = help_inference(filter_map_cached_ttl_async(
+            user_id,
+            {
+                let calls = Arc::clone(&calls);
+                move |id: u32| {
+                    calls.fetch_add(1, Ordering::Relaxed);
+                    async move { Some(id).filter(|&id| id != 0) }
+                }
+            },
+            Duration::from_secs(10),
+            clock.clone(),
+            cache.clone(),
+        ))
+        .endpoint(|x: u32| async move { x });
+
+        // `None`s expire as well.
+        assert_eq!(handler.dispatch(deps![0u32]).await, ControlFlow::Continue(deps![0u32]));
+        assert_eq!(handler.dispatch(deps![0u32]).await, ControlFlow::Continue(deps![0u32]));
+        assert_eq!(calls.load(Ordering::Relaxed), 1);
+        clock.advance(Duration::from_secs(10));
+        assert_eq!(handler.dispatch(deps![0u32]).await, ControlFlow::Continue(deps![0u32]));
+        assert_eq!(calls.load(Ordering::Relaxed), 2);
+
+        // An entry inserted into the cache directly does not expire.
+        clock.advance(Duration::from_secs(100));
+        assert_eq!(handler.dispatch(deps![2u32]).await, ControlFlow::Break(0));
+        assert_eq!(calls.load(Ordering::Relaxed), 2);
+    }
+
+    #[tokio::test]
+    async fn test_filter_map_once() {
+        let calls = Arc::new(AtomicUsize::new(0));
+
+        let handler = help_inference(filter_map_once_async({
+            let calls = Arc::clone(&calls);
+            move || {
+                calls.fetch_add(1, Ordering::SeqCst);
+                async {
+                    tokio::task::yield_now().await;
+                    Some(42u64)
+                }
+            }
+        }))
+        .endpoint(|value: u64, x: i32| async move { value + x as u64 });
+
+        for x in 0..3 {
+            assert_eq!(handler.dispatch(deps![x]).await, ControlFlow::Break(42 + x as u64));
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_filter_map_once_concurrent() {
+        let calls = Arc::new(AtomicUsize::new(0));
+
+        let handler = help_inference(filter_map_once({
+            let calls = Arc::clone(&calls);
+            move || {
+                calls.fetch_add(1, Ordering::SeqCst);
+                None::<u64>
+            }
+        }))
+        .endpoint(|| async {});
+
+        let results = futures::future::join_all((0..3).map(|x| handler.dispatch(deps![x]))).await;
+
+        assert!(results.iter().all(ControlFlow::is_continue));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+}
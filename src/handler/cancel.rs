@@ -0,0 +1,151 @@
+use crate::{di::DependencySupplier, from_fn_with_description, Handler, HandlerDescription};
+use alloc::sync::Arc;
+use core::{
+    ops::ControlFlow,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+/// A signal that tells handlers to stop, e.g., on a graceful shutdown.
+///
+/// See [`Handler::cancel_on`].
+pub trait CancelSignal {
+    /// Returns `true` if the signal has fired.
+    fn is_cancelled(&self) -> bool;
+}
+
+impl CancelSignal for AtomicBool {
+    fn is_cancelled(&self) -> bool {
+        self.load(Ordering::SeqCst)
+    }
+}
+
+impl<S> CancelSignal for Arc<S>
+where
+    S: CancelSignal + ?Sized,
+{
+    fn is_cancelled(&self) -> bool {
+        (**self).is_cancelled()
+    }
+}
+
+impl<'a, Input, Output, Descr> Handler<'a, Input, Output, Descr>
+where
+    Input: Send + 'a,
+    Output: 'a,
+    Descr: HandlerDescription,
+{
+    /// Returns a handler that breaks with `output` instead of executing this
+    /// handler once the signal of type `Signal` has fired.
+    ///
+    /// Every time the execution reaches the returned handler, the signal is
+    /// taken from the input, as any other dependency. If it has fired, the
+    /// returned handler breaks with a copy of `output`, e.g., a "shutting
+    /// down" reply; otherwise, this handler is executed as usual.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use dptree::prelude::*;
+    /// use std::sync::{
+    ///     atomic::{AtomicBool, Ordering},
+    ///     Arc,
+    /// };
+    ///
+    /// let shutdown = Arc::new(AtomicBool::new(false));
+    ///
+    /// let handler: Handler<_, _> =
+    ///     dptree::endpoint(|| async { "handled" }).cancel_on::<Arc<AtomicBool>>("shutting down");
+    ///
+    /// let deps = dptree::deps![Arc::clone(&shutdown)];
+    /// assert_eq!(handler.dispatch(deps.clone()).await, ControlFlow::Break("handled"));
+    ///
+    /// shutdown.store(true, Ordering::SeqCst);
+    /// assert_eq!(handler.dispatch(deps).await, ControlFlow::Break("shutting down"));
+    /// # }
+    /// ```
+    #[must_use]
+    #[track_caller]
+    pub fn cancel_on<Signal>(self, output: Output) -> Self
+    where
+        Signal: CancelSignal,
+        Input: DependencySupplier<Signal>,
+        Output: Clone + Send + Sync,
+    {
+        let description = Descr::user_defined()
+            .with_dependencies(&[core::any::type_name::<Signal>()], &[])
+            .merge_chain(self.description());
+
+        from_fn_with_description(description, move |event: Input, cont| {
+            let this = self.clone();
+            let output = output.clone();
+            let signal: Arc<Signal> = event.get();
+            let cancelled = signal.is_cancelled();
+
+            async move {
+                if cancelled {
+                    ControlFlow::Break(output)
+                } else {
+                    this.execute(event, cont).await
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicUsize;
+
+    use super::*;
+    use crate::{deps, di::DependencyMap, help_inference};
+
+    #[tokio::test]
+    async fn test_cancel_on() {
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let handled = Arc::new(AtomicUsize::new(0));
+
+        let handler = help_inference(crate::entry())
+            .branch(
+                crate::filter(|x: i32| x > 0)
+                    .endpoint({
+                        let handled = Arc::clone(&handled);
+                        move || {
+                            handled.fetch_add(1, Ordering::SeqCst);
+                            async { "handled" }
+                        }
+                    })
+                    .cancel_on::<Arc<AtomicBool>>("shutting down"),
+            )
+            .branch(crate::endpoint(|| async { "fallback" }));
+
+        let deps = deps![Arc::clone(&shutdown)];
+        assert_eq!(handler.dispatch(deps.clone().with(1)).await, ControlFlow::Break("handled"));
+        assert_eq!(handler.dispatch(deps.clone().with(0)).await, ControlFlow::Break("fallback"));
+
+        shutdown.store(true, Ordering::SeqCst);
+
+        // The subtree is skipped regardless of the input.
+        assert_eq!(
+            handler.dispatch(deps.clone().with(1)).await,
+            ControlFlow::Break("shutting down")
+        );
+        assert_eq!(handler.dispatch(deps.with(0)).await, ControlFlow::Break("shutting down"));
+        assert_eq!(handled.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_cancel_on_description() {
+        use crate::description::Tree;
+
+        let handler: Handler<DependencyMap, (), Tree> =
+            crate::endpoint(|| async {}).cancel_on::<Arc<AtomicBool>>(());
+
+        assert_eq!(
+            handler.validate_deps(&deps![]).unwrap_err()[0].type_name,
+            core::any::type_name::<Arc<AtomicBool>>()
+        );
+        assert_eq!(handler.validate_deps(&deps![Arc::new(AtomicBool::new(false))]), Ok(()));
+    }
+}
@@ -0,0 +1,334 @@
+use crate::{from_fn_with_description, Handler, HandlerDescription};
+use alloc::{string::String, vec::Vec};
+use core::ops::ControlFlow;
+use futures::{FutureExt, StreamExt};
+
+/// A type whose values can be combined into one.
+///
+/// `combine` must be associative, and `empty` must be its identity: combining
+/// `empty()` with a value (on either side) must return this value.
+///
+/// See [`branch_all`].
+pub trait Combine {
+    /// Returns the identity of [`Combine::combine`].
+    fn empty() -> Self;
+
+    /// Combines two values into one.
+    fn combine(self, other: Self) -> Self;
+}
+
+impl<T> Combine for Vec<T> {
+    fn empty() -> Self {
+        Vec::new()
+    }
+
+    fn combine(mut self, other: Self) -> Self {
+        self.extend(other);
+        self
+    }
+}
+
+impl Combine for String {
+    fn empty() -> Self {
+        String::new()
+    }
+
+    fn combine(mut self, other: Self) -> Self {
+        self.push_str(&other);
+        self
+    }
+}
+
+impl Combine for () {
+    fn empty() -> Self {}
+
+    fn combine(self, _other: Self) -> Self {}
+}
+
+// Integers are added with saturation, so that combining never overflows.
+// Saturating addition of signed integers is associative only as long as no
+// intermediate sum saturates.
+macro_rules! impl_combine_for_integers {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl Combine for $ty {
+                fn empty() -> Self {
+                    0
+                }
+
+                fn combine(self, other: Self) -> Self {
+                    self.saturating_add(other)
+                }
+            }
+        )*
+    };
+}
+
+impl_combine_for_integers!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+
+macro_rules! impl_combine_for_floats {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl Combine for $ty {
+                fn empty() -> Self {
+                    0.0
+                }
+
+                fn combine(self, other: Self) -> Self {
+                    self + other
+                }
+            }
+        )*
+    };
+}
+
+impl_combine_for_floats!(f32, f64);
+
+/// Constructs a handler that executes all of `handlers` and combines their
+/// outputs.
+///
+/// Each handler is dispatched, in order, with a copy of the input. The outputs
+/// of all handlers that break are combined with [`Combine::combine`], starting
+/// from [`Combine::empty`]. If all handlers continue, the returned handler
+/// returns [`ControlFlow::Continue`] with the original input.
+///
+/// Since `handlers` play the role of the rest of the chain, the returned
+/// handler is [terminal](crate::Handler#terminal-handlers).
+///
+/// # Examples
+///
+/// ```
+/// # #[tokio::main]
+/// # async fn main() {
+/// use dptree::prelude::*;
+///
+/// let handler: Handler<_, u32> = dptree::branch_all([
+///     dptree::filter(|x: u32| x % 2 == 0).endpoint(|| async { 1 }),
+///     dptree::filter(|x: u32| x % 3 == 0).endpoint(|| async { 10 }),
+///     dptree::filter(|x: u32| x % 5 == 0).endpoint(|| async { 100 }),
+/// ]);
+///
+/// assert_eq!(handler.dispatch(dptree::deps![6u32]).await, ControlFlow::Break(11));
+/// assert_eq!(handler.dispatch(dptree::deps![30u32]).await, ControlFlow::Break(111));
+/// assert!(matches!(handler.dispatch(dptree::deps![7u32]).await, ControlFlow::Continue(_)));
+/// # }
+/// ```
+#[must_use]
+#[track_caller]
+pub fn branch_all<'a, Handlers, Input, Output, Descr>(
+    handlers: Handlers,
+) -> Handler<'a, Input, Output, Descr>
+where
+    Handlers: IntoIterator<Item = Handler<'a, Input, Output, Descr>>,
+    Input: Clone + Send + 'a,
+    Output: Combine + Send + 'a,
+    Descr: HandlerDescription,
+{
+    let handlers: Vec<_> = handlers.into_iter().collect();
+    let description = handlers.iter().fold(Descr::entry(), |description, handler| {
+        description.merge_branch(handler.description())
+    });
+
+    from_fn_with_description(description, move |container: Input, _cont| {
+        let handlers = handlers.clone();
+
+        async move {
+            let mut combined = None;
+
+            for handler in handlers {
+                if let ControlFlow::Break(output) = handler.dispatch(container.clone()).await {
+                    combined = Some(combined.unwrap_or_else(Output::empty).combine(output));
+                }
+            }
+
+            match combined {
+                Some(output) => ControlFlow::Break(output),
+                None => ControlFlow::Continue(container),
+            }
+        }
+    })
+}
+
+/// The concurrent version of [`branch_all`].
+///
+/// At most `limit` of `handlers` are executed at the same time. The outputs
+/// are combined in the order of `handlers`, not in the order of completion,
+/// since [`Combine::combine`] need not be commutative. As with
+/// [`Handler::dispatch_all`], the handlers are polled by the current task
+/// rather than spawned.
+///
+/// # Panics
+///
+/// Panics if `limit` is zero.
+///
+/// # Examples
+///
+/// ```
+/// # #[tokio::main]
+/// # async fn main() {
+/// use dptree::prelude::*;
+///
+/// let handler: Handler<_, Vec<&str>> = dptree::par_branch_all(
+///     [
+///         dptree::endpoint(|| async {
+///             tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+///             vec!["slow"]
+///         }),
+///         dptree::endpoint(|| async { vec!["fast"] }),
+///     ],
+///     2,
+/// );
+///
+/// assert_eq!(handler.dispatch(dptree::deps![]).await, ControlFlow::Break(vec!["slow", "fast"]));
+/// # }
+/// ```
+#[must_use]
+#[track_caller]
+pub fn par_branch_all<'a, Handlers, Input, Output, Descr>(
+    handlers: Handlers,
+    limit: usize,
+) -> Handler<'a, Input, Output, Descr>
+where
+    Handlers: IntoIterator<Item = Handler<'a, Input, Output, Descr>>,
+    Input: Clone + Send + 'a,
+    Output: Combine + Send + 'a,
+    Descr: HandlerDescription,
+{
+    assert!(limit > 0, "the concurrency limit must be positive");
+
+    let handlers: Vec<_> = handlers.into_iter().collect();
+    let description = handlers.iter().fold(Descr::entry(), |description, handler| {
+        description.merge_branch(handler.description())
+    });
+
+    from_fn_with_description(description, move |container: Input, _cont| {
+        let handlers = handlers.clone();
+
+        async move {
+            let dispatches: Vec<_> = handlers
+                .into_iter()
+                .map(|handler| {
+                    let input = container.clone();
+                    async move { handler.dispatch(input).await }.boxed()
+                })
+                .collect();
+            let results: Vec<_> = futures::stream::iter(dispatches).buffered(limit).collect().await;
+
+            let combined = results.into_iter().fold(None, |combined, result| match result {
+                ControlFlow::Break(output) => {
+                    Some(combined.unwrap_or_else(Output::empty).combine(output))
+                }
+                ControlFlow::Continue(_) => combined,
+            });
+
+            match combined {
+                Some(output) => ControlFlow::Break(output),
+                None => ControlFlow::Continue(container),
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{deps, di::DependencyMap};
+
+    #[tokio::test]
+    async fn test_branch_all_strings() {
+        let handler: Handler<DependencyMap, String> = branch_all(vec![
+            crate::endpoint(|x: i32| async move { x.to_string() }),
+            crate::filter(|x: i32| x < 0).endpoint(|| async { "-".to_owned() }),
+            crate::endpoint(|| async { "!".to_owned() }),
+        ]);
+
+        assert_eq!(handler.dispatch(deps![1]).await, ControlFlow::Break("1!".to_owned()));
+        assert_eq!(handler.dispatch(deps![-1]).await, ControlFlow::Break("-1-!".to_owned()));
+    }
+
+    #[tokio::test]
+    async fn test_branch_all_sum() {
+        let handler: Handler<DependencyMap, i64> = branch_all(vec![
+            crate::endpoint(|x: i32| async move { x as i64 }),
+            crate::endpoint(|x: i32| async move { x as i64 * 10 }),
+            crate::filter(|x: i32| x > 100).endpoint(|| async { 1000 }),
+        ]);
+
+        assert_eq!(handler.dispatch(deps![2]).await, ControlFlow::Break(22));
+        assert_eq!(handler.dispatch(deps![101]).await, ControlFlow::Break(2111));
+    }
+
+    #[tokio::test]
+    async fn test_branch_all_sum_saturates() {
+        let handler: Handler<DependencyMap, u8> = branch_all(vec![
+            crate::endpoint(|| async { 200u8 }),
+            crate::endpoint(|| async { 100u8 }),
+        ]);
+
+        assert_eq!(handler.dispatch(deps![]).await, ControlFlow::Break(u8::MAX));
+    }
+
+    #[tokio::test]
+    async fn test_branch_all_continue() {
+        let handler: Handler<DependencyMap, Vec<i32>> = branch_all(vec![
+            crate::filter(|x: i32| x > 0).endpoint(|x: i32| async move { vec![x] }),
+            crate::filter(|x: i32| x > 1).endpoint(|x: i32| async move { vec![x, x] }),
+        ]);
+
+        assert_eq!(handler.dispatch(deps![2]).await, ControlFlow::Break(vec![2, 2, 2]));
+        assert_eq!(handler.dispatch(deps![0]).await, ControlFlow::Continue(deps![0]));
+    }
+
+    #[tokio::test]
+    async fn test_branch_all_empty() {
+        let handler: Handler<DependencyMap, ()> = branch_all(Vec::new());
+
+        assert_eq!(handler.dispatch(deps![0]).await, ControlFlow::Continue(deps![0]));
+    }
+
+    #[tokio::test]
+    async fn test_par_branch_all() {
+        use std::sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        };
+
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+
+        let branch = |x: u64| -> Handler<DependencyMap, Vec<u64>> {
+            let in_flight = Arc::clone(&in_flight);
+            let peak = Arc::clone(&peak);
+            crate::endpoint(move || {
+                let in_flight = Arc::clone(&in_flight);
+                let peak = Arc::clone(&peak);
+                async move {
+                    let current = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                    peak.fetch_max(current, Ordering::SeqCst);
+                    tokio::time::sleep(std::time::Duration::from_millis(10 * (6 - x))).await;
+                    in_flight.fetch_sub(1, Ordering::SeqCst);
+                    vec![x]
+                }
+            })
+        };
+
+        let handler = par_branch_all((0..6).map(branch).collect::<Vec<_>>(), 2);
+
+        assert_eq!(handler.dispatch(deps![]).await, ControlFlow::Break(vec![0, 1, 2, 3, 4, 5]));
+        assert_eq!(peak.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_par_branch_all_continue() {
+        let handler: Handler<DependencyMap, i32> = par_branch_all(
+            vec![
+                crate::filter(|x: i32| x > 0).endpoint(|| async { 1 }),
+                crate::filter(|x: i32| x > 1).endpoint(|| async { 2 }),
+            ],
+            4,
+        );
+
+        assert_eq!(handler.dispatch(deps![1]).await, ControlFlow::Break(1));
+        assert_eq!(handler.dispatch(deps![0]).await, ControlFlow::Continue(deps![0]));
+    }
+}
@@ -0,0 +1,385 @@
+use crate::{di::DependencyMap, from_fn_with_description, Handler, HandlerDescription};
+use alloc::{string::String, sync::Arc, vec::Vec};
+use core::{
+    fmt::{self, Debug, Display},
+    ops::{ControlFlow, Deref},
+    str::FromStr,
+};
+
+/// A source of configuration values, such as environment variables.
+pub trait ConfigProvider: Send + Sync + 'static {
+    /// Returns the raw value of `key`, or `None` if it is not set.
+    fn get(&self, key: &str) -> Option<String>;
+}
+
+impl<F> ConfigProvider for F
+where
+    F: Fn(&str) -> Option<String> + Send + Sync + 'static,
+{
+    fn get(&self, key: &str) -> Option<String> {
+        self(key)
+    }
+}
+
+/// A typed configuration key.
+///
+/// A key is a type naming a value of a [`ConfigProvider`] and the type this
+/// value is parsed into.
+///
+/// # Examples
+///
+/// ```
+/// use dptree::ConfigKey;
+///
+/// struct Port;
+///
+/// impl ConfigKey for Port {
+///     type Value = u16;
+///     const NAME: &'static str = "PORT";
+/// }
+/// ```
+pub trait ConfigKey: 'static {
+    /// The type of the value.
+    type Value: FromStr + Send + Sync + 'static;
+
+    /// The name of the key in a [`ConfigProvider`].
+    const NAME: &'static str;
+}
+
+/// The value of the configuration key `K`.
+///
+/// Handlers inject it once `K` is declared with [`Configs::key`] and the
+/// [`Configs`] are added to the container with [`provider`].
+pub struct Config<K: ConfigKey>(pub K::Value);
+
+impl<K: ConfigKey> Deref for Config<K> {
+    type Target = K::Value;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+// `#[derive(Clone)]` obligates `K` to satisfy `Clone` as well.
+impl<K> Clone for Config<K>
+where
+    K: ConfigKey,
+    K::Value: Clone,
+{
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<K> Debug for Config<K>
+where
+    K: ConfigKey,
+    K::Value: Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Config").field(&K::NAME).field(&self.0).finish()
+    }
+}
+
+/// An error resolving a configuration key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigError {
+    /// The key is not set.
+    Missing {
+        /// The name of the key.
+        key: &'static str,
+    },
+    /// The value of the key cannot be parsed.
+    Invalid {
+        /// The name of the key.
+        key: &'static str,
+        /// The raw value of the key.
+        value: String,
+    },
+}
+
+impl Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Missing { key } => write!(f, "the configuration key `{key}` is not set"),
+            Self::Invalid { key, value } => {
+                write!(f, "the configuration key `{key}` has an invalid value `{value}`")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ConfigError {}
+
+/// A [`ConfigProvider`] along with the configuration keys it resolves.
+///
+/// [`provider`] adds it to the container, so that handlers can inject
+/// [`Config<K>`] for each key `K` declared with [`Configs::key`].
+#[derive(Clone)]
+pub struct Configs {
+    provider: Arc<dyn ConfigProvider>,
+    keys: Vec<Key>,
+}
+
+/// A key declared with [`Configs::key`].
+#[derive(Clone, Copy)]
+struct Key {
+    type_name: &'static str,
+    insert: fn(&Configs, &mut DependencyMap),
+}
+
+impl Configs {
+    /// Wraps `provider`, with no keys declared.
+    #[must_use]
+    pub fn new<P: ConfigProvider>(provider: P) -> Self {
+        Self { provider: Arc::new(provider), keys: Vec::new() }
+    }
+
+    /// Declares `K`, so that handlers can inject [`Config<K>`].
+    ///
+    /// `K` is resolved each time [`Config<K>`] is injected; if it is not set
+    /// or cannot be parsed, the injection panics with a [`ConfigError`], like
+    /// for any other missing dependency.
+    #[must_use]
+    pub fn key<K: ConfigKey>(mut self) -> Self {
+        fn insert<K: ConfigKey>(configs: &Configs, container: &mut DependencyMap) {
+            let configs = configs.clone();
+            container.insert_lazy(move |_: &DependencyMap| match configs.try_get::<K>() {
+                Ok(value) => Config::<K>(value),
+                Err(error) => panic!("{}", error),
+            });
+        }
+
+        self.keys.push(Key { type_name: core::any::type_name::<Config<K>>(), insert: insert::<K> });
+        self
+    }
+
+    /// Resolves `K`.
+    ///
+    /// Returns `None` if `K` is not set or cannot be parsed into
+    /// [`ConfigKey::Value`].
+    #[must_use]
+    pub fn get<K: ConfigKey>(&self) -> Option<K::Value> {
+        self.try_get::<K>().ok()
+    }
+
+    /// Resolves `K`, or returns the reason it cannot be resolved.
+    pub fn try_get<K: ConfigKey>(&self) -> Result<K::Value, ConfigError> {
+        let value = self.provider.get(K::NAME).ok_or(ConfigError::Missing { key: K::NAME })?;
+        value.parse().map_err(|_| ConfigError::Invalid { key: K::NAME, value })
+    }
+}
+
+impl<P: ConfigProvider> From<P> for Configs {
+    fn from(provider: P) -> Self {
+        Self::new(provider)
+    }
+}
+
+impl Debug for Configs {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let keys: Vec<_> = self.keys.iter().map(|key| key.type_name).collect();
+        f.debug_struct("Configs").field("keys", &keys).finish_non_exhaustive()
+    }
+}
+
+/// A [`ConfigProvider`] reading environment variables.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct EnvProvider;
+
+#[cfg(feature = "std")]
+impl ConfigProvider for EnvProvider {
+    fn get(&self, key: &str) -> Option<String> {
+        std::env::var(key).ok()
+    }
+}
+
+/// Constructs a handler that adds `configs` to the container.
+///
+/// Besides [`Configs`] itself, the container receives [`Config<K>`] for each
+/// key `K` declared with [`Configs::key`]. No keys are resolved at this point:
+/// each of them is resolved through the provider when [`Config<K>`] is
+/// injected, so handlers not injecting it do not require it to be set.
+///
+/// # Examples
+///
+/// ```
+/// # #[tokio::main]
+/// # async fn main() {
+/// use dptree::{prelude::*, Config, ConfigKey, ConfigProvider, Configs};
+///
+/// struct Vars;
+///
+/// impl ConfigProvider for Vars {
+///     fn get(&self, key: &str) -> Option<String> {
+///         (key == "PORT").then(|| "8080".to_owned())
+///     }
+/// }
+///
+/// struct Port;
+///
+/// impl ConfigKey for Port {
+///     type Value = u16;
+///     const NAME: &'static str = "PORT";
+/// }
+///
+/// let handler: Handler<_, _> = dptree::provider(Configs::new(Vars).key::<Port>())
+///     .endpoint(|port: Config<Port>| async move { *port + 1 });
+///
+/// assert_eq!(handler.dispatch(dptree::deps![]).await, ControlFlow::Break(8081));
+/// # }
+/// ```
+#[must_use]
+#[track_caller]
+pub fn provider<'a, C, Output, Descr>(configs: C) -> Handler<'a, DependencyMap, Output, Descr>
+where
+    C: Into<Configs>,
+    Output: 'a,
+    Descr: HandlerDescription,
+{
+    let configs = configs.into();
+    let provided: Vec<_> = core::iter::once(core::any::type_name::<Configs>())
+        .chain(configs.keys.iter().map(|key| key.type_name))
+        .collect();
+
+    from_fn_with_description(
+        Descr::map().with_dependencies(&[], &provided),
+        move |container: DependencyMap, cont| {
+            let mut intermediate = container.clone();
+            intermediate.insert(configs.clone());
+            for key in &configs.keys {
+                (key.insert)(&configs, &mut intermediate);
+            }
+
+            async move {
+                match cont(intermediate).await {
+                    ControlFlow::Continue(_) => ControlFlow::Continue(container),
+                    ControlFlow::Break(result) => ControlFlow::Break(result),
+                }
+            }
+        },
+    )
+}
+
+impl<'a, Output, Descr> Handler<'a, DependencyMap, Output, Descr>
+where
+    Output: 'a,
+    Descr: HandlerDescription,
+{
+    /// Chain this handler with a handler adding `configs` to the container.
+    ///
+    /// See [`provider`] for the details.
+    #[must_use]
+    #[track_caller]
+    pub fn provider<C>(self, configs: C) -> Handler<'a, DependencyMap, Output, Descr>
+    where
+        C: Into<Configs>,
+    {
+        self.chain(provider(configs))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{deps, help_inference};
+    use alloc::{borrow::ToOwned, collections::BTreeMap};
+    use core::ops::ControlFlow;
+
+    struct MockProvider(BTreeMap<&'static str, &'static str>);
+
+    impl ConfigProvider for MockProvider {
+        fn get(&self, key: &str) -> Option<String> {
+            self.0.get(key).map(|&value| value.to_owned())
+        }
+    }
+
+    struct Port;
+
+    impl ConfigKey for Port {
+        type Value = u16;
+        const NAME: &'static str = "PORT";
+    }
+
+    struct Host;
+
+    impl ConfigKey for Host {
+        type Value = String;
+        const NAME: &'static str = "HOST";
+    }
+
+    fn configs(vars: &[(&'static str, &'static str)]) -> Configs {
+        Configs::new(MockProvider(vars.iter().copied().collect())).key::<Port>().key::<Host>()
+    }
+
+    #[tokio::test]
+    async fn test_config() {
+        let handler = help_inference(crate::provider(configs(&[("PORT", "80"), ("HOST", "a.b")])))
+            .endpoint(|port: Config<Port>, host: Config<Host>| async move {
+                alloc::format!("{}:{}", *host, *port)
+            });
+
+        assert_eq!(handler.dispatch(deps![]).await, ControlFlow::Break("a.b:80".to_owned()));
+    }
+
+    #[tokio::test]
+    async fn test_config_lazy() {
+        // Only the injected keys are resolved.
+        let handler = help_inference(crate::provider(configs(&[("PORT", "80")])))
+            .endpoint(|port: Config<Port>| async move { *port });
+
+        assert_eq!(handler.dispatch(deps![]).await, ControlFlow::Break(80));
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "the configuration key `HOST` is not set")]
+    async fn test_config_missing() {
+        let handler = help_inference(crate::provider(configs(&[])))
+            .endpoint(|host: Config<Host>| async move { host.0 });
+
+        let _ = handler.dispatch(deps![]).await;
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "the configuration key `PORT` has an invalid value `port`")]
+    async fn test_config_invalid() {
+        let handler = help_inference(crate::provider(configs(&[("PORT", "port")])))
+            .endpoint(|port: Config<Port>| async move { alloc::format!("{}", *port) });
+
+        let _ = handler.dispatch(deps![]).await;
+    }
+
+    #[test]
+    fn test_config_description() {
+        use crate::description::Tree;
+
+        let handler: Handler<DependencyMap, u16, Tree> = crate::entry()
+            .provider(configs(&[]))
+            .endpoint(|port: Config<Port>| async move { *port });
+        assert_eq!(handler.validate_deps(&deps![]), Ok(()));
+
+        // `Config<Port>` is not declared.
+        let handler: Handler<DependencyMap, u16, Tree> = crate::entry()
+            .provider(Configs::new(MockProvider(BTreeMap::new())))
+            .endpoint(|port: Config<Port>| async move { *port });
+        assert!(handler.validate_deps(&deps![]).is_err());
+    }
+
+    #[test]
+    fn test_configs_get() {
+        let configs = configs(&[("PORT", "8080"), ("HOST", "localhost")]);
+
+        assert_eq!(configs.get::<Port>(), Some(8080));
+        assert_eq!(configs.get::<Host>(), Some("localhost".to_owned()));
+
+        let configs = self::configs(&[("PORT", "port")]);
+        assert_eq!(configs.get::<Port>(), None);
+        assert_eq!(
+            configs.try_get::<Port>(),
+            Err(ConfigError::Invalid { key: "PORT", value: "port".to_owned() })
+        );
+        assert_eq!(configs.try_get::<Host>(), Err(ConfigError::Missing { key: "HOST" }));
+    }
+}
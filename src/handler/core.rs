@@ -1,8 +1,17 @@
-use std::{future::Future, ops::ControlFlow, sync::Arc};
+use alloc::{boxed::Box, sync::Arc, vec, vec::Vec};
+use core::{
+    fmt::{self, Debug, Formatter},
+    future::Future,
+    ops::ControlFlow,
+};
 
 use futures::future::BoxFuture;
 
-use crate::{description, HandlerDescription};
+use crate::{
+    description,
+    di::{Asyncify, Injectable},
+    HandlerDescription,
+};
 
 /// An instance that receives an input and decides whether to break a chain or
 /// pass the value further.
@@ -44,15 +53,35 @@ use crate::{description, HandlerDescription};
 /// This is very crucial when `b` is a filter: if it is chained, it decides
 /// whether or not to call `c`, but when it is branched, whether `c` is called
 /// depends solely on `a`.
+///
+/// ## Terminal handlers
+///
+/// Some combinators, such as [`Handler::pipe`] and [`crate::branch_all`],
+/// dispatch complete handlers that play the role of the rest of the chain.
+/// The handlers they return are _terminal_: just like an
+/// [`endpoint`](fn@crate::endpoint), they never execute their own
+/// continuation, so anything chained after them is never executed. They can
+/// still be branched: when they do not break, they return
+/// [`ControlFlow::Continue`] with the input, and the next branches are tried.
 pub struct Handler<'a, Input, Output, Descr = description::Unspecified> {
-    data: Arc<HandlerData<Descr, DynF<'a, Input, Output>>>,
+    data: Arc<DynHandlerData<'a, Input, Output, Descr>>,
+    tag: Option<&'static str>,
 }
 
-struct HandlerData<Descr, F: ?Sized> {
+struct HandlerData<Descr, L, F: ?Sized> {
     description: Descr,
+    // The handlers of a chain built by `Handler::chain`, empty for other
+    // handlers. Chains are kept flat, so that building and dropping a long
+    // chain does not recurse into every link.
+    links: L,
     f: F,
 }
 
+type DynHandlerData<'a, Input, Output, Descr> =
+    HandlerData<Descr, Links<'a, Input, Output, Descr>, DynF<'a, Input, Output>>;
+
+type Links<'a, Input, Output, Descr> = Arc<[Handler<'a, Input, Output, Descr>]>;
+
 type DynF<'a, Input, Output> =
     dyn Fn(Input, Cont<'a, Input, Output>) -> HandlerResult<'a, Input, Output> + Send + Sync + 'a;
 
@@ -67,7 +96,17 @@ pub type HandlerResult<'a, Input, Output> = BoxFuture<'a, ControlFlow<Output, In
 // but we do not need it here because of `Arc`.
 impl<'a, Input, Output, Descr> Clone for Handler<'a, Input, Output, Descr> {
     fn clone(&self) -> Self {
-        Handler { data: Arc::clone(&self.data) }
+        Handler { data: Arc::clone(&self.data), tag: self.tag }
+    }
+}
+
+// Functions cannot be formatted, so we show the description only.
+impl<'a, Input, Output, Descr> Debug for Handler<'a, Input, Output, Descr>
+where
+    Descr: Debug,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        Debug::fmt(&self.data.description, f)
     }
 }
 
@@ -108,12 +147,54 @@ where
     pub fn chain(self, next: Self) -> Self {
         let required_update_kinds_set = self.description().merge_chain(next.description());
 
-        from_fn_with_description(required_update_kinds_set, move |event, cont| {
-            let this = self.clone();
-            let next = next.clone();
+        let mut links = self.into_links();
+        links.extend(next.into_links());
+        let links: Links<'a, Input, Output, Descr> = links.into();
 
-            this.execute(event, |event| next.execute(event, cont))
-        })
+        let mut handler = from_fn_with_description(required_update_kinds_set, {
+            let links = Arc::clone(&links);
+            move |event, cont| run_links(Arc::clone(&links), 0, event, cont)
+        });
+        Arc::get_mut(&mut handler.data).expect("The handler is not shared yet").links = links;
+        handler
+    }
+
+    /// Chain `prev` before this handler.
+    ///
+    /// `self.prepend(prev)` is equivalent to `prev.chain(self)`; it reads
+    /// better when adding a handler in front of an already built tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use dptree::prelude::*;
+    ///
+    /// let tree: Handler<_, _> = dptree::endpoint(|x: i32| async move { x * 2 });
+    /// let handler = tree.prepend(dptree::filter(|x: i32| x > 0));
+    ///
+    /// assert_eq!(handler.dispatch(dptree::deps![10]).await, ControlFlow::Break(20));
+    /// assert_eq!(
+    ///     handler.dispatch(dptree::deps![-10]).await,
+    ///     ControlFlow::Continue(dptree::deps![-10])
+    /// );
+    /// # }
+    /// ```
+    #[must_use]
+    #[track_caller]
+    pub fn prepend(self, prev: Self) -> Self {
+        prev.chain(self)
+    }
+
+    // Returns the links of this handler if it is a chain, or this handler
+    // itself otherwise.
+    fn into_links(self) -> Vec<Self> {
+        if self.data.links.is_empty() {
+            vec![self]
+        } else {
+            self.data.links.to_vec()
+        }
     }
 
     /// Chain two handlers to make a tree of responsibility.
@@ -158,8 +239,50 @@ where
         Output: Send,
     {
         let required_update_kinds_set = self.description().merge_branch(next.description());
+        self.branch_with_description(required_update_kinds_set, next)
+    }
 
-        from_fn_with_description(required_update_kinds_set, move |event, cont| {
+    /// Adds the branches of `sub` as branches of this handler.
+    ///
+    /// This behaves exactly like [`Handler::branch`]: `sub` is tried after this
+    /// handler, and if it continues, the execution continues further. The
+    /// difference is in the description: if `sub` is a plain group of
+    /// branches, such as `dptree::entry().branch(a).branch(b)`, its branches
+    /// become siblings of the branches of this handler instead of forming a
+    /// nested group (see [`HandlerDescription::merge_flat_branch`]). This
+    /// keeps the description flat when composing handlers defined elsewhere.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dptree::{description::Tree, prelude::*};
+    ///
+    /// let commands: Handler<DependencyMap, (), Tree> = dptree::entry()
+    ///     .branch(dptree::filter(|s: &'static str| s == "/start").endpoint(|| async {}))
+    ///     .branch(dptree::filter(|s: &'static str| s == "/help").endpoint(|| async {}));
+    ///
+    /// let handler = dptree::entry()
+    ///     .branch(dptree::filter(|s: &'static str| s.is_empty()).endpoint(|| async {}))
+    ///     .flat_branch(commands);
+    ///
+    /// assert_eq!(handler.description().children.len(), 3);
+    /// ```
+    #[must_use]
+    #[track_caller]
+    pub fn flat_branch(self, sub: Self) -> Self
+    where
+        Output: Send,
+    {
+        let description = self.description().merge_flat_branch(sub.description());
+        self.branch_with_description(description, sub)
+    }
+
+    #[track_caller]
+    fn branch_with_description(self, description: Descr, next: Self) -> Self
+    where
+        Output: Send,
+    {
+        from_fn_with_description(description, move |event, cont| {
             let this = self.clone();
             let next = next.clone();
 
@@ -172,6 +295,82 @@ where
         })
     }
 
+    /// Adds `next` as a branch only if `cond` is `true`.
+    ///
+    /// If `cond` is `false`, this handler is returned as is, so the resulting
+    /// tree does not contain `next` at all, neither in its execution nor in its
+    /// description. This is useful for branches that depend on compile-time
+    /// configuration, e.g., `cfg!(feature = "...")`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use dptree::prelude::*;
+    ///
+    /// let handler: Handler<_, _> = dptree::entry()
+    ///     .branch_if(cfg!(debug_assertions), dptree::endpoint(|| async { "debug" }))
+    ///     .branch(dptree::endpoint(|| async { "release" }));
+    ///
+    /// let expected = if cfg!(debug_assertions) { "debug" } else { "release" };
+    /// assert_eq!(handler.dispatch(dptree::deps![]).await, ControlFlow::Break(expected));
+    /// # }
+    /// ```
+    #[must_use]
+    #[track_caller]
+    pub fn branch_if(self, cond: bool, next: Self) -> Self
+    where
+        Output: Send,
+    {
+        if cond {
+            self.branch(next)
+        } else {
+            self
+        }
+    }
+
+    /// Adds `next` as a branch that is tried only while `flag` returns `true`.
+    ///
+    /// Unlike [`Handler::branch_if`], `flag` is consulted on every execution,
+    /// so a branch can be toggled at run-time, e.g., for a gradual rollout.
+    /// `flag` is dependency-injected like a [`filter`](crate::filter)
+    /// predicate, so it can either read a global switch or take a flag
+    /// provider from the input. When `flag` returns `false`, `next` is skipped
+    /// as if it continued.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use dptree::prelude::*;
+    ///
+    /// #[derive(Clone)]
+    /// struct Flags {
+    ///     new_greeting: bool,
+    /// }
+    ///
+    /// let handler: Handler<_, _> = dptree::entry()
+    ///     .when_feature(|flags: Flags| flags.new_greeting, dptree::endpoint(|| async { "Howdy!" }))
+    ///     .branch(dptree::endpoint(|| async { "Hello!" }));
+    ///
+    /// let on = dptree::deps![Flags { new_greeting: true }];
+    /// let off = dptree::deps![Flags { new_greeting: false }];
+    /// assert_eq!(handler.dispatch(on).await, ControlFlow::Break("Howdy!"));
+    /// assert_eq!(handler.dispatch(off).await, ControlFlow::Break("Hello!"));
+    /// # }
+    /// ```
+    #[must_use]
+    #[track_caller]
+    pub fn when_feature<Flag, FnArgs>(self, flag: Flag, next: Self) -> Self
+    where
+        Asyncify<Flag>: Injectable<Input, bool, FnArgs> + Send + Sync + 'a,
+        Output: Send,
+    {
+        self.branch(crate::filter(flag).chain(next))
+    }
+
     /// Executes this handler with a continuation.
     ///
     /// Usually, you do not want to call this method by yourself, if you do not
@@ -213,17 +412,87 @@ where
         self.clone().execute(container, |event| async move { ControlFlow::Continue(event) }).await
     }
 
+    /// Executes this handler, consuming it.
+    ///
+    /// This is the same as [`Handler::dispatch`], but it does not clone the
+    /// handler, which is useful for handlers that are dispatched only once.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use dptree::prelude::*;
+    ///
+    /// let handler: Handler<_, _> = dptree::endpoint(|x: i32| async move { x + 1 });
+    /// assert_eq!(handler.dispatch_owned(dptree::deps![1]).await, ControlFlow::Break(2));
+    /// # }
+    /// ```
+    pub async fn dispatch_owned(self, container: Input) -> ControlFlow<Output, Input> {
+        self.execute(container, |event| async move { ControlFlow::Continue(event) }).await
+    }
+
     /// Returns the set of updates that can be processed by this handler.
     pub fn description(&self) -> &Descr {
         &self.data.description
     }
+
+    /// Attaches the identifier `tag` to this handler, replacing the previous
+    /// one.
+    ///
+    /// A tag is metadata that does not affect the execution; it lets
+    /// registries of handlers detect duplicates. The tag is kept by clones of
+    /// this handler, but not by the handlers built from it, so tag a handler
+    /// after all combinators have been applied.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dptree::prelude::*;
+    ///
+    /// let start: Handler<DependencyMap, ()> = dptree::endpoint(|| async {}).tag("start");
+    /// let mut registry = vec![start.clone(), dptree::endpoint(|| async {}).tag("help"), start];
+    ///
+    /// let mut seen = std::collections::HashSet::new();
+    /// registry.retain(|handler| handler.tag_of().map_or(true, |tag| seen.insert(tag)));
+    ///
+    /// let tags: Vec<_> = registry.iter().map(Handler::tag_of).collect();
+    /// assert_eq!(tags, [Some("start"), Some("help")]);
+    /// ```
+    #[must_use]
+    pub fn tag(self, tag: &'static str) -> Self {
+        Self { tag: Some(tag), ..self }
+    }
+
+    /// Returns the tag attached by [`Handler::tag`], if any.
+    pub fn tag_of(&self) -> Option<&'static str> {
+        self.tag
+    }
+
+    /// Returns a description for a handler that wraps this one without
+    /// altering its structure.
+    ///
+    /// [`HandlerDescription::entry`] is neutral with respect to chaining, so
+    /// chaining it with our own description yields an equivalent one.
+    pub(crate) fn wrapper_description(&self) -> Descr {
+        Descr::entry().merge_chain(self.description())
+    }
+
+    /// Returns a description for a handler that wraps this one and may break
+    /// on its own, e.g., with a default output.
+    ///
+    /// [`HandlerDescription::user_defined`] makes the wrapper itself count as
+    /// a handler able to break, as checked by `Handler::require_non_empty`.
+    pub(crate) fn breaking_wrapper_description(&self) -> Descr {
+        Descr::user_defined().merge_chain(self.description())
+    }
 }
 
 /// Constructs a handler from a function.
 ///
 /// Most of the time, you do not want to use this function. Take a look at more
-/// specialised functions: [`crate::endpoint`], [`crate::filter`],
-/// [`crate::filter_map`], etc.
+/// specialised functions: [`crate::endpoint`](fn@crate::endpoint),
+/// [`crate::filter`], [`crate::filter_map`], etc.
 #[must_use]
 pub fn from_fn<'a, F, Fut, Input, Output, Descr>(f: F) -> Handler<'a, Input, Output, Descr>
 where
@@ -246,11 +515,49 @@ where
     F: Send + Sync + 'a,
     Fut: Future<Output = ControlFlow<Output, Input>> + Send + 'a,
 {
+    let links: Links<'a, Input, Output, Descr> = Arc::from(Vec::new());
+
     Handler {
         data: Arc::new(HandlerData {
             f: move |event, cont| Box::pin(f(event, cont)) as HandlerResult<_, _>,
+            links,
             description,
         }),
+        tag: None,
+    }
+}
+
+// Executes `links[index..]` one after another, followed by `cont`.
+//
+// Each link is given a continuation that executes the next link directly,
+// without any intermediate futures, which keeps the nesting of futures, and
+// thus the stack usage when polling them, as small as possible.
+fn run_links<'a, Input, Output, Descr>(
+    links: Links<'a, Input, Output, Descr>,
+    index: usize,
+    event: Input,
+    cont: Cont<'a, Input, Output>,
+) -> HandlerResult<'a, Input, Output>
+where
+    Input: Send + 'a,
+    Output: 'a,
+    Descr: HandlerDescription,
+{
+    match links.get(index) {
+        Some(link) => {
+            let link = link.clone();
+            let next: Cont<'a, Input, Output> =
+                Box::new(move |event| run_links(links, index + 1, event, cont));
+            // A link may execute the continuation right away, so the stack is
+            // grown for the call as well as for the polls of the future.
+            #[cfg(feature = "std")]
+            let fut =
+                super::recursion::guard(super::recursion::grow(|| (link.data.f)(event, next)));
+            #[cfg(not(feature = "std"))]
+            let fut = (link.data.f)(event, next);
+            fut
+        }
+        None => cont(event),
     }
 }
 
@@ -269,6 +576,127 @@ where
     from_fn_with_description(Descr::entry(), |event, cont| cont(event))
 }
 
+/// A handler that cannot be composed further.
+///
+/// Constructed by [`Handler::freeze`]. The links of the top-level chain are
+/// collected once, and [`FrozenHandler::dispatch`] executes them one after
+/// another without tracking the depth for [`Handler::dispatch_with_limit`].
+/// This is not measurably faster than [`Handler::dispatch`]: every link still
+/// allocates its continuation and grows the stack when needed.
+///
+/// The behaviour of [`FrozenHandler::dispatch`] is the same as of
+/// [`Handler::dispatch`] on the original handler.
+pub struct FrozenHandler<'a, Input, Output, Descr = description::Unspecified> {
+    links: Links<'a, Input, Output, Descr>,
+    handler: Handler<'a, Input, Output, Descr>,
+}
+
+// `#[derive(Clone)]` obligates all type parameters to satisfy `Clone` as well.
+impl<'a, Input, Output, Descr> Clone for FrozenHandler<'a, Input, Output, Descr> {
+    fn clone(&self) -> Self {
+        Self { links: Arc::clone(&self.links), handler: self.handler.clone() }
+    }
+}
+
+impl<'a, Input, Output, Descr> Debug for FrozenHandler<'a, Input, Output, Descr>
+where
+    Descr: Debug,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FrozenHandler")
+            .field("description", &self.handler.data.description)
+            .finish()
+    }
+}
+
+impl<'a, Input, Output, Descr> Handler<'a, Input, Output, Descr>
+where
+    Input: Send + 'a,
+    Output: 'a,
+    Descr: HandlerDescription,
+{
+    /// Turns this handler into a [`FrozenHandler`].
+    ///
+    /// See [`FrozenHandler`] for the details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use dptree::prelude::*;
+    ///
+    /// let handler: Handler<_, i32> =
+    ///     dptree::filter(|x: i32| x > 0).map(|x: i32| x * 2).endpoint(|x: i32| async move { x });
+    /// let frozen = handler.freeze();
+    ///
+    /// assert_eq!(frozen.dispatch(dptree::deps![21]).await, ControlFlow::Break(42));
+    /// assert_eq!(frozen.dispatch(dptree::deps![-1]).await, ControlFlow::Continue(dptree::deps![-1]));
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn freeze(self) -> FrozenHandler<'a, Input, Output, Descr> {
+        FrozenHandler { links: self.clone().into_links().into(), handler: self }
+    }
+}
+
+impl<'a, Input, Output, Descr> FrozenHandler<'a, Input, Output, Descr>
+where
+    Input: Send + 'a,
+    Output: 'a,
+    Descr: HandlerDescription,
+{
+    /// Executes this handler, as [`Handler::dispatch`] does.
+    pub fn dispatch(&self, container: Input) -> HandlerResult<'a, Input, Output> {
+        run_frozen(
+            Arc::clone(&self.links),
+            0,
+            container,
+            Box::new(|event| Box::pin(async move { ControlFlow::Continue(event) })),
+        )
+    }
+
+    /// Returns the description of the original handler.
+    pub fn description(&self) -> &Descr {
+        self.handler.description()
+    }
+
+    /// Returns the original handler.
+    #[must_use]
+    pub fn into_handler(self) -> Handler<'a, Input, Output, Descr> {
+        self.handler
+    }
+}
+
+// Executes `links[index..]` one after another, followed by `cont`, as
+// `run_links` does, but without tracking the recursion depth.
+fn run_frozen<'a, Input, Output, Descr>(
+    links: Links<'a, Input, Output, Descr>,
+    index: usize,
+    event: Input,
+    cont: Cont<'a, Input, Output>,
+) -> HandlerResult<'a, Input, Output>
+where
+    Input: Send + 'a,
+    Output: 'a,
+    Descr: HandlerDescription,
+{
+    if index == links.len() {
+        return cont(event);
+    }
+
+    let next = Arc::clone(&links);
+    let cont: Cont<'a, Input, Output> =
+        Box::new(move |event| run_frozen(next, index + 1, event, cont));
+    #[cfg(feature = "std")]
+    let fut = super::recursion::grow_future(super::recursion::grow(|| {
+        (links[index].data.f)(event, cont)
+    }));
+    #[cfg(not(feature = "std"))]
+    let fut = (links[index].data.f)(event, cont);
+    fut
+}
+
 #[cfg(test)]
 pub(crate) fn help_inference<I, O>(h: Handler<I, O>) -> Handler<I, O> {
     h
@@ -276,14 +704,11 @@ pub(crate) fn help_inference<I, O>(h: Handler<I, O>) -> Handler<I, O> {
 
 #[cfg(test)]
 mod tests {
-    use std::collections::HashSet;
-
-    use maplit::hashset;
-
     use crate::{
-        deps, description, filter_map, filter_map_with_description,
+        deps,
+        description::Tree,
+        di::DependencyMap,
         handler::{endpoint, filter, filter_async},
-        prelude::DependencyMap,
     };
 
     use super::*;
@@ -347,6 +772,53 @@ mod tests {
         assert!(result == ControlFlow::Break(output));
     }
 
+    #[tokio::test]
+    async fn test_dispatch_owned() {
+        let handler: Handler<_, _, Tree> =
+            filter(|x: i32| x > 0).endpoint(|x: i32| async move { x * 2 });
+        let weak = Arc::downgrade(&handler.data);
+
+        assert_eq!(handler.clone().dispatch_owned(deps![1]).await, ControlFlow::Break(2));
+        assert_eq!(
+            handler.clone().dispatch_owned(deps![-1]).await,
+            ControlFlow::Continue(deps![-1])
+        );
+
+        // The last owner drops the handler after the dispatch.
+        assert_eq!(handler.dispatch_owned(deps![2]).await, ControlFlow::Break(4));
+        assert!(weak.upgrade().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_prepend() {
+        let order = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let push = |x: i32| {
+            let order = Arc::clone(&order);
+            help_inference(from_fn(move |event: i32, cont: Cont<i32, ()>| {
+                order.lock().unwrap().push(x);
+                cont(event)
+            }))
+        };
+
+        let handler = push(2).chain(push(3)).prepend(push(1)).prepend(push(0));
+        assert_eq!(handler.dispatch(0).await, ControlFlow::Continue(0));
+        assert_eq!(*order.lock().unwrap(), [0, 1, 2, 3]);
+    }
+
+    #[tokio::test]
+    async fn test_prepend_filter() {
+        let handler: Handler<_, _, Tree> =
+            endpoint(|x: i32| async move { x }).prepend(filter(|x: i32| x > 0));
+        let expected: Handler<_, _, Tree> =
+            filter(|x: i32| x > 0).chain(endpoint(|x: i32| async move { x }));
+
+        for x in [1, -1] {
+            assert_eq!(handler.dispatch(deps![x]).await, expected.dispatch(deps![x]).await);
+        }
+        assert_eq!(handler.description().kind, expected.description().kind);
+        assert_eq!(handler.description().children.len(), 2);
+    }
+
     #[tokio::test]
     async fn test_deeply_nested_tree() {
         #[derive(Debug, PartialEq)]
@@ -387,9 +859,18 @@ mod tests {
         assert_eq!(dispatcher.dispatch(deps![-2]).await, ControlFlow::Break(Output::LT));
     }
 
+    #[cfg(feature = "std")]
     #[tokio::test]
     async fn allowed_updates() {
-        use crate::description::{EventKind, InterestSet};
+        use std::collections::HashSet;
+
+        use maplit::hashset;
+
+        use crate::{
+            description::{self, EventKind, InterestSet},
+            filter_map, filter_map_with_description,
+            prelude::DependencyMap,
+        };
         use UpdateKind::*;
 
         #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -508,4 +989,140 @@ mod tests {
         // Chained non-overlapping filters do not allow anything.
         assert(filter_a().chain(filter_b()).endpoint(|| async {}), hashset! {});
     }
+
+    #[tokio::test]
+    async fn test_branch_if() {
+        let handler = |cond| -> Handler<'static, DependencyMap, &'static str, Tree> {
+            crate::entry()
+                .branch_if(cond, endpoint(|| async { "optional" }))
+                .branch(endpoint(|| async { "fallback" }))
+        };
+
+        assert_eq!(handler(true).dispatch(deps![]).await, ControlFlow::Break("optional"));
+        assert_eq!(handler(false).dispatch(deps![]).await, ControlFlow::Break("fallback"));
+
+        // The branch is absent from the tree.
+        assert_eq!(handler(true).description().children.len(), 2);
+        assert_eq!(handler(false).description().children.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_flat_branch() {
+        let sub = || -> Handler<'static, DependencyMap, &'static str, Tree> {
+            crate::entry()
+                .branch(filter(|x: i32| x == 1).endpoint(|| async { "one" }))
+                .branch(filter(|x: i32| x == 2).endpoint(|| async { "two" }))
+        };
+        let handler: Handler<'static, DependencyMap, &'static str, Tree> = crate::entry()
+            .branch(filter(|x: i32| x == 0).endpoint(|| async { "zero" }))
+            .flat_branch(sub())
+            .branch(endpoint(|| async { "other" }));
+
+        for (x, expected) in [(0, "zero"), (1, "one"), (2, "two"), (3, "other")] {
+            assert_eq!(handler.dispatch(deps![x]).await, ControlFlow::Break(expected));
+        }
+
+        // The branches of `sub` are siblings of the others.
+        assert_eq!(handler.description().children.len(), 4);
+        let nested = crate::entry().branch(endpoint(|| async { "zero" })).branch(sub());
+        assert_eq!(nested.description().children.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_tag() {
+        let handler: Handler<DependencyMap, i32> = endpoint(|| async { 1 });
+        assert_eq!(handler.tag_of(), None);
+
+        let handler = handler.tag("one");
+        assert_eq!(handler.tag_of(), Some("one"));
+        assert_eq!(handler.clone().tag_of(), Some("one"));
+        assert_eq!(handler.clone().tag("uno").tag_of(), Some("uno"));
+        assert_eq!(handler.dispatch(deps![]).await, ControlFlow::Break(1));
+
+        // Handlers built from a tagged handler are not tagged.
+        assert_eq!(filter(|| true).chain(handler).tag_of(), None);
+    }
+
+    #[test]
+    fn test_dedup_by_tag() {
+        let handlers: Vec<Handler<DependencyMap, ()>> = vec![
+            endpoint(|| async {}).tag("a"),
+            endpoint(|| async {}).tag("b"),
+            endpoint(|| async {}).tag("a"),
+            endpoint(|| async {}),
+        ];
+
+        let mut seen = std::collections::HashSet::new();
+        let deduped: Vec<_> = handlers
+            .into_iter()
+            .filter(|handler| match handler.tag_of() {
+                Some(tag) => seen.insert(tag),
+                None => true,
+            })
+            .collect();
+
+        assert_eq!(
+            deduped.iter().map(Handler::tag_of).collect::<Vec<_>>(),
+            [Some("a"), Some("b"), None]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_when_feature() {
+        use std::sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc,
+        };
+
+        let enabled = Arc::new(AtomicBool::new(false));
+
+        let handler: Handler<DependencyMap, &'static str> = crate::entry()
+            .when_feature(
+                {
+                    let enabled = Arc::clone(&enabled);
+                    move || enabled.load(Ordering::Relaxed)
+                },
+                endpoint(|| async { "new" }),
+            )
+            .branch(endpoint(|| async { "old" }));
+
+        assert_eq!(handler.dispatch(deps![]).await, ControlFlow::Break("old"));
+
+        enabled.store(true, Ordering::Relaxed);
+        assert_eq!(handler.dispatch(deps![]).await, ControlFlow::Break("new"));
+
+        enabled.store(false, Ordering::Relaxed);
+        assert_eq!(handler.dispatch(deps![]).await, ControlFlow::Break("old"));
+    }
+
+    #[tokio::test]
+    async fn test_freeze() {
+        let handler: Handler<DependencyMap, String> = crate::entry()
+            .branch(
+                filter(|x: i32| x > 0)
+                    .map(|x: i32| x * 2)
+                    .endpoint(|x: i32| async move { format!("positive {x}") }),
+            )
+            .branch(filter(|x: i32| x < 0).endpoint(|| async { "negative".to_owned() }))
+            .chain(endpoint(|| async { "zero".to_owned() }));
+        let frozen = handler.clone().freeze();
+
+        for x in [-5, 0, 5] {
+            assert_eq!(frozen.dispatch(deps![x]).await, handler.dispatch(deps![x]).await);
+        }
+        assert_eq!(frozen.dispatch(deps![5]).await, ControlFlow::Break("positive 10".to_owned()));
+    }
+
+    #[tokio::test]
+    async fn test_freeze_continue() {
+        // A single handler, which is not a chain.
+        let frozen = help_inference::<_, ()>(filter(|x: i32| x > 0)).freeze();
+        assert_eq!(frozen.dispatch(deps![1]).await, ControlFlow::Continue(deps![1]));
+
+        let handler: Handler<DependencyMap, (), Tree> =
+            filter(|x: i32| x > 0).endpoint(|| async {});
+        let frozen = handler.clone().freeze();
+        assert_eq!(frozen.dispatch(deps![-1]).await, ControlFlow::Continue(deps![-1]));
+        assert_eq!(frozen.description(), handler.description());
+    }
 }
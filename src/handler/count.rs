@@ -0,0 +1,80 @@
+use crate::{from_fn_with_description, Handler, HandlerDescription};
+use alloc::sync::Arc;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+impl<'a, Input, Output, Descr> Handler<'a, Input, Output, Descr>
+where
+    Input: Send + 'a,
+    Output: 'a,
+    Descr: HandlerDescription,
+{
+    /// Returns this handler along with a counter of its executions.
+    ///
+    /// The counter is incremented every time the execution reaches the
+    /// returned handler, regardless of whether it then breaks or continues.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use dptree::prelude::*;
+    /// use std::sync::atomic::Ordering;
+    ///
+    /// let (handler, counter) = dptree::endpoint(|| async { "done" }).count_dispatches();
+    /// let handler: Handler<_, _> = dptree::filter(|x: i32| x > 0).chain(handler);
+    ///
+    /// let _ = handler.dispatch(dptree::deps![10]).await;
+    /// let _ = handler.dispatch(dptree::deps![-10]).await;
+    ///
+    /// assert_eq!(counter.load(Ordering::Relaxed), 1);
+    /// # }
+    /// ```
+    #[must_use]
+    #[track_caller]
+    pub fn count_dispatches(self) -> (Self, Arc<AtomicUsize>) {
+        let counter = Arc::new(AtomicUsize::new(0));
+        let description = self.wrapper_description();
+
+        let handler = {
+            let counter = Arc::clone(&counter);
+
+            from_fn_with_description(description, move |event, cont| {
+                counter.fetch_add(1, Ordering::Relaxed);
+                self.clone().execute(event, cont)
+            })
+        };
+
+        (handler, counter)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ops::ControlFlow;
+
+    use super::*;
+    use crate::{deps, help_inference};
+
+    #[tokio::test]
+    async fn test_count_dispatches() {
+        let (counted, counter) =
+            help_inference(crate::filter(|x: i32| x % 2 == 0)).count_dispatches();
+        let handler = crate::filter(|x: i32| x > 0).chain(counted).endpoint(|| async {});
+
+        for x in [-2, -1, 1, 2, 3, 4] {
+            let _ = handler.dispatch(deps![x]).await;
+        }
+
+        assert_eq!(counter.load(Ordering::Relaxed), 4);
+    }
+
+    #[tokio::test]
+    async fn test_count_dispatches_continue() {
+        let (handler, counter) = help_inference(crate::entry()).count_dispatches();
+
+        let result: ControlFlow<(), _> = handler.dispatch(deps![1]).await;
+        assert_eq!(result, ControlFlow::Continue(deps![1]));
+        assert_eq!(counter.load(Ordering::Relaxed), 1);
+    }
+}
@@ -0,0 +1,232 @@
+use crate::{description, from_fn_with_description, Handler, HandlerDescription};
+use alloc::sync::Arc;
+use core::{future::Future, ops::ControlFlow};
+
+/// A handler threading an explicit context alongside its input.
+///
+/// `CtxHandler<Input, Ctx, Output>` is a [`Handler`] whose input is a pair of
+/// `Input` and `Ctx`: the input stays the same along a chain, while the
+/// context is an accumulator updated by the nodes and read by the endpoint,
+/// without storing intermediate values in a DI container. This suits
+/// parser-combinator style handlers.
+///
+/// Nodes are constructed by [`CtxHandler::filter_map_with_context`] and
+/// [`CtxHandler::endpoint`] and composed by [`CtxHandler::chain`] and
+/// [`CtxHandler::branch`], which work as their [`Handler`] counterparts. If the
+/// rest of a chain continues, a node returns the context it has received, so
+/// every branch starts with the context as it was before the previous branches
+/// were tried.
+///
+/// # Examples
+///
+/// ```
+/// # #[tokio::main]
+/// # async fn main() {
+/// use dptree::{prelude::*, CtxHandler};
+///
+/// // Splits off a word and appends it to the context.
+/// let word = |expected: &'static str| {
+///     CtxHandler::filter_map_with_context(move |text: &&str, words: &mut Vec<&str>| {
+///         match text.split_whitespace().nth(words.len()) {
+///             Some(word) if word == expected => {
+///                 words.push(expected);
+///                 true
+///             }
+///             _ => false,
+///         }
+///     })
+/// };
+/// let echo = || CtxHandler::endpoint(|_, words: Vec<&str>| async move { words.join(" ") });
+///
+/// let handler: CtxHandler<_, _, _> =
+///     word("git").branch(word("commit").chain(echo())).branch(word("push").chain(echo()));
+///
+/// assert_eq!(
+///     handler.dispatch("git push", vec![]).await,
+///     ControlFlow::Break("git push".to_owned())
+/// );
+/// assert!(matches!(handler.dispatch("git pull", vec![]).await, ControlFlow::Continue(_)));
+/// # }
+/// ```
+pub struct CtxHandler<'a, Input, Ctx, Output, Descr = description::Unspecified> {
+    handler: Handler<'a, (Input, Ctx), Output, Descr>,
+}
+
+// `#[derive(Clone)]` obligates all type parameters to satisfy `Clone` as well.
+impl<'a, Input, Ctx, Output, Descr> Clone for CtxHandler<'a, Input, Ctx, Output, Descr> {
+    fn clone(&self) -> Self {
+        Self { handler: self.handler.clone() }
+    }
+}
+
+impl<'a, Input, Ctx, Output, Descr> From<Handler<'a, (Input, Ctx), Output, Descr>>
+    for CtxHandler<'a, Input, Ctx, Output, Descr>
+{
+    fn from(handler: Handler<'a, (Input, Ctx), Output, Descr>) -> Self {
+        Self { handler }
+    }
+}
+
+impl<'a, Input, Ctx, Output, Descr> CtxHandler<'a, Input, Ctx, Output, Descr>
+where
+    Input: Send + 'a,
+    Ctx: Send + 'a,
+    Output: 'a,
+    Descr: HandlerDescription,
+{
+    /// Constructs an entry point handler, which passes its input and context
+    /// further unchanged.
+    #[must_use]
+    #[track_caller]
+    pub fn entry() -> Self {
+        crate::entry().into()
+    }
+
+    /// Constructs a handler that updates the context with `f`.
+    ///
+    /// `f` receives the input and a copy of the context. If it returns `true`,
+    /// the updated copy is passed further in a handler chain along with the
+    /// input. Otherwise, the handler returns [`ControlFlow::Continue`]. In both
+    /// cases, the handler continues with the original context, since the
+    /// updated one belongs to the rest of the chain.
+    #[must_use]
+    #[track_caller]
+    pub fn filter_map_with_context<F>(f: F) -> Self
+    where
+        F: Fn(&Input, &mut Ctx) -> bool + Send + Sync + 'a,
+        Ctx: Clone,
+    {
+        from_fn_with_description(Descr::filter_map(), move |(input, ctx): (Input, Ctx), cont| {
+            let mut updated = ctx.clone();
+            let cond = f(&input, &mut updated);
+
+            async move {
+                if !cond {
+                    return ControlFlow::Continue((input, ctx));
+                }
+
+                match cont((input, updated)).await {
+                    ControlFlow::Continue((input, _)) => ControlFlow::Continue((input, ctx)),
+                    ControlFlow::Break(output) => ControlFlow::Break(output),
+                }
+            }
+        })
+        .into()
+    }
+
+    /// Constructs a handler that breaks with the output of `f`, which receives
+    /// the input and the context.
+    #[must_use]
+    #[track_caller]
+    pub fn endpoint<F, Fut>(f: F) -> Self
+    where
+        F: Fn(Input, Ctx) -> Fut + Send + Sync + 'a,
+        Fut: Future<Output = Output> + Send + 'a,
+    {
+        let f = Arc::new(f);
+
+        from_fn_with_description(Descr::endpoint(), move |(input, ctx), _cont| {
+            let f = Arc::clone(&f);
+            async move { ControlFlow::Break(f(input, ctx).await) }
+        })
+        .into()
+    }
+
+    /// Chain two handlers, as [`Handler::chain`] does.
+    #[must_use]
+    #[track_caller]
+    pub fn chain(self, next: Self) -> Self {
+        self.handler.chain(next.handler).into()
+    }
+
+    /// Branch two handlers, as [`Handler::branch`] does.
+    #[must_use]
+    #[track_caller]
+    pub fn branch(self, next: Self) -> Self
+    where
+        Output: Send,
+    {
+        self.handler.branch(next.handler).into()
+    }
+
+    /// Executes this handler with `input` and the initial context `ctx`.
+    ///
+    /// Returns [`ControlFlow::Continue`] with the input and the context if the
+    /// handler continues.
+    pub async fn dispatch(&self, input: Input, ctx: Ctx) -> ControlFlow<Output, (Input, Ctx)> {
+        self.handler.dispatch((input, ctx)).await
+    }
+
+    /// Returns the underlying handler, whose input is the pair of the input
+    /// and the context.
+    #[must_use]
+    pub fn into_handler(self) -> Handler<'a, (Input, Ctx), Output, Descr> {
+        self.handler
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::{vec, vec::Vec};
+
+    type Parser = CtxHandler<'static, &'static str, Vec<char>, String>;
+
+    // Accepts the next character of the input if it is `expected`.
+    fn char(expected: char) -> Parser {
+        CtxHandler::filter_map_with_context(move |input: &&str, parsed: &mut Vec<char>| {
+            if input.chars().nth(parsed.len()) == Some(expected) {
+                parsed.push(expected);
+                true
+            } else {
+                false
+            }
+        })
+    }
+
+    fn done() -> Parser {
+        CtxHandler::filter_map_with_context(|input: &&str, parsed: &mut Vec<char>| {
+            input.chars().count() == parsed.len()
+        })
+        .chain(CtxHandler::endpoint(|_, parsed: Vec<char>| async move {
+            parsed.into_iter().collect()
+        }))
+    }
+
+    #[tokio::test]
+    async fn test_ctx_handler() {
+        let handler = char('a').chain(char('b')).chain(done());
+
+        assert_eq!(handler.dispatch("ab", vec![]).await, ControlFlow::Break("ab".to_owned()));
+        assert_eq!(handler.dispatch("abc", vec![]).await, ControlFlow::Continue(("abc", vec![])));
+        assert_eq!(handler.dispatch("b", vec![]).await, ControlFlow::Continue(("b", vec![])));
+    }
+
+    #[tokio::test]
+    async fn test_ctx_handler_branch() {
+        // The second branch starts with the context as it was before the first
+        // one, even though the first one has appended to it.
+        let handler = char('a')
+            .branch(char('b').chain(char('c')).chain(done()))
+            .branch(char('b').chain(char('d')).chain(done()));
+
+        assert_eq!(handler.dispatch("abc", vec![]).await, ControlFlow::Break("abc".to_owned()));
+        assert_eq!(handler.dispatch("abd", vec![]).await, ControlFlow::Break("abd".to_owned()));
+        assert_eq!(handler.dispatch("abe", vec![]).await, ControlFlow::Continue(("abe", vec![])));
+    }
+
+    #[tokio::test]
+    async fn test_ctx_handler_entry() {
+        let handler: CtxHandler<i32, Vec<i32>, Vec<i32>> = CtxHandler::entry()
+            .chain(CtxHandler::filter_map_with_context(|&x: &i32, acc: &mut Vec<i32>| {
+                acc.push(x);
+                true
+            }))
+            .chain(CtxHandler::endpoint(|x, mut acc: Vec<i32>| async move {
+                acc.push(x * 10);
+                acc
+            }));
+
+        assert_eq!(handler.dispatch(1, vec![0]).await, ControlFlow::Break(vec![0, 1, 10]));
+    }
+}
@@ -0,0 +1,150 @@
+use crate::{
+    di::{Asyncify, Injectable},
+    from_fn_with_description, Clock, Handler, HandlerDescription,
+};
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    hash::Hash,
+    ops::ControlFlow,
+    sync::{Arc, Mutex, PoisonError},
+    time::{Duration, Instant},
+};
+
+/// Constructs a handler that lets through only the first input per key within
+/// `window`.
+///
+/// `key_fn` extracts a key from the input, e.g., a chat id. If no input with
+/// the same key has passed within the last `window`, as measured by `clock`, a
+/// continuation of the handler will be called. Otherwise, the input is skipped
+/// and the handler returns [`ControlFlow::Continue`]. The keys are debounced
+/// independently of each other.
+///
+/// The state is shared by all clones of the handler and is safe to use from
+/// concurrent dispatches. The keys whose windows have ended are dropped, so the
+/// state does not grow beyond the keys seen within one window.
+///
+/// # Examples
+///
+/// ```
+/// # #[tokio::main]
+/// # async fn main() {
+/// use dptree::{prelude::*, ManualClock};
+/// use std::time::Duration;
+///
+/// let clock = ManualClock::new();
+/// let handler: Handler<_, _> =
+///     dptree::debounce(|chat_id: i64| chat_id, Duration::from_secs(1), clock.clone())
+///         .endpoint(|| async { "handled" });
+///
+/// assert_eq!(handler.dispatch(dptree::deps![1i64]).await, ControlFlow::Break("handled"));
+/// assert!(matches!(handler.dispatch(dptree::deps![1i64]).await, ControlFlow::Continue(_)));
+///
+/// clock.advance(Duration::from_secs(1));
+/// assert_eq!(handler.dispatch(dptree::deps![1i64]).await, ControlFlow::Break("handled"));
+/// # }
+/// ```
+#[must_use]
+#[track_caller]
+pub fn debounce<'a, KeyFn, Key, C, Input, Output, Args, Descr>(
+    key_fn: KeyFn,
+    window: Duration,
+    clock: C,
+) -> Handler<'a, Input, Output, Descr>
+where
+    Asyncify<KeyFn>: Injectable<Input, Key, Args> + Send + Sync + 'a,
+    Key: Hash + Eq + Send + 'a,
+    C: Clock + 'a,
+    Input: Send + 'a,
+    Output: 'a,
+    Descr: HandlerDescription,
+{
+    let required = <Asyncify<KeyFn> as Injectable<Input, Key, Args>>::dependencies();
+    let key_fn = Arc::new(Asyncify(key_fn));
+    let clock = Arc::new(clock);
+    let passed: Arc<Mutex<HashMap<Key, Instant>>> = Arc::new(Mutex::new(HashMap::new()));
+
+    from_fn_with_description(
+        Descr::filter().with_dependencies(&required, &[]),
+        move |event, cont| {
+            let key_fn = Arc::clone(&key_fn);
+            let clock = Arc::clone(&clock);
+            let passed = Arc::clone(&passed);
+
+            async move {
+                let key_fn = key_fn.inject(&event);
+                let key = key_fn().await;
+                drop(key_fn);
+
+                let cond = {
+                    let now = clock.now();
+                    let mut passed = passed.lock().unwrap_or_else(PoisonError::into_inner);
+                    passed.retain(|_, &mut since| now.duration_since(since) < window);
+                    match passed.entry(key) {
+                        Entry::Occupied(_) => false,
+                        Entry::Vacant(entry) => {
+                            entry.insert(now);
+                            true
+                        }
+                    }
+                };
+
+                if cond {
+                    cont(event).await
+                } else {
+                    ControlFlow::Continue(event)
+                }
+            }
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{deps, help_inference, ManualClock};
+
+    #[tokio::test]
+    async fn test_debounce() {
+        let clock = ManualClock::new();
+        let handler = help_inference(crate::entry())
+            .branch(
+                debounce(|chat: u32| chat, Duration::from_secs(10), clock.clone())
+                    .endpoint(|chat: u32| async move { format!("handled {chat}") }),
+            )
+            .branch(crate::endpoint(|chat: u32| async move { format!("skipped {chat}") }));
+
+        let dispatch = |chat: u32| {
+            let handler = handler.clone();
+            async move {
+                match handler.dispatch(deps![chat]).await {
+                    ControlFlow::Break(output) => output,
+                    ControlFlow::Continue(_) => unreachable!(),
+                }
+            }
+        };
+
+        assert_eq!(dispatch(1).await, "handled 1");
+        assert_eq!(dispatch(1).await, "skipped 1");
+        // Another key is debounced independently.
+        assert_eq!(dispatch(2).await, "handled 2");
+
+        clock.advance(Duration::from_secs(5));
+        assert_eq!(dispatch(1).await, "skipped 1");
+        assert_eq!(dispatch(2).await, "skipped 2");
+
+        // The window of the first key has ended, but not of the second one.
+        clock.advance(Duration::from_secs(5));
+        assert_eq!(dispatch(1).await, "handled 1");
+        assert_eq!(dispatch(1).await, "skipped 1");
+    }
+
+    #[tokio::test]
+    async fn test_debounce_shared_between_clones() {
+        let clock = ManualClock::new();
+        let handler =
+            help_inference(debounce(|| (), Duration::from_secs(1), clock)).endpoint(|| async {});
+
+        assert_eq!(handler.clone().dispatch(deps![]).await, ControlFlow::Break(()));
+        assert_eq!(handler.dispatch(deps![]).await, ControlFlow::Continue(deps![]));
+    }
+}
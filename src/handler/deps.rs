@@ -0,0 +1,584 @@
+use crate::{
+    description::{NodeKind, Tree},
+    di::DependencyMap,
+    from_fn_with_description, Handler, HandlerDescription,
+};
+use alloc::{collections::BTreeSet, sync::Arc, vec::Vec};
+use core::ops::{ControlFlow, Deref};
+
+/// Asserts that a handler requires exactly the listed dependency types.
+///
+/// `ensure_deps!(handler, [A, B, ...])` is a shorthand for
+/// [`Handler::ensure_deps`] with the names of the listed types. It panics if
+/// the handler requires a type that is neither listed nor added by the handler
+/// itself, or if a listed type is not required by any node of the handler.
+///
+/// # Examples
+///
+/// ```
+/// use dptree::{description::Tree, prelude::*};
+///
+/// let handler: Handler<DependencyMap, String, Tree> = dptree::entry()
+///     .map(|x: i32| x.to_string())
+///     .endpoint(|s: String, flag: bool| async move { format!("{s} {flag}") });
+///
+/// dptree::ensure_deps!(handler, [i32, bool]);
+/// ```
+///
+/// ```should_panic
+/// # use dptree::{description::Tree, prelude::*};
+/// # let handler: Handler<DependencyMap, String, Tree> = dptree::entry()
+/// #     .map(|x: i32| x.to_string())
+/// #     .endpoint(|s: String, flag: bool| async move { format!("{s} {flag}") });
+/// // `bool` is missing, and `u8` is not used by any handler.
+/// dptree::ensure_deps!(handler, [i32, u8]);
+/// ```
+#[macro_export]
+macro_rules! ensure_deps {
+    ($handler:expr, [$($ty:ty),* $(,)?]) => {
+        $handler.ensure_deps(&[$(::core::any::type_name::<$ty>()),*])
+    };
+}
+
+/// A dependency that is required by a handler but not available to it.
+///
+/// See [`Handler::validate_deps`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MissingDep {
+    /// The type name of the dependency.
+    pub type_name: &'static str,
+
+    /// The kind of the node that requires the dependency.
+    pub kind: NodeKind,
+
+    /// The label of the node that requires the dependency, if any.
+    pub label: Option<&'static str>,
+}
+
+/// A set of dependency types that containers will provide.
+///
+/// Unlike a [`DependencyMap`], a registry records only the types, so it can be
+/// filled before the values are available, e.g., when building a handler at
+/// startup. See [`Handler::build_checked`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TypedRegistry {
+    type_names: BTreeSet<&'static str>,
+}
+
+impl TypedRegistry {
+    /// Constructs an empty registry.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records the type `T`.
+    pub fn insert<T: ?Sized>(&mut self) {
+        self.type_names.insert(core::any::type_name::<T>());
+    }
+
+    /// Records the type `T` and returns the registry.
+    #[must_use]
+    pub fn with<T: ?Sized>(mut self) -> Self {
+        self.insert::<T>();
+        self
+    }
+
+    /// Returns `true` if the type `T` is recorded.
+    #[must_use]
+    pub fn contains<T: ?Sized>(&self) -> bool {
+        self.type_names.contains(core::any::type_name::<T>())
+    }
+}
+
+impl From<&DependencyMap> for TypedRegistry {
+    fn from(container: &DependencyMap) -> Self {
+        Self { type_names: container.type_names().collect() }
+    }
+}
+
+/// A handler whose dependencies have been checked against a
+/// [`TypedRegistry`].
+///
+/// Constructed by [`Handler::build_checked`]. It dereferences to the handler,
+/// so it can be dispatched as usual.
+#[derive(Debug, Clone)]
+pub struct CheckedHandler<'a, Input, Output> {
+    handler: Handler<'a, Input, Output, Tree>,
+}
+
+impl<'a, Input, Output> CheckedHandler<'a, Input, Output> {
+    /// Returns the checked handler.
+    #[must_use]
+    pub fn into_inner(self) -> Handler<'a, Input, Output, Tree> {
+        self.handler
+    }
+}
+
+impl<'a, Input, Output> Deref for CheckedHandler<'a, Input, Output> {
+    type Target = Handler<'a, Input, Output, Tree>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.handler
+    }
+}
+
+impl<'a, Input, Output> Handler<'a, Input, Output, Tree>
+where
+    Input: Send + 'a,
+    Output: 'a,
+{
+    /// Checks that every dependency of this handler can be satisfied.
+    ///
+    /// `template` must contain values of the same types as the containers this
+    /// handler will be dispatched with; the values themselves are ignored. A
+    /// dependency is considered satisfied if it is either in `template` or
+    /// added by a preceding handler in the same chain, e.g., by
+    /// [`map`](crate::map). Values added inside a branch are not visible to
+    /// the handlers after this branch.
+    ///
+    /// Only handlers built from dependency-injected functions record their
+    /// dependencies, so handlers created by [`from_fn`](crate::from_fn) are
+    /// not checked. Types are compared by their names.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dptree::{description::Tree, prelude::*};
+    ///
+    /// let handler: Handler<DependencyMap, String, Tree> = dptree::entry()
+    ///     .map(|x: i32| x.to_string())
+    ///     .endpoint(|s: String, flag: bool| async move { format!("{s} {flag}") });
+    ///
+    /// assert_eq!(handler.validate_deps(&dptree::deps![0i32, false]), Ok(()));
+    ///
+    /// let missing = handler.validate_deps(&dptree::deps![0i32]).unwrap_err();
+    /// assert_eq!(missing.len(), 1);
+    /// assert_eq!(missing[0].type_name, "bool");
+    /// ```
+    pub fn validate_deps(&self, template: &DependencyMap) -> Result<(), Vec<MissingDep>> {
+        let mut available = template.type_names().collect();
+        let mut missing = Vec::new();
+        collect_missing(self.description(), &mut available, &mut missing);
+
+        if missing.is_empty() {
+            Ok(())
+        } else {
+            Err(missing)
+        }
+    }
+
+    /// Checks the dependencies of this handler against `registry` once, at
+    /// build time.
+    ///
+    /// This is [`Handler::validate_deps`] with the types recorded in
+    /// `registry` instead of a template container: a missing dependency is
+    /// reported when the handler is built rather than panicking in
+    /// [`DependencySupplier::get`] during a dispatch.
+    ///
+    /// [`DependencySupplier::get`]: crate::di::DependencySupplier::get
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use dptree::{description::Tree, prelude::*, TypedRegistry};
+    ///
+    /// let handler: Handler<DependencyMap, String, Tree> =
+    ///     dptree::endpoint(|name: &'static str, age: u8| async move { format!("{name}, {age}") });
+    ///
+    /// let missing = handler.clone().build_checked(&TypedRegistry::new().with::<&str>()).unwrap_err();
+    /// assert_eq!(missing[0].type_name, "u8");
+    ///
+    /// let handler = handler.build_checked(&TypedRegistry::new().with::<&str>().with::<u8>()).unwrap();
+    /// assert_eq!(
+    ///     handler.dispatch(dptree::deps!["Alice", 30u8]).await,
+    ///     ControlFlow::Break("Alice, 30".to_owned())
+    /// );
+    /// # }
+    /// ```
+    pub fn build_checked(
+        self,
+        registry: &TypedRegistry,
+    ) -> Result<CheckedHandler<'a, Input, Output>, Vec<MissingDep>> {
+        let mut available = registry.type_names.clone();
+        let mut missing = Vec::new();
+        collect_missing(self.description(), &mut available, &mut missing);
+
+        if missing.is_empty() {
+            Ok(CheckedHandler { handler: self })
+        } else {
+            Err(missing)
+        }
+    }
+
+    /// Returns the type names of the dependencies that containers must
+    /// provide to this handler.
+    ///
+    /// These are the dependencies injected by the nodes of this handler,
+    /// including endpoints, except for those added by a preceding handler, as
+    /// in [`Handler::validate_deps`]. Each type name is listed once, in the
+    /// order of the first node requiring it. This is useful for generating
+    /// documentation, e.g., "this bot requires: ...".
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dptree::{description::Tree, prelude::*};
+    ///
+    /// let handler: Handler<DependencyMap, String, Tree> = dptree::entry()
+    ///     .map(|x: i32| x.to_string())
+    ///     .endpoint(|s: String, flag: bool| async move { format!("{s} {flag}") });
+    ///
+    /// assert_eq!(handler.deps_required(), ["i32", "bool"]);
+    /// ```
+    #[must_use]
+    pub fn deps_required(&self) -> Vec<&'static str> {
+        let mut missing = Vec::new();
+        collect_missing(self.description(), &mut BTreeSet::new(), &mut missing);
+
+        let mut seen = BTreeSet::new();
+        missing.into_iter().map(|dep| dep.type_name).filter(|&ty| seen.insert(ty)).collect()
+    }
+
+    /// Asserts that this handler requires exactly the types in `listed`.
+    ///
+    /// Every dependency of this handler must either be in `listed` or be added
+    /// by a preceding handler, as in [`Handler::validate_deps`]. Additionally,
+    /// every type in `listed` must be required by at least one node of this
+    /// handler, which catches dependencies that nothing consumes. Types are
+    /// identified by their [`type_name`](core::any::type_name)s.
+    ///
+    /// Usually, this method is called through
+    /// [`ensure_deps!`](crate::ensure_deps).
+    ///
+    /// # Panics
+    ///
+    /// Panics if either of the conditions above does not hold, listing the
+    /// offending types.
+    #[track_caller]
+    pub fn ensure_deps(&self, listed: &[&'static str]) {
+        let mut available = listed.iter().copied().collect();
+        let mut missing = Vec::new();
+        collect_missing(self.description(), &mut available, &mut missing);
+
+        let mut required = BTreeSet::new();
+        collect_required(self.description(), &mut required);
+        let unused: Vec<_> = listed.iter().filter(|&ty| !required.contains(ty)).collect();
+
+        let missing: Vec<_> = missing.iter().map(|dep| dep.type_name).collect();
+        if !missing.is_empty() || !unused.is_empty() {
+            panic!(
+                "the handler dependencies do not match the listed types: missing {:?}, unused {:?}",
+                missing, unused
+            );
+        }
+    }
+}
+
+/// A tuple of dependency types, as accepted by [`require_deps`].
+///
+/// Implemented for tuples of up to nine types, each of which is `Send + Sync +
+/// 'static`, as with [`DependencyMap::insert`].
+pub trait DependencySet {
+    /// Returns the type names of the types absent from `container`, in the
+    /// order of the tuple.
+    fn missing_from(container: &DependencyMap) -> Vec<&'static str>;
+}
+
+macro_rules! impl_dependency_set {
+    ($($generic:ident),*) => {
+        impl<$($generic),*> DependencySet for ($($generic,)*)
+        where
+            $($generic: Send + Sync + 'static,)*
+        {
+            #[allow(unused_mut, unused_variables)]
+            fn missing_from(container: &DependencyMap) -> Vec<&'static str> {
+                let mut missing = Vec::new();
+                $(
+                    if !container.contains::<$generic>() {
+                        missing.push(core::any::type_name::<$generic>());
+                    }
+                )*
+                missing
+            }
+        }
+    };
+}
+
+impl_dependency_set!();
+impl_dependency_set!(A);
+impl_dependency_set!(A, B);
+impl_dependency_set!(A, B, C);
+impl_dependency_set!(A, B, C, D);
+impl_dependency_set!(A, B, C, D, E);
+impl_dependency_set!(A, B, C, D, E, F);
+impl_dependency_set!(A, B, C, D, E, F, G);
+impl_dependency_set!(A, B, C, D, E, F, G, H);
+impl_dependency_set!(A, B, C, D, E, F, G, H, I);
+
+/// Constructs a handler that checks that all of `Deps` are in the container.
+///
+/// If the container has a value of each type in `Deps`, the handler passes it
+/// further. Otherwise, it breaks with the output of `err_fn`, which receives
+/// the type names of the missing dependencies. This is the runtime
+/// counterpart of [`Handler::validate_deps`]: placed at the boundary of a
+/// subtree, it reports a missing dependency as an output instead of a panic
+/// deep inside the subtree.
+///
+/// # Examples
+///
+/// ```
+/// # #[tokio::main]
+/// # async fn main() {
+/// use dptree::prelude::*;
+///
+/// let handler: Handler<_, String> = dptree::require_deps::<(i32, bool), _, _, _>(|missing| {
+///     format!("missing {}", missing.join(", "))
+/// })
+/// .endpoint(|x: i32, flag: bool| async move { format!("{x} {flag}") });
+///
+/// assert_eq!(
+///     handler.dispatch(dptree::deps![1, true]).await,
+///     ControlFlow::Break("1 true".to_owned())
+/// );
+/// assert_eq!(
+///     handler.dispatch(dptree::deps![1]).await,
+///     ControlFlow::Break("missing bool".to_owned())
+/// );
+/// # }
+/// ```
+#[must_use]
+#[track_caller]
+pub fn require_deps<'a, Deps, F, Output, Descr>(
+    err_fn: F,
+) -> Handler<'a, DependencyMap, Output, Descr>
+where
+    Deps: DependencySet,
+    F: Fn(&[&'static str]) -> Output + Send + Sync + 'a,
+    Output: 'a,
+    Descr: HandlerDescription,
+{
+    let err_fn = Arc::new(err_fn);
+
+    from_fn_with_description(Descr::user_defined(), move |container: DependencyMap, cont| {
+        let missing = Deps::missing_from(&container);
+        let err_fn = Arc::clone(&err_fn);
+
+        async move {
+            if missing.is_empty() {
+                cont(container).await
+            } else {
+                ControlFlow::Break(err_fn(&missing))
+            }
+        }
+    })
+}
+
+impl<'a, Output, Descr> Handler<'a, DependencyMap, Output, Descr>
+where
+    Output: 'a,
+    Descr: HandlerDescription,
+{
+    /// Chain this handler with a check that all of `Deps` are in the
+    /// container.
+    ///
+    /// See [`require_deps`] for the details.
+    #[must_use]
+    #[track_caller]
+    pub fn require_deps<Deps, F>(self, err_fn: F) -> Handler<'a, DependencyMap, Output, Descr>
+    where
+        Deps: DependencySet,
+        F: Fn(&[&'static str]) -> Output + Send + Sync + 'a,
+    {
+        self.chain(require_deps::<Deps, F, Output, Descr>(err_fn))
+    }
+}
+
+fn collect_missing(
+    tree: &Tree,
+    available: &mut BTreeSet<&'static str>,
+    missing: &mut Vec<MissingDep>,
+) {
+    match tree.kind {
+        NodeKind::Chain => {
+            for child in &tree.children {
+                collect_missing(child, available, missing);
+            }
+        }
+        NodeKind::Branch => {
+            for child in &tree.children {
+                collect_missing(child, &mut available.clone(), missing);
+            }
+        }
+        kind => {
+            missing.extend(
+                tree.required
+                    .iter()
+                    .filter(|&&ty| !available.contains(ty))
+                    .map(|&type_name| MissingDep { type_name, kind, label: tree.label }),
+            );
+            available.extend(tree.provided.iter().copied());
+        }
+    }
+}
+
+fn collect_required(tree: &Tree, required: &mut BTreeSet<&'static str>) {
+    required.extend(tree.required.iter().copied());
+    for child in &tree.children {
+        collect_required(child, required);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::deps;
+    use core::ops::ControlFlow;
+
+    #[tokio::test]
+    async fn test_require_deps() {
+        let handler: Handler<DependencyMap, Result<i64, Vec<&str>>> = crate::entry()
+            .require_deps::<(i32, u8, bool), _>(|missing| Err(missing.to_vec()))
+            .endpoint(|x: i32, y: u8, flag: bool| async move {
+                Ok(if flag { x as i64 + y as i64 } else { 0 })
+            });
+
+        assert_eq!(handler.dispatch(deps![1, 2u8, true]).await, ControlFlow::Break(Ok(3)));
+        assert_eq!(handler.dispatch(deps![1, true]).await, ControlFlow::Break(Err(vec!["u8"])));
+        assert_eq!(
+            handler.dispatch(deps!["unrelated"]).await,
+            ControlFlow::Break(Err(vec!["i32", "u8", "bool"]))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_require_deps_continue() {
+        let handler: Handler<DependencyMap, &str> = crate::entry()
+            .branch(
+                require_deps::<(i32,), _, _, _>(|_| "missing")
+                    .filter(|x: i32| x > 0)
+                    .endpoint(|| async { "positive" }),
+            )
+            .branch(crate::endpoint(|| async { "fallback" }));
+
+        assert_eq!(handler.dispatch(deps![1]).await, ControlFlow::Break("positive"));
+        // The dependency is present, but the rest of the subtree continues.
+        assert_eq!(handler.dispatch(deps![-1]).await, ControlFlow::Break("fallback"));
+        // The dependency is absent, so the next branches are not tried.
+        assert_eq!(handler.dispatch(deps![]).await, ControlFlow::Break("missing"));
+    }
+
+    #[test]
+    fn test_require_deps_description() {
+        // The error is a break, so the handler is not empty.
+        let handler: Handler<DependencyMap, (), Tree> =
+            crate::entry().chain(require_deps::<(i32,), _, _, _>(|_| ()));
+        let _ = handler.require_non_empty();
+    }
+
+    #[test]
+    fn test_validate_deps() {
+        let handler: Handler<DependencyMap, (), Tree> = crate::entry()
+            .branch(crate::filter(|x: i32| x > 0).endpoint(|_: &'static str| async {}))
+            .branch(crate::endpoint(|_: bool| async {}).label("flag"));
+
+        assert_eq!(handler.validate_deps(&deps![0, "", true]), Ok(()));
+        assert_eq!(
+            handler.validate_deps(&deps![0, ""]),
+            Err(vec![MissingDep {
+                type_name: "bool",
+                kind: NodeKind::Endpoint,
+                label: Some("flag")
+            }])
+        );
+    }
+
+    #[test]
+    fn test_validate_deps_provided() {
+        let handler: Handler<DependencyMap, (), Tree> = crate::entry()
+            .branch(crate::map(|| 1u8).endpoint(|_: u8| async {}))
+            .branch(crate::filter_map(|x: i32| Some(x as i64)).inspect(|_: i64| {}))
+            .branch(crate::endpoint(|_: u8, _: i64| async {}));
+
+        // `u8` and `i64` are added only inside the first two branches.
+        assert_eq!(
+            handler.validate_deps(&deps![0i32]),
+            Err(vec![
+                MissingDep { type_name: "u8", kind: NodeKind::Endpoint, label: None },
+                MissingDep { type_name: "i64", kind: NodeKind::Endpoint, label: None },
+            ])
+        );
+    }
+
+    #[test]
+    fn test_deps_required() {
+        let handler: Handler<DependencyMap, (), Tree> = crate::entry()
+            .branch(crate::filter(|x: i32| x > 0).endpoint(|_: &'static str, _: i32| async {}))
+            .branch(crate::map(|| 1u8).endpoint(|_: u8, _: bool, _: &'static str| async {}));
+
+        // `u8` is added by the handler itself.
+        assert_eq!(handler.deps_required(), ["i32", "&str", "bool"]);
+    }
+
+    #[tokio::test]
+    async fn test_build_checked() {
+        let handler: Handler<DependencyMap, i64, Tree> = crate::entry()
+            .branch(
+                crate::filter(|x: i32| x > 0)
+                    .map(|x: i32| x as i64)
+                    .endpoint(|x: i64| async move { x }),
+            )
+            .branch(crate::endpoint(|x: i32, offset: u64| async move { x as i64 + offset as i64 }));
+
+        let registry = TypedRegistry::new().with::<i32>();
+        assert_eq!(
+            handler.clone().build_checked(&registry).unwrap_err(),
+            vec![MissingDep { type_name: "u64", kind: NodeKind::Endpoint, label: None }]
+        );
+
+        let handler = handler.build_checked(&registry.with::<u64>()).unwrap();
+        assert_eq!(handler.dispatch(deps![-1i32, 5u64]).await, ControlFlow::Break(4));
+    }
+
+    #[test]
+    fn test_typed_registry() {
+        let mut registry = TypedRegistry::from(&deps![0i32, "abc"]);
+        registry.insert::<u8>();
+
+        assert!(registry.contains::<i32>());
+        assert!(registry.contains::<&'static str>());
+        assert!(registry.contains::<u8>());
+        assert!(!registry.contains::<u16>());
+    }
+
+    #[test]
+    fn test_ensure_deps() {
+        let handler: Handler<DependencyMap, (), Tree> = crate::entry()
+            .branch(crate::map(|x: i32| x as u8).endpoint(|_: u8| async {}))
+            .branch(crate::endpoint(|_: bool| async {}));
+
+        crate::ensure_deps!(handler, [i32, bool]);
+        crate::ensure_deps!(handler, [bool, i32,]);
+    }
+
+    #[test]
+    #[should_panic(expected = "the handler dependencies do not match the listed types: missing \
+                               [\"bool\"], unused []")]
+    fn test_ensure_deps_missing() {
+        let handler: Handler<DependencyMap, (), Tree> =
+            crate::entry().map(|x: i32| x as u8).endpoint(|_: u8, _: bool| async {});
+
+        crate::ensure_deps!(handler, [i32]);
+    }
+
+    #[test]
+    #[should_panic(expected = "the handler dependencies do not match the listed types: missing \
+                               [], unused [\"u8\"]")]
+    fn test_ensure_deps_unused() {
+        let handler: Handler<DependencyMap, (), Tree> = crate::entry().endpoint(|_: i32| async {});
+
+        crate::ensure_deps!(handler, [i32, u8]);
+    }
+}
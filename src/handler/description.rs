@@ -1,9 +1,13 @@
 //! Built-in handler description types.
 
+#[cfg(feature = "std")]
 mod interest_set;
+mod tree;
 mod unspecified;
 
+#[cfg(feature = "std")]
 pub use interest_set::{EventKind, InterestSet};
+pub use tree::{EndpointInfo, NodeKind, NodeLabel, Tree, Visitor};
 pub use unspecified::Unspecified;
 
 /// Handler description.
@@ -70,6 +74,18 @@ pub trait HandlerDescription: Sized + Send + Sync + 'static {
     /// Merge descriptions to get a description for a branch handler.
     fn merge_branch(&self, other: &Self) -> Self;
 
+    /// Merge descriptions to get a description for
+    /// [`flat_branch`](crate::Handler::flat_branch), which splices the
+    /// branches of `other` into the branches of `self`.
+    ///
+    /// ## Default implementation
+    ///
+    /// By default this returns the value from
+    /// [`merge_branch`](HandlerDescription::merge_branch).
+    fn merge_flat_branch(&self, other: &Self) -> Self {
+        self.merge_branch(other)
+    }
+
     /// Description for [`map`](crate::map).
     ///
     /// ## Default implementation
@@ -92,6 +108,26 @@ pub trait HandlerDescription: Sized + Send + Sync + 'static {
         Self::user_defined()
     }
 
+    /// Description for [`map_using`](crate::map_using).
+    ///
+    /// ## Default implementation
+    ///
+    /// By default this returns the value from [`map`](HandlerDescription::map).
+    #[track_caller]
+    fn map_using() -> Self {
+        Self::map()
+    }
+
+    /// Description for [`map_or_continue`](crate::map_or_continue).
+    ///
+    /// ## Default implementation
+    ///
+    /// By default this returns the value from [`map`](HandlerDescription::map).
+    #[track_caller]
+    fn map_or_continue() -> Self {
+        Self::map()
+    }
+
     /// Description for [`filter`](crate::filter).
     ///
     /// ## Default implementation
@@ -158,7 +194,7 @@ pub trait HandlerDescription: Sized + Send + Sync + 'static {
         Self::user_defined()
     }
 
-    /// Description for [`endpoint`](crate::endpoint).
+    /// Description for [`endpoint`](fn@crate::endpoint).
     ///
     /// ## Default implementation
     ///
@@ -168,4 +204,20 @@ pub trait HandlerDescription: Sized + Send + Sync + 'static {
     fn endpoint() -> Self {
         Self::user_defined()
     }
+
+    /// Attaches the dependencies of a handler to its description.
+    ///
+    /// `required` are the type names of values that the handler takes from its
+    /// input, `provided` are the type names of values that it adds to the
+    /// input. This is called by handlers built from dependency-injected
+    /// functions, such as [`filter`](crate::filter), [`map`](crate::map), or
+    /// [`endpoint`](fn@crate::endpoint).
+    ///
+    /// ## Default implementation
+    ///
+    /// By default this returns `self` unchanged.
+    fn with_dependencies(self, required: &[&'static str], provided: &[&'static str]) -> Self {
+        let _ = (required, provided);
+        self
+    }
 }
@@ -0,0 +1,874 @@
+use alloc::{vec, vec::Vec};
+use core::fmt::{self, Debug, Formatter};
+
+use crate::{from_fn_with_description, Handler, HandlerDescription};
+
+/// Description that records the structure of a handler tree.
+///
+/// Each handler is described by a node of a certain [`NodeKind`], an optional
+/// label, and its children. Chains and branches are flattened, so
+/// `a.chain(b).chain(c)` results in a single [`NodeKind::Chain`] node with
+/// three children, and `entry().branch(a).branch(b)` results in a single
+/// [`NodeKind::Branch`] node with two children. An unlabeled
+/// [`entry`](crate::entry) is invisible in the resulting tree.
+///
+/// Labels can be attached by [`Handler::label`](crate::Handler::label) or by
+/// [`Tree::labeled`] when constructing handlers with custom descriptions.
+/// Labeled nodes are never flattened into their parents.
+///
+/// The [`Debug`] implementation renders the tree in a compact, indented form.
+///
+/// # Examples
+///
+/// ```
+/// use dptree::{description::Tree, prelude::DependencyMap, Handler};
+///
+/// let handler: Handler<DependencyMap, (), Tree> = dptree::entry()
+///     .branch(dptree::filter(|x: i32| x > 0).label("positive").endpoint(|| async {}))
+///     .branch(dptree::endpoint(|| async {}).label("fallback"));
+///
+/// assert_eq!(
+///     format!("{:?}", handler),
+///     "branch\n  chain\n    filter \"positive\"\n    endpoint\n  endpoint \"fallback\""
+/// );
+/// ```
+#[derive(Clone, PartialEq, Eq)]
+pub struct Tree {
+    /// The kind of this node.
+    pub kind: NodeKind,
+
+    /// A human-readable label of this node, if any.
+    pub label: Option<&'static str>,
+
+    /// Children of this node.
+    ///
+    /// Only [`NodeKind::Chain`] and [`NodeKind::Branch`] nodes have children.
+    pub children: Vec<Tree>,
+
+    /// Type names of values that this node takes from its input.
+    ///
+    /// Only nodes built from dependency-injected functions record their
+    /// dependencies; see [`HandlerDescription::with_dependencies`].
+    pub required: Vec<&'static str>,
+
+    /// Type names of values that this node adds to its input.
+    pub provided: Vec<&'static str>,
+}
+
+/// A kind of a [`Tree`] node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum NodeKind {
+    /// [`entry`](crate::entry).
+    Entry,
+    /// A user-defined handler.
+    UserDefined,
+    /// Handlers executed one after another.
+    Chain,
+    /// Handlers tried one after another until one of them breaks.
+    Branch,
+    /// [`map`](crate::map).
+    Map,
+    /// [`map_async`](crate::map_async).
+    MapAsync,
+    /// [`map_using`](crate::map_using).
+    MapUsing,
+    /// [`map_or_continue`](crate::map_or_continue).
+    MapOrContinue,
+    /// [`filter`](crate::filter).
+    Filter,
+    /// [`filter_async`](crate::filter_async).
+    FilterAsync,
+    /// [`filter_map`](crate::filter_map).
+    FilterMap,
+    /// [`filter_map_async`](crate::filter_map_async).
+    FilterMapAsync,
+    /// [`inspect`](crate::inspect).
+    Inspect,
+    /// [`inspect_async`](crate::inspect_async).
+    InspectAsync,
+    /// [`endpoint`](fn@crate::endpoint).
+    Endpoint,
+}
+
+/// A node on a path through a [`Tree`].
+///
+/// See [`Tree::paths`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NodeLabel {
+    /// The kind of the node.
+    pub kind: NodeKind,
+
+    /// The label of the node, if any.
+    pub label: Option<&'static str>,
+}
+
+/// An endpoint of a [`Tree`] along with the filters guarding it.
+///
+/// See [`Handler::collect_endpoints`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct EndpointInfo {
+    /// The label of the endpoint node itself, if any.
+    ///
+    /// Note that labeling a chain ending with an endpoint, as in
+    /// `handler.endpoint(f).label("...")`, labels the chain rather than the
+    /// endpoint; use `handler.chain(dptree::endpoint(f).label("..."))` instead.
+    pub label: Option<&'static str>,
+
+    /// The labels of the labeled filters on the path to the endpoint, from the
+    /// root to the endpoint.
+    ///
+    /// Filters include [`NodeKind::Filter`], [`NodeKind::FilterAsync`],
+    /// [`NodeKind::FilterMap`], and [`NodeKind::FilterMapAsync`] nodes.
+    pub filters: Vec<&'static str>,
+}
+
+/// A walker over the nodes of a [`Tree`].
+///
+/// Both methods do nothing by default, so that implementors only need to
+/// override the ones they are interested in.
+///
+/// See [`Tree::visit`] and [`Handler::visit`].
+pub trait Visitor {
+    /// Called when the walk enters a node, before its children.
+    fn enter_node(&mut self, kind: NodeKind, label: Option<&'static str>) {
+        let _ = (kind, label);
+    }
+
+    /// Called when the walk leaves a node, after its children.
+    fn exit_node(&mut self, kind: NodeKind, label: Option<&'static str>) {
+        let _ = (kind, label);
+    }
+}
+
+impl Tree {
+    /// Constructs a childless, unlabeled node of the given kind.
+    pub fn new(kind: NodeKind) -> Self {
+        Self::with_children(kind, Vec::new())
+    }
+
+    fn with_children(kind: NodeKind, children: Vec<Self>) -> Self {
+        Self { kind, label: None, children, required: Vec::new(), provided: Vec::new() }
+    }
+
+    /// Attaches `label` to this node, replacing the previous one.
+    #[must_use]
+    pub fn labeled(self, label: &'static str) -> Self {
+        Self { label: Some(label), ..self }
+    }
+
+    /// Returns every distinct path from the root of this tree to an
+    /// [`NodeKind::Endpoint`] node.
+    ///
+    /// A path lists the nodes that the execution passes through, in order.
+    /// Unlabeled chains, branches, and entries only shape the paths and are
+    /// not listed themselves. Paths that end without reaching an endpoint,
+    /// e.g., a branch consisting of a single filter, are not returned.
+    pub fn paths(&self) -> Vec<Vec<NodeLabel>> {
+        self.partial_paths()
+            .into_iter()
+            .filter_map(|(path, complete)| if complete { Some(path) } else { None })
+            .collect()
+    }
+
+    /// Walks this tree depth-first, calling `visitor` on each node.
+    ///
+    /// Every node, including unlabeled chains, branches, and entries, is
+    /// entered and then exited exactly once; the children of a node are
+    /// walked in order between these two calls.
+    pub fn visit<V>(&self, visitor: &mut V)
+    where
+        V: Visitor + ?Sized,
+    {
+        visitor.enter_node(self.kind, self.label);
+        for child in &self.children {
+            child.visit(visitor);
+        }
+        visitor.exit_node(self.kind, self.label);
+    }
+
+    // Returns the paths through this node, each with a flag telling whether it
+    // has reached an endpoint.
+    fn partial_paths(&self) -> Vec<(Vec<NodeLabel>, bool)> {
+        let prefix = match (self.kind, self.label) {
+            (NodeKind::Entry | NodeKind::Chain | NodeKind::Branch, None) => vec![],
+            (kind, label) => vec![NodeLabel { kind, label }],
+        };
+
+        match self.kind {
+            NodeKind::Chain => {
+                let mut paths = vec![(prefix, false)];
+                for child in &self.children {
+                    paths = paths
+                        .into_iter()
+                        .flat_map(|(path, complete)| {
+                            if complete {
+                                return vec![(path, true)];
+                            }
+                            child
+                                .partial_paths()
+                                .into_iter()
+                                .map(|(rest, complete)| {
+                                    let mut path = path.clone();
+                                    path.extend(rest);
+                                    (path, complete)
+                                })
+                                .collect()
+                        })
+                        .collect();
+                }
+                paths
+            }
+            NodeKind::Branch => self
+                .children
+                .iter()
+                .flat_map(Tree::partial_paths)
+                .map(|(rest, complete)| {
+                    let mut path = prefix.clone();
+                    path.extend(rest);
+                    (path, complete)
+                })
+                .collect(),
+            kind => vec![(prefix, kind == NodeKind::Endpoint)],
+        }
+    }
+
+    fn is_transparent(&self, kind: NodeKind) -> bool {
+        self.kind == kind && self.label.is_none()
+    }
+
+    fn into_items(self, kind: NodeKind) -> Vec<Self> {
+        if self.is_transparent(kind) {
+            self.children
+        } else {
+            vec![self]
+        }
+    }
+
+    fn fmt_indented(&self, f: &mut Formatter<'_>, depth: usize) -> fmt::Result {
+        write!(f, "{:indent$}{}", "", self.kind, indent = depth * 2)?;
+        if let Some(label) = self.label {
+            write!(f, " {:?}", label)?;
+        }
+
+        for child in &self.children {
+            writeln!(f)?;
+            child.fmt_indented(f, depth + 1)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for NodeKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            NodeKind::Entry => "entry",
+            NodeKind::UserDefined => "user_defined",
+            NodeKind::Chain => "chain",
+            NodeKind::Branch => "branch",
+            NodeKind::Map => "map",
+            NodeKind::MapAsync => "map_async",
+            NodeKind::MapUsing => "map_using",
+            NodeKind::MapOrContinue => "map_or_continue",
+            NodeKind::Filter => "filter",
+            NodeKind::FilterAsync => "filter_async",
+            NodeKind::FilterMap => "filter_map",
+            NodeKind::FilterMapAsync => "filter_map_async",
+            NodeKind::Inspect => "inspect",
+            NodeKind::InspectAsync => "inspect_async",
+            NodeKind::Endpoint => "endpoint",
+        };
+
+        f.write_str(name)
+    }
+}
+
+impl Debug for Tree {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.fmt_indented(f, 0)
+    }
+}
+
+impl HandlerDescription for Tree {
+    fn entry() -> Self {
+        Self::new(NodeKind::Entry)
+    }
+
+    fn user_defined() -> Self {
+        Self::new(NodeKind::UserDefined)
+    }
+
+    fn merge_chain(&self, other: &Self) -> Self {
+        if self.is_transparent(NodeKind::Entry) {
+            return other.clone();
+        }
+        if other.is_transparent(NodeKind::Entry) {
+            return self.clone();
+        }
+
+        let mut children = self.clone().into_items(NodeKind::Chain);
+        children.extend(other.clone().into_items(NodeKind::Chain));
+
+        Self::with_children(NodeKind::Chain, children)
+    }
+
+    fn merge_branch(&self, other: &Self) -> Self {
+        // `a.branch(b)` executes `a` and then tries `b`, so if `a` already ends
+        // with a group of branches, `b` simply joins this group.
+        if self.is_transparent(NodeKind::Entry) {
+            return Self::with_children(NodeKind::Branch, vec![other.clone()]);
+        }
+        if self.is_transparent(NodeKind::Branch) {
+            let mut this = self.clone();
+            this.children.push(other.clone());
+            return this;
+        }
+
+        let mut children = self.clone().into_items(NodeKind::Chain);
+        match children.last_mut() {
+            Some(last) if last.is_transparent(NodeKind::Branch) => {
+                last.children.push(other.clone())
+            }
+            _ => children.push(Self::with_children(NodeKind::Branch, vec![other.clone()])),
+        }
+
+        Self::with_children(NodeKind::Chain, children)
+    }
+
+    fn merge_flat_branch(&self, other: &Self) -> Self {
+        if other.is_transparent(NodeKind::Branch) {
+            other.children.iter().fold(self.clone(), |this, child| this.merge_branch(child))
+        } else {
+            self.merge_branch(other)
+        }
+    }
+
+    fn map() -> Self {
+        Self::new(NodeKind::Map)
+    }
+
+    fn map_async() -> Self {
+        Self::new(NodeKind::MapAsync)
+    }
+
+    fn map_using() -> Self {
+        Self::new(NodeKind::MapUsing)
+    }
+
+    fn map_or_continue() -> Self {
+        Self::new(NodeKind::MapOrContinue)
+    }
+
+    fn filter() -> Self {
+        Self::new(NodeKind::Filter)
+    }
+
+    fn filter_async() -> Self {
+        Self::new(NodeKind::FilterAsync)
+    }
+
+    fn filter_map() -> Self {
+        Self::new(NodeKind::FilterMap)
+    }
+
+    fn filter_map_async() -> Self {
+        Self::new(NodeKind::FilterMapAsync)
+    }
+
+    fn inspect() -> Self {
+        Self::new(NodeKind::Inspect)
+    }
+
+    fn inspect_async() -> Self {
+        Self::new(NodeKind::InspectAsync)
+    }
+
+    fn endpoint() -> Self {
+        Self::new(NodeKind::Endpoint)
+    }
+
+    fn with_dependencies(self, required: &[&'static str], provided: &[&'static str]) -> Self {
+        Self { required: required.to_vec(), provided: provided.to_vec(), ..self }
+    }
+}
+
+impl<'a, Input, Output> Handler<'a, Input, Output, Tree>
+where
+    Input: Send + 'a,
+    Output: 'a,
+{
+    /// Attaches `label` to the description of this handler.
+    ///
+    /// See [`Tree`] for the details.
+    #[must_use]
+    #[track_caller]
+    pub fn label(self, label: &'static str) -> Self {
+        let description = self.wrapper_description().labeled(label);
+        #[cfg(feature = "std")]
+        let kind = description.kind;
+
+        from_fn_with_description(description, move |event, cont| {
+            #[cfg(feature = "std")]
+            let cont = crate::handler::filter_trace::record(kind, label, cont);
+            self.clone().execute(event, cont)
+        })
+    }
+
+    /// Asserts that this handler can break, returning it unchanged.
+    ///
+    /// A handler without endpoints, such as a bare [`entry`](crate::entry),
+    /// always returns [`ControlFlow::Continue`], which is usually a
+    /// misconfiguration. This method catches it when the handler is built,
+    /// rather than when it silently does nothing. Nodes of the
+    /// [`NodeKind::UserDefined`] kind may break as well, so a handler with
+    /// such nodes is accepted; this includes wrappers that break on their own,
+    /// such as [`Handler::or_default`] and [`Handler::cancel_on`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the description of this handler contains no
+    /// [`NodeKind::Endpoint`] and no [`NodeKind::UserDefined`] nodes.
+    ///
+    /// [`ControlFlow::Continue`]: core::ops::ControlFlow::Continue
+    ///
+    /// # Examples
+    ///
+    /// ```should_panic
+    /// use dptree::{description::Tree, prelude::*};
+    ///
+    /// // The branches are filters, but there is nothing to break with.
+    /// let handler: Handler<DependencyMap, (), Tree> = dptree::entry()
+    ///     .branch(dptree::filter(|x: i32| x > 0))
+    ///     .branch(dptree::filter(|x: i32| x < 0))
+    ///     .require_non_empty();
+    /// ```
+    #[must_use]
+    #[track_caller]
+    pub fn require_non_empty(self) -> Self {
+        fn can_break(tree: &Tree) -> bool {
+            matches!(tree.kind, NodeKind::Endpoint | NodeKind::UserDefined)
+                || tree.children.iter().any(can_break)
+        }
+
+        if !can_break(self.description()) {
+            panic!(
+                "the handler has no endpoints, so it always continues; add an endpoint with \
+                 `Handler::endpoint`"
+            );
+        }
+        self
+    }
+
+    /// Walks the description of this handler with `visitor`.
+    ///
+    /// See [`Tree::visit`] for the details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dptree::{
+    ///     description::{NodeKind, Tree, Visitor},
+    ///     prelude::*,
+    /// };
+    ///
+    /// #[derive(Default)]
+    /// struct Filters(Vec<&'static str>);
+    ///
+    /// impl Visitor for Filters {
+    ///     fn enter_node(&mut self, kind: NodeKind, label: Option<&'static str>) {
+    ///         if let (NodeKind::Filter, Some(label)) = (kind, label) {
+    ///             self.0.push(label);
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// let handler: Handler<DependencyMap, (), Tree> = dptree::entry()
+    ///     .branch(dptree::filter(|x: i32| x > 0).label("positive").endpoint(|| async {}))
+    ///     .branch(dptree::filter(|x: i32| x < 0).label("negative").endpoint(|| async {}));
+    ///
+    /// let mut filters = Filters::default();
+    /// handler.visit(&mut filters);
+    /// assert_eq!(filters.0, ["positive", "negative"]);
+    /// ```
+    pub fn visit<V>(&self, visitor: &mut V)
+    where
+        V: Visitor + ?Sized,
+    {
+        self.description().visit(visitor);
+    }
+
+    /// Returns every distinct path from the root of this handler to an
+    /// endpoint.
+    ///
+    /// See [`Tree::paths`] for the details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dptree::{
+    ///     description::{NodeKind, NodeLabel, Tree},
+    ///     prelude::*,
+    /// };
+    ///
+    /// let handler: Handler<DependencyMap, (), Tree> = dptree::entry()
+    ///     .branch(
+    ///         dptree::filter(|s: &'static str| s == "/start").label("/start").endpoint(|| async {}),
+    ///     )
+    ///     .branch(
+    ///         dptree::filter(|s: &'static str| s == "/help").label("/help").endpoint(|| async {}),
+    ///     );
+    ///
+    /// let commands: Vec<_> =
+    ///     handler.describe_paths().iter().map(|path| path[0].label.unwrap()).collect();
+    /// assert_eq!(commands, ["/start", "/help"]);
+    /// ```
+    pub fn describe_paths(&self) -> Vec<Vec<NodeLabel>> {
+        self.description().paths()
+    }
+
+    /// Returns every endpoint of this handler along with the labels of the
+    /// filters leading to it.
+    ///
+    /// Endpoints reachable by several paths are listed once per path, in the
+    /// order of [`Handler::describe_paths`]. This is useful for generating a
+    /// list of available commands, e.g., for a `/help` command.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dptree::{description::Tree, prelude::*};
+    ///
+    /// let handler: Handler<DependencyMap, (), Tree> = dptree::entry()
+    ///     .branch(
+    ///         dptree::filter(|s: &'static str| s.starts_with("/ban"))
+    ///             .label("/ban")
+    ///             .chain(dptree::filter(|admin: bool| admin).label("admins only"))
+    ///             .chain(dptree::endpoint(|| async {}).label("Bans a user")),
+    ///     )
+    ///     .branch(dptree::endpoint(|| async {}).label("Replies to messages"));
+    ///
+    /// let endpoints = handler.collect_endpoints();
+    /// let labels: Vec<_> = endpoints.iter().map(|endpoint| endpoint.label.unwrap()).collect();
+    /// assert_eq!(labels, ["Bans a user", "Replies to messages"]);
+    /// assert_eq!(endpoints[0].filters, ["/ban", "admins only"]);
+    /// assert!(endpoints[1].filters.is_empty());
+    /// ```
+    pub fn collect_endpoints(&self) -> Vec<EndpointInfo> {
+        self.describe_paths()
+            .into_iter()
+            .filter_map(|path| {
+                let (endpoint, ancestors) = path.split_last()?;
+                let filters = ancestors
+                    .iter()
+                    .filter(|node| {
+                        matches!(
+                            node.kind,
+                            NodeKind::Filter
+                                | NodeKind::FilterAsync
+                                | NodeKind::FilterMap
+                                | NodeKind::FilterMapAsync
+                        )
+                    })
+                    .filter_map(|node| node.label)
+                    .collect();
+
+                Some(EndpointInfo { label: endpoint.label, filters })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{deps, di::DependencyMap};
+    use std::ops::ControlFlow;
+
+    fn node(kind: NodeKind, children: Vec<Tree>) -> Tree {
+        Tree::with_children(kind, children)
+    }
+
+    #[test]
+    fn require_non_empty() {
+        let handler: Handler<DependencyMap, (), Tree> = crate::entry()
+            .branch(crate::filter(|| true))
+            .branch(crate::filter(|| true).endpoint(|| async {}));
+        let _ = handler.require_non_empty();
+
+        let handler: Handler<DependencyMap, (), Tree> = crate::from_fn(|event, cont| cont(event));
+        let _ = handler.require_non_empty();
+    }
+
+    #[test]
+    fn require_non_empty_breaking_wrappers() {
+        let handler: Handler<DependencyMap, &str, Tree> =
+            crate::filter(|| false).or_default("fallback");
+        let _ = handler.require_non_empty();
+
+        let handler: Handler<DependencyMap, &str, Tree> =
+            crate::filter(|| false).cancel_on::<std::sync::Arc<std::sync::atomic::AtomicBool>>("");
+        let _ = handler.require_non_empty();
+    }
+
+    #[test]
+    #[should_panic(expected = "the handler has no endpoints")]
+    fn require_non_empty_entry() {
+        let handler: Handler<DependencyMap, (), Tree> = crate::entry();
+        let _ = handler.require_non_empty();
+    }
+
+    #[test]
+    #[should_panic(expected = "the handler has no endpoints")]
+    fn require_non_empty_filters() {
+        let handler: Handler<DependencyMap, (), Tree> = crate::entry()
+            .branch(crate::filter(|| true).map(|| 1))
+            .branch(crate::entry().chain(crate::inspect(|| {})));
+        let _ = handler.require_non_empty();
+    }
+
+    #[test]
+    fn flattening() {
+        let handler: Handler<DependencyMap, (), Tree> = crate::entry()
+            .branch(crate::filter(|| true).map(|| 1).endpoint(|| async {}))
+            .branch(crate::entry().chain(crate::inspect(|| {})))
+            .chain(crate::endpoint(|| async {}));
+
+        let expected = node(
+            NodeKind::Chain,
+            vec![
+                node(
+                    NodeKind::Branch,
+                    vec![
+                        node(
+                            NodeKind::Chain,
+                            vec![
+                                Tree::new(NodeKind::Filter),
+                                Tree::new(NodeKind::Map).with_dependencies(&[], &["i32"]),
+                                Tree::new(NodeKind::Endpoint),
+                            ],
+                        ),
+                        Tree::new(NodeKind::Inspect),
+                    ],
+                ),
+                Tree::new(NodeKind::Endpoint),
+            ],
+        );
+
+        assert_eq!(*handler.description(), expected);
+    }
+
+    #[test]
+    fn branch_after_chain() {
+        let handler: Handler<DependencyMap, (), Tree> = crate::filter(|| true)
+            .branch(crate::endpoint(|| async {}))
+            .branch(crate::endpoint(|| async {}));
+
+        let expected = node(
+            NodeKind::Chain,
+            vec![
+                Tree::new(NodeKind::Filter),
+                node(
+                    NodeKind::Branch,
+                    vec![Tree::new(NodeKind::Endpoint), Tree::new(NodeKind::Endpoint)],
+                ),
+            ],
+        );
+
+        assert_eq!(*handler.description(), expected);
+    }
+
+    #[tokio::test]
+    async fn labels() {
+        let handler: Handler<DependencyMap, i32, Tree> = crate::entry()
+            .branch(crate::filter(|x: i32| x == 1).label("one").endpoint(|| async { 1 }))
+            .branch(crate::endpoint(|| async { 0 }).label("other"))
+            .label("root");
+
+        let debug = format!("{:?}", handler);
+        assert!(debug.starts_with("branch \"root\""));
+        assert!(debug.contains("filter \"one\""));
+        assert!(debug.contains("endpoint \"other\""));
+
+        // Labeling does not affect the behaviour.
+        assert_eq!(handler.dispatch(deps![1]).await, ControlFlow::Break(1));
+        assert_eq!(handler.dispatch(deps![2]).await, ControlFlow::Break(0));
+    }
+
+    #[test]
+    fn paths() {
+        let handler: Handler<DependencyMap, (), Tree> = crate::entry()
+            .inspect(|| {})
+            .branch(crate::filter(|| true).label("start").endpoint(|| async {}))
+            .branch(crate::map(|| 1).endpoint(|| async {}).label("fallback"));
+
+        let label = |kind, label| NodeLabel { kind, label };
+        assert_eq!(
+            handler.describe_paths(),
+            vec![
+                vec![
+                    label(NodeKind::Inspect, None),
+                    label(NodeKind::Filter, Some("start")),
+                    label(NodeKind::Endpoint, None),
+                ],
+                vec![
+                    label(NodeKind::Inspect, None),
+                    label(NodeKind::Chain, Some("fallback")),
+                    label(NodeKind::Map, None),
+                    label(NodeKind::Endpoint, None),
+                ],
+            ]
+        );
+    }
+
+    #[test]
+    fn flat_branch() {
+        let sub: Handler<DependencyMap, (), Tree> = crate::entry()
+            .branch(crate::filter(|| true).label("a").endpoint(|| async {}))
+            .branch(crate::filter(|| true).label("b").endpoint(|| async {}));
+        let labeled = sub.clone().label("labeled");
+
+        let handler: Handler<DependencyMap, (), Tree> = crate::entry()
+            .inspect(|| {})
+            .branch(crate::endpoint(|| async {}).label("first"))
+            .flat_branch(sub)
+            .flat_branch(labeled);
+
+        let tree = handler.description();
+        assert_eq!(tree.kind, NodeKind::Chain);
+        assert_eq!(tree.children[0].kind, NodeKind::Inspect);
+
+        let branches = &tree.children[1];
+        assert_eq!(branches.kind, NodeKind::Branch);
+        let labels: Vec<_> = branches
+            .children
+            .iter()
+            .map(|child| child.label.or_else(|| child.children[0].label))
+            .collect();
+        // A labeled group is not a plain group of branches, so it stays nested.
+        assert_eq!(labels, [Some("first"), Some("a"), Some("b"), Some("labeled")]);
+    }
+
+    #[test]
+    fn paths_through_branches() {
+        // The handlers chained after a branch continue each of its children, so
+        // the last endpoint is reachable from the first child only.
+        let handler: Handler<DependencyMap, (), Tree> = crate::entry()
+            .branch(crate::filter(|| true))
+            .branch(crate::endpoint(|| async {}))
+            .chain(crate::endpoint(|| async {}));
+
+        assert_eq!(
+            handler.describe_paths(),
+            vec![
+                vec![
+                    NodeLabel { kind: NodeKind::Filter, label: None },
+                    NodeLabel { kind: NodeKind::Endpoint, label: None },
+                ],
+                vec![NodeLabel { kind: NodeKind::Endpoint, label: None }],
+            ]
+        );
+    }
+
+    #[test]
+    fn endpoints() {
+        let handler: Handler<DependencyMap, (), Tree> = crate::entry()
+            .branch(
+                crate::filter(|| true)
+                    .label("/start")
+                    .map(|| 1)
+                    .chain(crate::endpoint(|| async {}).label("start")),
+            )
+            .branch(
+                crate::filter(|| true)
+                    .label("/ban")
+                    .chain(crate::filter_map(|| Some(true)).label("admin"))
+                    .branch(crate::endpoint(|| async {}).label("ban"))
+                    .branch(crate::endpoint(|| async {})),
+            )
+            .branch(crate::filter(|| true).label("dead end"));
+
+        assert_eq!(
+            handler.collect_endpoints(),
+            vec![
+                EndpointInfo { label: Some("start"), filters: vec!["/start"] },
+                EndpointInfo { label: Some("ban"), filters: vec!["/ban", "admin"] },
+                EndpointInfo { label: None, filters: vec!["/ban", "admin"] },
+            ]
+        );
+    }
+
+    #[derive(Default)]
+    struct Counter {
+        entered: usize,
+        exited: usize,
+        depth: usize,
+        max_depth: usize,
+        events: Vec<(bool, NodeKind, Option<&'static str>)>,
+    }
+
+    impl Visitor for Counter {
+        fn enter_node(&mut self, kind: NodeKind, label: Option<&'static str>) {
+            self.entered += 1;
+            self.depth += 1;
+            self.max_depth = self.max_depth.max(self.depth);
+            self.events.push((true, kind, label));
+        }
+
+        fn exit_node(&mut self, kind: NodeKind, label: Option<&'static str>) {
+            self.exited += 1;
+            self.depth -= 1;
+            self.events.push((false, kind, label));
+        }
+    }
+
+    #[test]
+    fn visit() {
+        let handler: Handler<DependencyMap, (), Tree> = crate::entry()
+            .branch(crate::filter(|| true).label("a").endpoint(|| async {}))
+            .branch(crate::endpoint(|| async {}).label("b"));
+
+        let mut counter = Counter::default();
+        handler.visit(&mut counter);
+
+        // branch, chain, filter, endpoint, endpoint
+        assert_eq!(counter.entered, 5);
+        assert_eq!(counter.exited, 5);
+        assert_eq!(counter.depth, 0);
+        assert_eq!(counter.max_depth, 3);
+        assert_eq!(
+            counter.events,
+            vec![
+                (true, NodeKind::Branch, None),
+                (true, NodeKind::Chain, None),
+                (true, NodeKind::Filter, Some("a")),
+                (false, NodeKind::Filter, Some("a")),
+                (true, NodeKind::Endpoint, None),
+                (false, NodeKind::Endpoint, None),
+                (false, NodeKind::Chain, None),
+                (true, NodeKind::Endpoint, Some("b")),
+                (false, NodeKind::Endpoint, Some("b")),
+                (false, NodeKind::Branch, None),
+            ]
+        );
+    }
+
+    #[test]
+    fn visit_dyn() {
+        struct Noop;
+        impl Visitor for Noop {}
+
+        let handler: Handler<DependencyMap, (), Tree> =
+            crate::filter(|| true).endpoint(|| async {});
+
+        let mut visitors: Vec<Box<dyn Visitor>> =
+            vec![Box::new(Noop), Box::new(Counter::default())];
+        for visitor in &mut visitors {
+            handler.visit(visitor.as_mut());
+        }
+
+        let mut counter = Counter::default();
+        handler.visit(&mut counter as &mut dyn Visitor);
+        assert_eq!((counter.entered, counter.exited), (3, 3));
+    }
+}
@@ -0,0 +1,253 @@
+use crate::{di::DependencyMap, Handler, HandlerDescription};
+use alloc::vec::Vec;
+use core::ops::ControlFlow;
+use futures::{future::BoxFuture, FutureExt, StreamExt};
+
+impl<'a, Input, Output, Descr> Handler<'a, Input, Output, Descr>
+where
+    Input: Send + 'a,
+    Output: 'a,
+    Descr: HandlerDescription,
+{
+    /// Dispatches every input of `inputs`, running at most `limit` dispatches
+    /// concurrently.
+    ///
+    /// The results are returned in the order of `inputs`, regardless of the
+    /// order in which the dispatches complete. The dispatches are polled by the
+    /// current task rather than spawned, so they are concurrent but not
+    /// parallel.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `limit` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use dptree::prelude::*;
+    ///
+    /// let handler: Handler<_, _> =
+    ///     dptree::filter(|x: i32| x > 0).endpoint(|x: i32| async move { x * 2 });
+    ///
+    /// let results = handler.dispatch_all(vec![dptree::deps![1], dptree::deps![-1]], 4).await;
+    ///
+    /// assert_eq!(results, vec![ControlFlow::Break(2), ControlFlow::Continue(dptree::deps![-1])]);
+    /// # }
+    /// ```
+    pub async fn dispatch_all(
+        &self,
+        inputs: Vec<Input>,
+        limit: usize,
+    ) -> Vec<ControlFlow<Output, Input>> {
+        assert!(limit > 0, "the concurrency limit must be positive");
+
+        futures::stream::iter(inputs)
+            .map(|input| self.dispatch(input))
+            .buffered(limit)
+            .collect()
+            .await
+    }
+
+    /// Collapses this handler into a plain asynchronous function.
+    ///
+    /// The returned closure dispatches this handler with its argument and
+    /// resolves to the output, or to a copy of `default` if the handler
+    /// continues (see [`Handler::or_default`]). This makes a handler usable
+    /// wherever a function `Fn(Input) -> impl Future<Output = Output>` is
+    /// expected. The closure is cheap to clone.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use dptree::prelude::*;
+    ///
+    /// let handler: Handler<_, _> =
+    ///     dptree::filter(|x: i32| x > 0).endpoint(|x: i32| async move { x * 2 });
+    ///
+    /// let service = handler.into_service_fn(0);
+    ///
+    /// assert_eq!(service(dptree::deps![21]).await, 42);
+    /// assert_eq!(service(dptree::deps![-1]).await, 0);
+    /// # }
+    /// ```
+    pub fn into_service_fn(
+        self,
+        default: Output,
+    ) -> impl Fn(Input) -> BoxFuture<'a, Output> + Clone + Send + Sync + 'a
+    where
+        Output: Clone + Send + Sync,
+    {
+        let handler = self.or_default(default);
+
+        move |input| {
+            let handler = handler.clone();
+
+            async move {
+                match handler.dispatch(input).await {
+                    ControlFlow::Break(output) => output,
+                    ControlFlow::Continue(_) => unreachable!("`or_default` never continues"),
+                }
+            }
+            .boxed()
+        }
+    }
+}
+
+impl<'a, Output, Descr> Handler<'a, DependencyMap, Output, Descr>
+where
+    Output: 'a,
+    Descr: HandlerDescription,
+{
+    /// Executes this handler with `overrides` shadowing the values of `input`.
+    ///
+    /// The handler is dispatched with a copy of `input` into which all values
+    /// of `overrides` are inserted, replacing the values of the same types.
+    /// `input` itself is not modified, so a base container can be cloned and
+    /// shared by concurrent dispatches with different request-scoped
+    /// overrides. Cloning a container is cheap, since values are stored behind
+    /// [`Arc`](alloc::sync::Arc)s.
+    ///
+    /// If the handler continues, `input` is returned without the overrides.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use dptree::prelude::*;
+    ///
+    /// let handler: Handler<_, _> =
+    ///     dptree::endpoint(|user: &'static str, greeting: String| async move {
+    ///         format!("{greeting}, {user}!")
+    ///     });
+    ///
+    /// let base = dptree::deps!["Hello".to_owned(), "anonymous"];
+    ///
+    /// assert_eq!(
+    ///     handler.dispatch_with(base.clone(), dptree::deps!["Alice"]).await,
+    ///     ControlFlow::Break("Hello, Alice!".to_owned())
+    /// );
+    /// assert_eq!(handler.dispatch(base).await, ControlFlow::Break("Hello, anonymous!".to_owned()));
+    /// # }
+    /// ```
+    pub async fn dispatch_with(
+        &self,
+        input: DependencyMap,
+        overrides: DependencyMap,
+    ) -> ControlFlow<Output, DependencyMap> {
+        let mut scoped = input.clone();
+        scoped.insert_container(overrides);
+
+        match self.dispatch(scoped).await {
+            ControlFlow::Continue(_) => ControlFlow::Continue(input),
+            ControlFlow::Break(output) => ControlFlow::Break(output),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{deps, di::DependencySupplier};
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_dispatch_with_concurrent() {
+        let handler: Handler<'static, DependencyMap, String> =
+            crate::endpoint(|prefix: &'static str, x: i32| async move {
+                tokio::task::yield_now().await;
+                format!("{prefix} {x}")
+            });
+        let base = Arc::new(deps!["base", 0]);
+
+        let tasks: Vec<_> = (1..=8)
+            .map(|x| {
+                let handler = handler.clone();
+                let base = Arc::clone(&base);
+                tokio::spawn(async move { handler.dispatch_with((*base).clone(), deps![x]).await })
+            })
+            .collect();
+
+        for (x, task) in (1..=8).zip(tasks) {
+            assert_eq!(task.await.unwrap(), ControlFlow::Break(format!("base {x}")));
+        }
+
+        let x: Arc<i32> = base.get();
+        assert_eq!(*x, 0);
+    }
+
+    #[tokio::test]
+    async fn test_dispatch_with_continue() {
+        let handler: Handler<'static, DependencyMap, ()> = crate::filter(|x: i32| x > 0);
+
+        match handler.dispatch_with(deps![0], deps![1]).await {
+            ControlFlow::Continue(input) => {
+                let x: Arc<i32> = input.get();
+                assert_eq!(*x, 0);
+            }
+            ControlFlow::Break(()) => panic!("the handler must continue"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_dispatch_all() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+
+        let handler: Handler<'static, DependencyMap, i32> = crate::endpoint({
+            let in_flight = Arc::clone(&in_flight);
+            let peak = Arc::clone(&peak);
+            move |x: i32| {
+                let in_flight = Arc::clone(&in_flight);
+                let peak = Arc::clone(&peak);
+                async move {
+                    let current = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                    peak.fetch_max(current, Ordering::SeqCst);
+                    // Later inputs complete first.
+                    tokio::time::sleep(std::time::Duration::from_millis(10 * (5 - x) as u64)).await;
+                    in_flight.fetch_sub(1, Ordering::SeqCst);
+                    x * 10
+                }
+            }
+        });
+
+        let inputs = (0..5).map(|x| deps![x]).collect();
+        let results = handler.dispatch_all(inputs, 3).await;
+
+        assert_eq!(results, (0..5).map(|x| ControlFlow::Break(x * 10)).collect::<Vec<_>>());
+        assert_eq!(peak.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_into_service_fn() {
+        async fn call<F, Fut>(service: F, inputs: Vec<DependencyMap>) -> Vec<&'static str>
+        where
+            F: Fn(DependencyMap) -> Fut,
+            Fut: core::future::Future<Output = &'static str>,
+        {
+            let mut outputs = Vec::new();
+            for input in inputs {
+                outputs.push(service(input).await);
+            }
+            outputs
+        }
+
+        let handler: Handler<'static, DependencyMap, &'static str> = crate::entry()
+            .branch(crate::filter(|x: i32| x > 0).endpoint(|| async { "positive" }))
+            .branch(crate::filter(|x: i32| x < 0).endpoint(|| async { "negative" }));
+        let service = handler.into_service_fn("zero");
+
+        assert_eq!(
+            call(service.clone(), vec![deps![1], deps![-1], deps![0]]).await,
+            ["positive", "negative", "zero"]
+        );
+        // The closure can be moved to another task.
+        assert_eq!(tokio::spawn(service(deps![2])).await.unwrap(), "positive");
+    }
+}
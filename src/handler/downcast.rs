@@ -0,0 +1,181 @@
+use crate::{from_fn_with_description, Handler, HandlerDescription};
+use std::{
+    any::Any,
+    ops::ControlFlow,
+    sync::{Arc, Mutex, PoisonError},
+};
+
+/// An event of an arbitrary type that handlers can mutate.
+///
+/// See [`filter_downcast_mut`].
+pub type DynEvent = Arc<Mutex<dyn Any + Send>>;
+
+/// Constructs a handler that filters an event of type `T` with the predicate
+/// `pred`, which can mutate the event.
+///
+/// If the event is not of type `T`, the handler returns
+/// [`ControlFlow::Continue`]. Otherwise, `pred` receives a mutable reference
+/// to it; if `pred` returns `true`, a continuation of the handler will be
+/// called, otherwise the handler returns [`ControlFlow::Continue`].
+///
+/// ## Locking
+///
+/// The event is locked only while `pred` is running and is released before the
+/// continuation is called, so the next handlers can lock it again. Do not
+/// lock the event inside `pred`: it would deadlock. If a previous handler
+/// panicked while holding the lock, the event is used as that handler left it.
+///
+/// # Examples
+///
+/// ```
+/// # #[tokio::main]
+/// # async fn main() {
+/// use dptree::{prelude::*, DynEvent};
+/// use std::sync::{Arc, Mutex};
+///
+/// struct Message {
+///     text: String,
+/// }
+///
+/// let handler: Handler<DynEvent, _> = dptree::filter_downcast_mut(|msg: &mut Message| {
+///     msg.text = msg.text.trim().to_owned();
+///     !msg.text.is_empty()
+/// })
+/// .endpoint(|| async { "non-empty" });
+///
+/// let event: DynEvent = Arc::new(Mutex::new(Message { text: "  hi ".to_owned() }));
+/// assert!(matches!(handler.dispatch(Arc::clone(&event)).await, ControlFlow::Break("non-empty")));
+/// assert_eq!(event.lock().unwrap().downcast_ref::<Message>().unwrap().text, "hi");
+///
+/// // Events of other types are not handled.
+/// let event: DynEvent = Arc::new(Mutex::new(42));
+/// assert!(matches!(handler.dispatch(event).await, ControlFlow::Continue(_)));
+/// # }
+/// ```
+#[must_use]
+#[track_caller]
+pub fn filter_downcast_mut<'a, T, Pred, Output, Descr>(
+    pred: Pred,
+) -> Handler<'a, DynEvent, Output, Descr>
+where
+    T: Any,
+    Pred: Fn(&mut T) -> bool + Send + Sync + 'a,
+    Output: 'a,
+    Descr: HandlerDescription,
+{
+    let pred = Arc::new(pred);
+
+    from_fn_with_description(Descr::filter(), move |event: DynEvent, cont| {
+        let cond = {
+            let mut guard = event.lock().unwrap_or_else(PoisonError::into_inner);
+            guard.downcast_mut::<T>().is_some_and(|event| pred(event))
+        };
+
+        async move {
+            if cond {
+                cont(event).await
+            } else {
+                ControlFlow::Continue(event)
+            }
+        }
+    })
+}
+
+/// Constructs a handler that mutates an event of type `T` with `f`.
+///
+/// If the event is not of type `T`, the handler returns
+/// [`ControlFlow::Continue`]. Otherwise, `f` receives a mutable reference to
+/// it, and then a continuation of the handler is called. Locking works as in
+/// [`filter_downcast_mut`].
+#[must_use]
+#[track_caller]
+pub fn inspect_downcast_mut<'a, T, F, Output, Descr>(f: F) -> Handler<'a, DynEvent, Output, Descr>
+where
+    T: Any,
+    F: Fn(&mut T) + Send + Sync + 'a,
+    Output: 'a,
+    Descr: HandlerDescription,
+{
+    filter_downcast_mut(move |event: &mut T| {
+        f(event);
+        true
+    })
+}
+
+impl<'a, Output, Descr> Handler<'a, DynEvent, Output, Descr>
+where
+    Output: 'a,
+    Descr: HandlerDescription,
+{
+    /// Chain this handler with [`filter_downcast_mut`].
+    #[must_use]
+    #[track_caller]
+    pub fn filter_downcast_mut<T, Pred>(self, pred: Pred) -> Self
+    where
+        T: Any,
+        Pred: Fn(&mut T) -> bool + Send + Sync + 'a,
+    {
+        self.chain(crate::filter_downcast_mut(pred))
+    }
+
+    /// Chain this handler with [`inspect_downcast_mut`].
+    #[must_use]
+    #[track_caller]
+    pub fn inspect_downcast_mut<T, F>(self, f: F) -> Self
+    where
+        T: Any,
+        F: Fn(&mut T) + Send + Sync + 'a,
+    {
+        self.chain(crate::inspect_downcast_mut(f))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct Counter {
+        value: i32,
+        visited: Vec<&'static str>,
+    }
+
+    fn counter(event: &DynEvent) -> std::sync::MutexGuard<'_, dyn Any + Send> {
+        event.lock().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_downcast_mut_sequential() {
+        let handler: Handler<DynEvent, &'static str> =
+            crate::inspect_downcast_mut(|c: &mut Counter| {
+                c.value += 1;
+                c.visited.push("first");
+            })
+            .filter_downcast_mut(|c: &mut Counter| {
+                c.value *= 10;
+                c.visited.push("second");
+                c.value > 10
+            })
+            .endpoint(|| async { "big" });
+
+        let event: DynEvent = Arc::new(Mutex::new(Counter { value: 1, visited: Vec::new() }));
+        assert!(matches!(handler.dispatch(Arc::clone(&event)).await, ControlFlow::Break("big")));
+        assert_eq!(
+            counter(&event).downcast_ref::<Counter>(),
+            Some(&Counter { value: 20, visited: vec!["first", "second"] })
+        );
+
+        let event: DynEvent = Arc::new(Mutex::new(Counter { value: 0, visited: Vec::new() }));
+        assert!(matches!(handler.dispatch(Arc::clone(&event)).await, ControlFlow::Continue(_)));
+        assert_eq!(counter(&event).downcast_ref::<Counter>().unwrap().value, 10);
+    }
+
+    #[tokio::test]
+    async fn test_downcast_mut_other_type() {
+        let handler: Handler<DynEvent, ()> =
+            crate::inspect_downcast_mut(|_: &mut Counter| unreachable!()).endpoint(|| async {});
+
+        let event: DynEvent = Arc::new(Mutex::new("not a counter"));
+        assert!(matches!(handler.dispatch(event).await, ControlFlow::Continue(_)));
+    }
+}
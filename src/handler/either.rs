@@ -0,0 +1,174 @@
+use crate::{di::Insert, from_fn_with_description, Handler, HandlerDescription};
+use alloc::sync::Arc;
+use core::ops::ControlFlow;
+
+/// A value of one of two types.
+///
+/// See [`filter_map_either`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Either<L, R> {
+    /// A value of the first type.
+    Left(L),
+
+    /// A value of the second type.
+    Right(R),
+}
+
+impl<T, E> From<Result<T, E>> for Either<T, E> {
+    fn from(result: Result<T, E>) -> Self {
+        match result {
+            Ok(value) => Self::Left(value),
+            Err(error) => Self::Right(error),
+        }
+    }
+}
+
+/// Constructs a handler that routes input to one of two handlers.
+///
+/// If `f` returns `Either::Left(l)`, then `l` is added to the container and
+/// `left` is executed; if it returns `Either::Right(r)`, then `r` is added to
+/// the container and `right` is executed. In both cases, the execution
+/// continues further in a handler chain if the chosen handler continues. If it
+/// does, the handler returns [`ControlFlow::Continue`] with the original
+/// container.
+///
+/// # Examples
+///
+/// ```
+/// # #[tokio::main]
+/// # async fn main() {
+/// use dptree::{di::DependencySupplier, prelude::*, Either};
+///
+/// let handler: Handler<_, _> = dptree::filter_map_either(
+///     |deps: &DependencyMap| {
+///         let s: std::sync::Arc<&'static str> = deps.get();
+///         match s.parse::<i32>() {
+///             Ok(n) => Either::Left(n),
+///             Err(_) => Either::Right(s.to_uppercase()),
+///         }
+///     },
+///     dptree::endpoint(|n: i32| async move { format!("number {n}") }),
+///     dptree::endpoint(|s: String| async move { format!("text {s}") }),
+/// );
+///
+/// assert_eq!(
+///     handler.dispatch(dptree::deps!["1"]).await,
+///     ControlFlow::Break("number 1".to_owned())
+/// );
+/// assert_eq!(handler.dispatch(dptree::deps!["a"]).await, ControlFlow::Break("text A".to_owned()));
+/// # }
+/// ```
+#[must_use]
+#[track_caller]
+pub fn filter_map_either<'a, F, Input, Output, L, R, Descr>(
+    f: F,
+    left: Handler<'a, Input, Output, Descr>,
+    right: Handler<'a, Input, Output, Descr>,
+) -> Handler<'a, Input, Output, Descr>
+where
+    F: Fn(&Input) -> Either<L, R> + Send + Sync + 'a,
+    Input: Insert<L> + Insert<R> + Clone + Send + 'a,
+    Output: 'a,
+    L: Send + 'a,
+    R: Send + 'a,
+    Descr: HandlerDescription,
+{
+    // `L` is added only before `left`, and `R` only before `right`.
+    let left_description = Descr::filter_map()
+        .with_dependencies(&[], &[core::any::type_name::<L>()])
+        .merge_chain(left.description());
+    let right_description = Descr::filter_map()
+        .with_dependencies(&[], &[core::any::type_name::<R>()])
+        .merge_chain(right.description());
+    let description =
+        Descr::entry().merge_branch(&left_description).merge_branch(&right_description);
+    let f = Arc::new(f);
+
+    from_fn_with_description(description, move |container: Input, cont| {
+        let mut intermediate = container.clone();
+        let next = match f(&container) {
+            Either::Left(value) => {
+                Insert::<L>::insert(&mut intermediate, value);
+                left.clone()
+            }
+            Either::Right(value) => {
+                Insert::<R>::insert(&mut intermediate, value);
+                right.clone()
+            }
+        };
+
+        async move {
+            match next.execute(intermediate, cont).await {
+                ControlFlow::Continue(_) => ControlFlow::Continue(container),
+                ControlFlow::Break(result) => ControlFlow::Break(result),
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        deps,
+        di::{DependencyMap, DependencySupplier},
+        help_inference,
+    };
+
+    fn parity(deps: &DependencyMap) -> Either<u32, i64> {
+        let x: Arc<i32> = deps.get();
+        if *x % 2 == 0 {
+            Either::Left(*x as u32)
+        } else {
+            Either::Right(*x as i64)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_filter_map_either() {
+        let handler = filter_map_either(
+            parity,
+            help_inference(crate::endpoint(|x: u32| async move { format!("even {x}") })),
+            crate::endpoint(|x: i64| async move { format!("odd {x}") }),
+        );
+
+        assert_eq!(handler.dispatch(deps![2]).await, ControlFlow::Break("even 2".to_owned()));
+        assert_eq!(handler.dispatch(deps![3]).await, ControlFlow::Break("odd 3".to_owned()));
+    }
+
+    #[tokio::test]
+    async fn test_filter_map_either_continue() {
+        let handler = filter_map_either(
+            parity,
+            help_inference(crate::filter(|x: u32| x > 10)),
+            crate::entry(),
+        )
+        .endpoint(|x: i32| async move { x });
+
+        assert_eq!(handler.dispatch(deps![12]).await, ControlFlow::Break(12));
+        assert_eq!(handler.dispatch(deps![3]).await, ControlFlow::Break(3));
+        assert!(
+            matches!(handler.dispatch(deps![2]).await, ControlFlow::Continue(deps) if !deps.contains::<u32>())
+        );
+    }
+
+    #[test]
+    fn test_filter_map_either_description() {
+        use crate::description::Tree;
+
+        let handler: Handler<DependencyMap, (), Tree> = filter_map_either(
+            parity,
+            crate::endpoint(|_: u32| async {}),
+            crate::endpoint(|_: i64, _: u32| async {}),
+        );
+
+        // `u32` is added only before the left handler.
+        assert_eq!(handler.validate_deps(&deps![0i32]).unwrap_err()[0].type_name, "u32");
+    }
+
+    #[test]
+    fn either_from_result() {
+        assert_eq!(Either::from(Ok::<_, ()>(1)), Either::Left(1));
+        assert_eq!(Either::from(Err::<(), _>(2)), Either::Right(2));
+    }
+}
@@ -1,6 +1,7 @@
 use crate::{description, di::Injectable, from_fn_with_description, Handler, HandlerDescription};
-use futures::FutureExt;
-use std::{ops::ControlFlow, sync::Arc};
+use alloc::sync::Arc;
+use core::ops::ControlFlow;
+use futures::{future::BoxFuture, FutureExt};
 
 /// Constructs a handler that has no further handlers in a chain.
 ///
@@ -16,9 +17,11 @@ where
     Output: 'a,
     Descr: HandlerDescription,
 {
+    let required = <F as Injectable<Input, Output, FnArgs>>::dependencies();
+    let description = Descr::endpoint().with_dependencies(&required, &[]);
     let f = Arc::new(f);
 
-    from_fn_with_description(Descr::endpoint(), move |x, _cont| {
+    from_fn_with_description(description, move |x, _cont| {
         let f = Arc::clone(&f);
         async move {
             let f = f.inject(&x);
@@ -27,6 +30,51 @@ where
     })
 }
 
+/// Constructs an endpoint whose future borrows the input.
+///
+/// The futures of dependency-injected functions must be `'static`, because
+/// the lifetime of the borrowed input cannot be expressed in the type of a
+/// generic future. `f` works around this by returning a [`BoxFuture`] tied to
+/// the lifetime of the input, so the future may hold references into the
+/// input until the endpoint completes.
+///
+/// # Examples
+///
+/// ```
+/// # #[tokio::main]
+/// # async fn main() {
+/// use dptree::prelude::*;
+///
+/// struct Request {
+///     body: String,
+/// }
+///
+/// let handler: Handler<Request, usize> = dptree::endpoint_ref(|request: &Request| {
+///     let body = request.body.as_str();
+///     Box::pin(async move { body.split_whitespace().count() })
+/// });
+///
+/// let request = Request { body: "hello, world".to_owned() };
+/// assert!(matches!(handler.dispatch(request).await, ControlFlow::Break(2)));
+/// # }
+/// ```
+#[must_use]
+#[track_caller]
+pub fn endpoint_ref<'a, F, Input, Output, Descr>(f: F) -> Endpoint<'a, Input, Output, Descr>
+where
+    F: for<'i> Fn(&'i Input) -> BoxFuture<'i, Output> + Send + Sync + 'a,
+    Input: Send + Sync + 'a,
+    Output: 'a,
+    Descr: HandlerDescription,
+{
+    let f = Arc::new(f);
+
+    from_fn_with_description(Descr::endpoint(), move |x, _cont| {
+        let f = Arc::clone(&f);
+        async move { f(&x).map(ControlFlow::Break).await }
+    })
+}
+
 /// A handler with no further handlers in a chain.
 pub type Endpoint<'a, Input, Output, Descr = description::Unspecified> =
     Handler<'a, Input, Output, Descr>;
@@ -54,4 +102,21 @@ mod tests {
         };
         assert_eq!(result, output);
     }
+
+    #[tokio::test]
+    async fn test_endpoint_ref() {
+        use crate::di::{DependencyMap, DependencySupplier};
+
+        let handler: Endpoint<DependencyMap, usize> = endpoint_ref(|deps: &DependencyMap| {
+            Box::pin(async move {
+                let text: Arc<String> = deps.get();
+                let words: Vec<&str> = text.split_whitespace().collect();
+                // The borrow of the injected value is held across an await point.
+                tokio::task::yield_now().await;
+                words.len()
+            })
+        });
+
+        assert_eq!(handler.dispatch(deps!["a b c".to_owned()]).await, ControlFlow::Break(3));
+    }
 }
@@ -4,7 +4,9 @@ use crate::{
     handler::core::Handler,
     HandlerDescription,
 };
-use std::{ops::ControlFlow, sync::Arc};
+use alloc::{sync::Arc, vec::Vec};
+use core::ops::ControlFlow;
+use futures::future::BoxFuture;
 
 /// Constructs a handler that filters input with the predicate `pred`.
 ///
@@ -22,7 +24,8 @@ where
     Output: 'a,
     Descr: HandlerDescription,
 {
-    filter_with_description(Descr::filter(), pred)
+    let required = <Asyncify<Pred> as Injectable<Input, bool, FnArgs>>::dependencies();
+    filter_with_description(Descr::filter().with_dependencies(&required, &[]), pred)
 }
 
 /// The asynchronous version of [`filter`].
@@ -37,7 +40,8 @@ where
     Output: 'a,
     Descr: HandlerDescription,
 {
-    filter_async_with_description(Descr::filter_async(), pred)
+    let required = <Pred as Injectable<Input, bool, FnArgs>>::dependencies();
+    filter_async_with_description(Descr::filter_async().with_dependencies(&required, &[]), pred)
 }
 
 /// [`filter`] with a custom description.
@@ -84,10 +88,282 @@ where
     })
 }
 
+/// Constructs a handler that validates input with the predicate `pred`.
+///
+/// If `pred` returns `true`, a continuation of the handler will be called.
+/// Otherwise, unlike [`filter`], the handler does not let the next branches
+/// try the input but breaks with `err(&input)`, e.g., a validation message for
+/// the user.
+///
+/// # Examples
+///
+/// ```
+/// # #[tokio::main]
+/// # async fn main() {
+/// use dptree::{di::DependencySupplier, prelude::*};
+///
+/// let handler: Handler<_, String> = dptree::validate(
+///     |age: u32| age >= 18,
+///     |deps: &DependencyMap| {
+///         let age: std::sync::Arc<u32> = deps.get();
+///         format!("You must be 18 or older, but you are {age}.")
+///     },
+/// )
+/// .endpoint(|| async { "Welcome!".to_owned() });
+///
+/// assert_eq!(
+///     handler.dispatch(dptree::deps![20u32]).await,
+///     ControlFlow::Break("Welcome!".to_owned())
+/// );
+/// assert_eq!(
+///     handler.dispatch(dptree::deps![16u32]).await,
+///     ControlFlow::Break("You must be 18 or older, but you are 16.".to_owned())
+/// );
+/// # }
+/// ```
+#[must_use]
+#[track_caller]
+pub fn validate<'a, Pred, Err, Input, Output, FnArgs, Descr>(
+    pred: Pred,
+    err: Err,
+) -> Handler<'a, Input, Output, Descr>
+where
+    Asyncify<Pred>: Injectable<Input, bool, FnArgs> + Send + Sync + 'a,
+    Err: Fn(&Input) -> Output + Send + Sync + 'a,
+    Input: Send + 'a,
+    Output: 'a,
+    Descr: HandlerDescription,
+{
+    let required = <Asyncify<Pred> as Injectable<Input, bool, FnArgs>>::dependencies();
+    validate_with_description(
+        Descr::user_defined().with_dependencies(&required, &[]),
+        Asyncify(pred),
+        err,
+    )
+}
+
+/// The asynchronous version of [`validate`].
+#[must_use]
+#[track_caller]
+pub fn validate_async<'a, Pred, Err, Input, Output, FnArgs, Descr>(
+    pred: Pred,
+    err: Err,
+) -> Handler<'a, Input, Output, Descr>
+where
+    Pred: Injectable<Input, bool, FnArgs> + Send + Sync + 'a,
+    Err: Fn(&Input) -> Output + Send + Sync + 'a,
+    Input: Send + 'a,
+    Output: 'a,
+    Descr: HandlerDescription,
+{
+    let required = <Pred as Injectable<Input, bool, FnArgs>>::dependencies();
+    validate_with_description(Descr::user_defined().with_dependencies(&required, &[]), pred, err)
+}
+
+fn validate_with_description<'a, Pred, Err, Input, Output, FnArgs, Descr>(
+    description: Descr,
+    pred: Pred,
+    err: Err,
+) -> Handler<'a, Input, Output, Descr>
+where
+    Pred: Injectable<Input, bool, FnArgs> + Send + Sync + 'a,
+    Err: Fn(&Input) -> Output + Send + Sync + 'a,
+    Input: Send + 'a,
+    Output: 'a,
+{
+    let pred = Arc::new(pred);
+    let err = Arc::new(err);
+
+    from_fn_with_description(description, move |event, cont| {
+        let pred = Arc::clone(&pred);
+        let err = Arc::clone(&err);
+
+        async move {
+            let pred = pred.inject(&event);
+            let cond = pred().await;
+            drop(pred);
+
+            if cond {
+                cont(event).await
+            } else {
+                ControlFlow::Break(err(&event))
+            }
+        }
+    })
+}
+
+/// Constructs a handler that rejects input with the async predicate `pred`.
+///
+/// This is [`validate_async`] with an asynchronous rejection: if `pred`
+/// returns `false`, the handler breaks with the output of the future returned
+/// by `reject(&input)`, e.g., a "403" reply for a failed authorization check.
+/// The future may borrow the input, so that the rejection can be built from it
+/// without cloning.
+///
+/// # Examples
+///
+/// ```
+/// # #[tokio::main]
+/// # async fn main() {
+/// use dptree::{di::DependencySupplier, prelude::*};
+/// use futures::FutureExt;
+///
+/// let handler: Handler<_, String> = dptree::guard_async(
+///     |user: &'static str| async move { user == "admin" },
+///     |deps: &DependencyMap| {
+///         async move {
+///             let user: std::sync::Arc<&'static str> = deps.get();
+///             format!("403: {user} is not allowed")
+///         }
+///         .boxed()
+///     },
+/// )
+/// .endpoint(|| async { "200".to_owned() });
+///
+/// assert_eq!(
+///     handler.dispatch(dptree::deps!["admin"]).await,
+///     ControlFlow::Break("200".to_owned())
+/// );
+/// assert_eq!(
+///     handler.dispatch(dptree::deps!["guest"]).await,
+///     ControlFlow::Break("403: guest is not allowed".to_owned())
+/// );
+/// # }
+/// ```
+#[must_use]
+#[track_caller]
+pub fn guard_async<'a, Pred, Reject, Input, Output, FnArgs, Descr>(
+    pred: Pred,
+    reject: Reject,
+) -> Handler<'a, Input, Output, Descr>
+where
+    Pred: Injectable<Input, bool, FnArgs> + Send + Sync + 'a,
+    Reject: for<'i> Fn(&'i Input) -> BoxFuture<'i, Output> + Send + Sync + 'a,
+    Input: Send + Sync + 'a,
+    Output: 'a,
+    Descr: HandlerDescription,
+{
+    let required = <Pred as Injectable<Input, bool, FnArgs>>::dependencies();
+    let pred = Arc::new(pred);
+    let reject = Arc::new(reject);
+
+    from_fn_with_description(
+        Descr::user_defined().with_dependencies(&required, &[]),
+        move |event, cont| {
+            let pred = Arc::clone(&pred);
+            let reject = Arc::clone(&reject);
+
+            async move {
+                let pred = pred.inject(&event);
+                let cond = pred().await;
+                drop(pred);
+
+                if cond {
+                    cont(event).await
+                } else {
+                    ControlFlow::Break(reject(&event).await)
+                }
+            }
+        },
+    )
+}
+
+/// Constructs a handler that passes the input further if any of `preds`
+/// returns `true`.
+///
+/// The predicates are called in order until one of them returns `true`. If
+/// `preds` is empty, the handler always returns [`ControlFlow::Continue`].
+///
+/// Since the predicates are stored in a [`Vec`], they can be registered
+/// dynamically; use `Box<dyn Fn(&Input) -> bool + Send + Sync>` to combine
+/// closures of different types.
+///
+/// # Examples
+///
+/// ```
+/// # #[tokio::main]
+/// # async fn main() {
+/// use dptree::{di::DependencySupplier, prelude::*};
+/// use std::sync::Arc;
+///
+/// type Pred = Box<dyn Fn(&DependencyMap) -> bool + Send + Sync>;
+///
+/// let prefix = |prefix: char| -> Pred {
+///     Box::new(move |deps| {
+///         let text: Arc<&str> = deps.get();
+///         text.starts_with(prefix)
+///     })
+/// };
+/// let preds = vec![prefix('/'), prefix('!')];
+/// let handler: Handler<_, _> = dptree::filter_any(preds).endpoint(|| async { "command" });
+///
+/// assert_eq!(handler.dispatch(dptree::deps!["!ban"]).await, ControlFlow::Break("command"));
+/// assert!(matches!(handler.dispatch(dptree::deps!["hello"]).await, ControlFlow::Continue(_)));
+/// # }
+/// ```
+#[must_use]
+#[track_caller]
+pub fn filter_any<'a, Pred, Input, Output, Descr>(
+    preds: Vec<Pred>,
+) -> Handler<'a, Input, Output, Descr>
+where
+    Pred: Fn(&Input) -> bool + Send + Sync + 'a,
+    Input: Send + 'a,
+    Output: 'a,
+    Descr: HandlerDescription,
+{
+    filter_by_preds(preds, |preds, event| preds.iter().any(|pred| pred(event)))
+}
+
+/// Constructs a handler that passes the input further if all of `preds`
+/// return `true`.
+///
+/// The predicates are called in order until one of them returns `false`. If
+/// `preds` is empty, the handler always passes the input further.
+///
+/// See [`filter_any`] for registering predicates of different types.
+#[must_use]
+#[track_caller]
+pub fn filter_all<'a, Pred, Input, Output, Descr>(
+    preds: Vec<Pred>,
+) -> Handler<'a, Input, Output, Descr>
+where
+    Pred: Fn(&Input) -> bool + Send + Sync + 'a,
+    Input: Send + 'a,
+    Output: 'a,
+    Descr: HandlerDescription,
+{
+    filter_by_preds(preds, |preds, event| preds.iter().all(|pred| pred(event)))
+}
+
+#[track_caller]
+fn filter_by_preds<'a, Pred, Input, Output, Descr>(
+    preds: Vec<Pred>,
+    combine: fn(&[Pred], &Input) -> bool,
+) -> Handler<'a, Input, Output, Descr>
+where
+    Pred: Fn(&Input) -> bool + Send + Sync + 'a,
+    Input: Send + 'a,
+    Output: 'a,
+    Descr: HandlerDescription,
+{
+    from_fn_with_description(Descr::filter(), move |event, cont| {
+        let cond = combine(&preds, &event);
+
+        async move {
+            if cond {
+                cont(event).await
+            } else {
+                ControlFlow::Continue(event)
+            }
+        }
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{deps, help_inference};
+    use crate::{deps, di::DependencyMap, help_inference};
 
     #[tokio::test]
     async fn test_filter() {
@@ -133,4 +409,136 @@ mod tests {
 
         assert!(result == ControlFlow::Break(output));
     }
+
+    #[tokio::test]
+    async fn test_validate() {
+        let handler = help_inference(crate::entry())
+            .branch(
+                crate::filter(|s: &'static str| s.starts_with("/age "))
+                    .validate(
+                        |s: &'static str| s[5..].parse::<u32>().is_ok(),
+                        |_: &DependencyMap| "Age must be a number.".to_owned(),
+                    )
+                    .endpoint(|s: &'static str| async move { format!("Age: {}", &s[5..]) }),
+            )
+            .branch(crate::endpoint(|| async { "Unknown command.".to_owned() }));
+
+        assert_eq!(
+            handler.dispatch(deps!["/age 30"]).await,
+            ControlFlow::Break("Age: 30".to_owned())
+        );
+        // A failed validation does not fall through to the next branch.
+        assert_eq!(
+            handler.dispatch(deps!["/age abc"]).await,
+            ControlFlow::Break("Age must be a number.".to_owned())
+        );
+        assert_eq!(
+            handler.dispatch(deps!["/help"]).await,
+            ControlFlow::Break("Unknown command.".to_owned())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_validate_async() {
+        let handler = help_inference(validate_async(
+            |x: i32| async move { x > 0 },
+            |_: &DependencyMap| "not positive",
+        ))
+        .endpoint(|| async { "ok" });
+
+        assert_eq!(handler.dispatch(deps![1]).await, ControlFlow::Break("ok"));
+        assert_eq!(handler.dispatch(deps![0]).await, ControlFlow::Break("not positive"));
+    }
+
+    #[test]
+    fn test_validate_description() {
+        use crate::description::Tree;
+
+        // The error output is a break, so the handler is not empty.
+        let handler: Handler<DependencyMap, (), Tree> =
+            crate::entry().chain(validate(|| true, |_: &DependencyMap| ()));
+        let _ = handler.require_non_empty();
+
+        let handler: Handler<DependencyMap, (), Tree> =
+            crate::entry().chain(validate_async(|| async { true }, |_: &DependencyMap| ()));
+        let _ = handler.require_non_empty();
+    }
+
+    #[test]
+    fn test_guard_async_description() {
+        use crate::description::Tree;
+
+        // The rejection is a break, so the handler is not empty.
+        let handler: Handler<DependencyMap, (), Tree> = crate::entry()
+            .chain(guard_async(|| async { true }, |_: &DependencyMap| Box::pin(async {})));
+        let _ = handler.require_non_empty();
+    }
+
+    #[tokio::test]
+    async fn test_guard_async() {
+        use futures::FutureExt;
+
+        let handler = help_inference(crate::entry())
+            .branch(
+                crate::filter(|s: &'static str| s.starts_with("/admin"))
+                    .guard_async(
+                        |token: u64| async move {
+                            tokio::task::yield_now().await;
+                            token == 42
+                        },
+                        |_: &DependencyMap| async { "403".to_owned() }.boxed(),
+                    )
+                    .endpoint(|| async { "admin panel".to_owned() }),
+            )
+            .branch(crate::endpoint(|| async { "Unknown command.".to_owned() }));
+
+        assert_eq!(
+            handler.dispatch(deps!["/admin", 42u64]).await,
+            ControlFlow::Break("admin panel".to_owned())
+        );
+        // Rejected immediately, the next branch is not tried.
+        assert_eq!(
+            handler.dispatch(deps!["/admin", 0u64]).await,
+            ControlFlow::Break("403".to_owned())
+        );
+        assert_eq!(
+            handler.dispatch(deps!["/start", 0u64]).await,
+            ControlFlow::Break("Unknown command.".to_owned())
+        );
+    }
+
+    type Pred = Box<dyn Fn(&i32) -> bool + Send + Sync>;
+
+    #[tokio::test]
+    async fn test_filter_any() {
+        let preds: Vec<Pred> = vec![Box::new(|&x| x < 0), Box::new(|&x| x > 10)];
+        let handler: Handler<i32, ()> =
+            filter_any(preds).chain(crate::from_fn(|_, _| async { ControlFlow::Break(()) }));
+
+        assert_eq!(handler.dispatch(-1).await, ControlFlow::Break(()));
+        assert_eq!(handler.dispatch(11).await, ControlFlow::Break(()));
+        assert_eq!(handler.dispatch(5).await, ControlFlow::Continue(5));
+    }
+
+    #[tokio::test]
+    async fn test_filter_all() {
+        let preds: Vec<Pred> = vec![Box::new(|&x| x > 0), Box::new(|&x| x % 2 == 1)];
+        let handler: Handler<i32, ()> =
+            filter_all(preds).chain(crate::from_fn(|_, _| async { ControlFlow::Break(()) }));
+
+        assert_eq!(handler.dispatch(3).await, ControlFlow::Break(()));
+        assert_eq!(handler.dispatch(2).await, ControlFlow::Continue(2));
+        assert_eq!(handler.dispatch(-3).await, ControlFlow::Continue(-3));
+    }
+
+    #[tokio::test]
+    async fn test_filter_any_all_empty() {
+        let any: Handler<i32, ()> = filter_any(Vec::<fn(&i32) -> bool>::new())
+            .chain(crate::from_fn(|_, _| async { ControlFlow::Break(()) }));
+        let all: Handler<i32, ()> = filter_all(Vec::<fn(&i32) -> bool>::new())
+            .chain(crate::from_fn(|_, _| async { ControlFlow::Break(()) }));
+
+        assert_eq!(any.dispatch(1).await, ControlFlow::Continue(1));
+        assert_eq!(all.dispatch(1).await, ControlFlow::Break(()));
+    }
 }
@@ -1,8 +1,10 @@
 use crate::{
-    di::{Asyncify, Injectable, Insert},
+    di::{Asyncify, Injectable, Insert, InsertArc, InsertMany},
     from_fn_with_description, Handler, HandlerDescription,
 };
-use std::{ops::ControlFlow, sync::Arc};
+use alloc::{sync::Arc, vec::Vec};
+use core::{convert::TryFrom, ops::ControlFlow};
+use futures::{future::BoxFuture, Stream, StreamExt};
 
 /// Constructs a handler that optionally passes a value of a new type further.
 ///
@@ -23,7 +25,10 @@ where
     Descr: HandlerDescription,
     NewType: Send,
 {
-    filter_map_with_description(Descr::filter_map(), proj)
+    let required =
+        <Asyncify<Projection> as Injectable<Input, Option<NewType>, Args>>::dependencies();
+    let provided = [core::any::type_name::<NewType>()];
+    filter_map_with_description(Descr::filter_map().with_dependencies(&required, &provided), proj)
 }
 
 /// The asynchronous version of [`filter_map`].
@@ -40,7 +45,12 @@ where
     Descr: HandlerDescription,
     NewType: Send,
 {
-    filter_map_async_with_description(Descr::filter_map_async(), proj)
+    let required = <Projection as Injectable<Input, Option<NewType>, Args>>::dependencies();
+    let provided = [core::any::type_name::<NewType>()];
+    filter_map_async_with_description(
+        Descr::filter_map_async().with_dependencies(&required, &provided),
+        proj,
+    )
 }
 
 /// [`filter_map`] with a custom description.
@@ -80,7 +90,7 @@ where
         async move {
             let proj = proj.inject(&container);
             let res = proj().await;
-            std::mem::drop(proj);
+            core::mem::drop(proj);
 
             match res {
                 Some(new_type) => {
@@ -97,10 +107,703 @@ where
     })
 }
 
+/// Constructs a handler that optionally passes several values of new types
+/// further.
+///
+/// `proj` returns a tuple of values, e.g., `Option<(A, B)>`. If it returns
+/// `Some`, then all of the values are added to the container, from left to
+/// right, and the container is passed further in a handler chain, so a single
+/// parsing step can provide several dependencies. As with [`filter_map`], each
+/// value replaces a previous value of its type in the container; if the tuple
+/// has several values of the same type, the rightmost one remains. If `proj`
+/// returns `None`, then the handler will return [`ControlFlow::Continue`] with
+/// the old container.
+///
+/// # Examples
+///
+/// ```
+/// # #[tokio::main]
+/// # async fn main() {
+/// use dptree::prelude::*;
+///
+/// #[derive(Clone)]
+/// struct Command(String);
+///
+/// #[derive(Clone)]
+/// struct Args(Vec<String>);
+///
+/// let handler: Handler<_, String> = dptree::filter_map_many(|text: &'static str| {
+///     let mut words = text.strip_prefix('/')?.split_whitespace().map(str::to_owned);
+///     Some((Command(words.next()?), Args(words.collect())))
+/// })
+/// .endpoint(|cmd: Command, args: Args| async move {
+///     format!("{}: {}", cmd.0, args.0.join(", "))
+/// });
+///
+/// assert_eq!(
+///     handler.dispatch(dptree::deps!["/ban alice bob"]).await,
+///     ControlFlow::Break("ban: alice, bob".to_owned())
+/// );
+/// assert!(matches!(handler.dispatch(dptree::deps!["hello"]).await, ControlFlow::Continue(_)));
+/// # }
+/// ```
+#[must_use]
+#[track_caller]
+pub fn filter_map_many<'a, Projection, Input, Output, Values, Args, Descr>(
+    proj: Projection,
+) -> Handler<'a, Input, Output, Descr>
+where
+    Input: Clone,
+    Asyncify<Projection>: Injectable<Input, Option<Values>, Args> + Send + Sync + 'a,
+    Input: InsertMany<Values> + Send + 'a,
+    Output: 'a,
+    Descr: HandlerDescription,
+    Values: Send,
+{
+    let required =
+        <Asyncify<Projection> as Injectable<Input, Option<Values>, Args>>::dependencies();
+    let provided = <Input as InsertMany<Values>>::type_names();
+    filter_map_many_with_description(
+        Descr::filter_map().with_dependencies(&required, &provided),
+        Asyncify(proj),
+    )
+}
+
+/// The asynchronous version of [`filter_map_many`].
+#[must_use]
+#[track_caller]
+pub fn filter_map_many_async<'a, Projection, Input, Output, Values, Args, Descr>(
+    proj: Projection,
+) -> Handler<'a, Input, Output, Descr>
+where
+    Input: Clone,
+    Projection: Injectable<Input, Option<Values>, Args> + Send + Sync + 'a,
+    Input: InsertMany<Values> + Send + 'a,
+    Output: 'a,
+    Descr: HandlerDescription,
+    Values: Send,
+{
+    let required = <Projection as Injectable<Input, Option<Values>, Args>>::dependencies();
+    let provided = <Input as InsertMany<Values>>::type_names();
+    filter_map_many_with_description(
+        Descr::filter_map_async().with_dependencies(&required, &provided),
+        proj,
+    )
+}
+
+fn filter_map_many_with_description<'a, Projection, Input, Output, Values, Args, Descr>(
+    description: Descr,
+    proj: Projection,
+) -> Handler<'a, Input, Output, Descr>
+where
+    Input: Clone,
+    Projection: Injectable<Input, Option<Values>, Args> + Send + Sync + 'a,
+    Input: InsertMany<Values> + Send + 'a,
+    Output: 'a,
+    Values: Send,
+{
+    let proj = Arc::new(proj);
+
+    from_fn_with_description(description, move |container: Input, cont| {
+        let proj = Arc::clone(&proj);
+
+        async move {
+            let proj = proj.inject(&container);
+            let res = proj().await;
+            core::mem::drop(proj);
+
+            match res {
+                Some(values) => {
+                    let mut intermediate = container.clone();
+                    intermediate.insert_many(values);
+                    match cont(intermediate).await {
+                        ControlFlow::Continue(_) => ControlFlow::Continue(container),
+                        ControlFlow::Break(result) => ControlFlow::Break(result),
+                    }
+                }
+                None => ControlFlow::Continue(container),
+            }
+        }
+    })
+}
+
+/// Constructs a handler that optionally passes a value of a new type further,
+/// computed asynchronously from a reference to the input.
+///
+/// Unlike [`filter_map_async`], `f` borrows the whole input instead of
+/// receiving injected values, and the input is never cloned, so it does not
+/// have to implement [`Clone`]. The future returned by `f` may borrow the
+/// input: the handler keeps the input alive until this future completes, but
+/// not longer, so the future cannot be spawned or stored elsewhere.
+///
+/// If the future resolves to `Some(v)`, then `v` is added to the input and
+/// the execution continues further in a handler chain. If it resolves to
+/// `None`, the handler returns [`ControlFlow::Continue`] with the input. Since
+/// the input is not cloned, the input returned when the rest of the chain
+/// continues contains `v`.
+///
+/// # Examples
+///
+/// ```
+/// # #[tokio::main]
+/// # async fn main() {
+/// use dptree::{di::DependencySupplier, prelude::*};
+/// use std::sync::Arc;
+///
+/// let handler: Handler<_, usize> = dptree::filter_map_async_ref(|deps: &DependencyMap| {
+///     let text: Arc<String> = deps.get();
+///     Box::pin(async move { text.split_whitespace().next().map(str::len) })
+/// })
+/// .endpoint(|len: usize| async move { len });
+///
+/// assert_eq!(
+///     handler.dispatch(dptree::deps!["hello world".to_owned()]).await,
+///     ControlFlow::Break(5)
+/// );
+/// # }
+/// ```
+#[must_use]
+#[track_caller]
+pub fn filter_map_async_ref<'a, F, Input, Output, NewType, Descr>(
+    f: F,
+) -> Handler<'a, Input, Output, Descr>
+where
+    F: for<'i> Fn(&'i Input) -> BoxFuture<'i, Option<NewType>> + Send + Sync + 'a,
+    Input: Insert<NewType> + Send + Sync + 'a,
+    Output: 'a,
+    NewType: Send,
+    Descr: HandlerDescription,
+{
+    let provided = [core::any::type_name::<NewType>()];
+    filter_map_async_ref_with_description(
+        Descr::filter_map_async().with_dependencies(&[], &provided),
+        f,
+    )
+}
+
+/// [`filter_map_async_ref`] with a custom description.
+#[must_use]
+pub fn filter_map_async_ref_with_description<'a, F, Input, Output, NewType, Descr>(
+    description: Descr,
+    f: F,
+) -> Handler<'a, Input, Output, Descr>
+where
+    F: for<'i> Fn(&'i Input) -> BoxFuture<'i, Option<NewType>> + Send + Sync + 'a,
+    Input: Insert<NewType> + Send + Sync + 'a,
+    Output: 'a,
+    NewType: Send,
+{
+    let f = Arc::new(f);
+
+    from_fn_with_description(description, move |mut container: Input, cont| {
+        let f = Arc::clone(&f);
+
+        async move {
+            match f(&container).await {
+                Some(new_type) => {
+                    container.insert(new_type);
+                    cont(container).await
+                }
+                None => ControlFlow::Continue(container),
+            }
+        }
+    })
+}
+
+/// Constructs a handler that optionally passes a shared value further,
+/// computed asynchronously from a reference to the input.
+///
+/// This is the same as [`filter_map_async_ref`], except that the future
+/// resolves to an [`Arc`], which is added to the input as is, by
+/// [`InsertArc::insert_arc`], instead of being wrapped into another [`Arc`].
+/// Thus, the value is available to the next handlers as `NewType`, and
+/// [`DependencySupplier::get`] returns the very same [`Arc`] that the future
+/// produced.
+///
+/// [`DependencySupplier::get`]: crate::di::DependencySupplier::get
+///
+/// # Examples
+///
+/// ```
+/// # #[tokio::main]
+/// # async fn main() {
+/// use dptree::prelude::*;
+/// use std::sync::Arc;
+///
+/// #[derive(Clone)]
+/// struct Config {
+///     greeting: &'static str,
+/// }
+///
+/// let config = Arc::new(Config { greeting: "hello" });
+///
+/// let handler: Handler<_, &str> = dptree::filter_map_arc({
+///     let config = Arc::clone(&config);
+///     move |_: &DependencyMap| {
+///         let config = Arc::clone(&config);
+///         Box::pin(async move { Some(config) })
+///     }
+/// })
+/// .endpoint(|config: Config| async move { config.greeting });
+///
+/// assert_eq!(handler.dispatch(dptree::deps![]).await, ControlFlow::Break("hello"));
+/// # }
+/// ```
+#[must_use]
+#[track_caller]
+pub fn filter_map_arc<'a, F, Input, Output, NewType, Descr>(
+    f: F,
+) -> Handler<'a, Input, Output, Descr>
+where
+    F: for<'i> Fn(&'i Input) -> BoxFuture<'i, Option<Arc<NewType>>> + Send + Sync + 'a,
+    Input: InsertArc<NewType> + Clone + Send + Sync + 'a,
+    Output: 'a,
+    NewType: Send + Sync,
+    Descr: HandlerDescription,
+{
+    let provided = [core::any::type_name::<NewType>()];
+    let f = Arc::new(f);
+
+    from_fn_with_description(
+        Descr::filter_map_async().with_dependencies(&[], &provided),
+        move |container: Input, cont| {
+            let f = Arc::clone(&f);
+
+            async move {
+                match f(&container).await {
+                    Some(new_type) => {
+                        let mut intermediate = container.clone();
+                        intermediate.insert_arc(new_type);
+                        match cont(intermediate).await {
+                            ControlFlow::Continue(_) => ControlFlow::Continue(container),
+                            ControlFlow::Break(result) => ControlFlow::Break(result),
+                        }
+                    }
+                    None => ControlFlow::Continue(container),
+                }
+            }
+        },
+    )
+}
+
+/// Constructs a handler that passes a value of a new type further, falling
+/// back to `default`.
+///
+/// Unlike [`filter_map`], this handler never filters: if `proj` returns
+/// `Some(v)`, then `v` is added to the container, otherwise a clone of
+/// `default` is added. In both cases, the execution continues further in a
+/// handler chain, so the next handlers can always request a value of
+/// `NewType`.
+///
+/// See also: [`crate::map`].
+#[must_use]
+#[track_caller]
+pub fn filter_map_or_default<'a, Projection, Input, Output, NewType, Args, Descr>(
+    proj: Projection,
+    default: NewType,
+) -> Handler<'a, Input, Output, Descr>
+where
+    Input: Clone,
+    Asyncify<Projection>: Injectable<Input, Option<NewType>, Args> + Send + Sync + 'a,
+    Input: Insert<NewType> + Send + 'a,
+    Output: 'a,
+    Descr: HandlerDescription,
+    NewType: Clone + Send + Sync + 'a,
+{
+    let required =
+        <Asyncify<Projection> as Injectable<Input, Option<NewType>, Args>>::dependencies();
+    let provided = [core::any::type_name::<NewType>()];
+    filter_map_or_default_with_description(
+        Descr::map().with_dependencies(&required, &provided),
+        Asyncify(proj),
+        default,
+    )
+}
+
+/// The asynchronous version of [`filter_map_or_default`].
+#[must_use]
+#[track_caller]
+pub fn filter_map_or_default_async<'a, Projection, Input, Output, NewType, Args, Descr>(
+    proj: Projection,
+    default: NewType,
+) -> Handler<'a, Input, Output, Descr>
+where
+    Input: Clone,
+    Projection: Injectable<Input, Option<NewType>, Args> + Send + Sync + 'a,
+    Input: Insert<NewType> + Send + 'a,
+    Output: 'a,
+    Descr: HandlerDescription,
+    NewType: Clone + Send + Sync + 'a,
+{
+    let required = <Projection as Injectable<Input, Option<NewType>, Args>>::dependencies();
+    let provided = [core::any::type_name::<NewType>()];
+    filter_map_or_default_with_description(
+        Descr::map_async().with_dependencies(&required, &provided),
+        proj,
+        default,
+    )
+}
+
+fn filter_map_or_default_with_description<'a, Projection, Input, Output, NewType, Args, Descr>(
+    description: Descr,
+    proj: Projection,
+    default: NewType,
+) -> Handler<'a, Input, Output, Descr>
+where
+    Input: Clone,
+    Projection: Injectable<Input, Option<NewType>, Args> + Send + Sync + 'a,
+    Input: Insert<NewType> + Send + 'a,
+    Output: 'a,
+    NewType: Clone + Send + Sync + 'a,
+{
+    let proj = Arc::new(proj);
+
+    from_fn_with_description(description, move |container: Input, cont| {
+        let proj = Arc::clone(&proj);
+        let default = default.clone();
+
+        async move {
+            let proj = proj.inject(&container);
+            let res = proj().await;
+            core::mem::drop(proj);
+
+            let mut intermediate = container.clone();
+            intermediate.insert(res.unwrap_or(default));
+            match cont(intermediate).await {
+                ControlFlow::Continue(_) => ControlFlow::Continue(container),
+                ControlFlow::Break(result) => ControlFlow::Break(result),
+            }
+        }
+    })
+}
+
+/// Constructs a handler that optionally passes a value of a new type further,
+/// dispatching `else_branch` otherwise.
+///
+/// If `proj` returns `Some(v)`, then `v` is added to the container and passed
+/// further in a handler chain, as with [`filter_map`]. If it returns `None`,
+/// `else_branch` is dispatched with the original container instead: the
+/// returned handler breaks with its output if it breaks, and returns
+/// [`ControlFlow::Continue`] with the container otherwise. The rest of the
+/// chain is not executed in the latter case, since it may require `NewType`.
+///
+/// # Examples
+///
+/// ```
+/// # #[tokio::main]
+/// # async fn main() {
+/// use dptree::prelude::*;
+///
+/// let handler: Handler<_, String> = dptree::filter_map_or_else(
+///     |s: &'static str| s.parse::<i32>().ok(),
+///     dptree::endpoint(|s: &'static str| async move { format!("not a number: {s}") }),
+/// )
+/// .endpoint(|x: i32| async move { format!("number: {x}") });
+///
+/// assert_eq!(
+///     handler.dispatch(dptree::deps!["42"]).await,
+///     ControlFlow::Break("number: 42".to_owned())
+/// );
+/// assert_eq!(
+///     handler.dispatch(dptree::deps!["abc"]).await,
+///     ControlFlow::Break("not a number: abc".to_owned())
+/// );
+/// # }
+/// ```
+#[must_use]
+#[track_caller]
+pub fn filter_map_or_else<'a, Projection, Input, Output, NewType, Args, Descr>(
+    proj: Projection,
+    else_branch: Handler<'a, Input, Output, Descr>,
+) -> Handler<'a, Input, Output, Descr>
+where
+    Input: Clone,
+    Asyncify<Projection>: Injectable<Input, Option<NewType>, Args> + Send + Sync + 'a,
+    Input: Insert<NewType> + Send + 'a,
+    Output: 'a,
+    Descr: HandlerDescription,
+    NewType: Send,
+{
+    let required =
+        <Asyncify<Projection> as Injectable<Input, Option<NewType>, Args>>::dependencies();
+    let provided = [core::any::type_name::<NewType>()];
+    filter_map_or_else_with_description(
+        Descr::filter_map().with_dependencies(&required, &provided),
+        Asyncify(proj),
+        else_branch,
+    )
+}
+
+/// The asynchronous version of [`filter_map_or_else`].
+#[must_use]
+#[track_caller]
+pub fn filter_map_or_else_async<'a, Projection, Input, Output, NewType, Args, Descr>(
+    proj: Projection,
+    else_branch: Handler<'a, Input, Output, Descr>,
+) -> Handler<'a, Input, Output, Descr>
+where
+    Input: Clone,
+    Projection: Injectable<Input, Option<NewType>, Args> + Send + Sync + 'a,
+    Input: Insert<NewType> + Send + 'a,
+    Output: 'a,
+    Descr: HandlerDescription,
+    NewType: Send,
+{
+    let required = <Projection as Injectable<Input, Option<NewType>, Args>>::dependencies();
+    let provided = [core::any::type_name::<NewType>()];
+    filter_map_or_else_with_description(
+        Descr::filter_map_async().with_dependencies(&required, &provided),
+        proj,
+        else_branch,
+    )
+}
+
+fn filter_map_or_else_with_description<'a, Projection, Input, Output, NewType, Args, Descr>(
+    description: Descr,
+    proj: Projection,
+    else_branch: Handler<'a, Input, Output, Descr>,
+) -> Handler<'a, Input, Output, Descr>
+where
+    Input: Clone,
+    Projection: Injectable<Input, Option<NewType>, Args> + Send + Sync + 'a,
+    Input: Insert<NewType> + Send + 'a,
+    Output: 'a,
+    Descr: HandlerDescription,
+    NewType: Send,
+{
+    let description = description.merge_branch(else_branch.description());
+    let proj = Arc::new(proj);
+
+    from_fn_with_description(description, move |container: Input, cont| {
+        let proj = Arc::clone(&proj);
+        let else_branch = else_branch.clone();
+
+        async move {
+            let proj = proj.inject(&container);
+            let res = proj().await;
+            core::mem::drop(proj);
+
+            match res {
+                Some(new_type) => {
+                    let mut intermediate = container.clone();
+                    intermediate.insert(new_type);
+                    match cont(intermediate).await {
+                        ControlFlow::Continue(_) => ControlFlow::Continue(container),
+                        ControlFlow::Break(result) => ControlFlow::Break(result),
+                    }
+                }
+                None => else_branch.dispatch(container).await,
+            }
+        }
+    })
+}
+
+/// Constructs a handler that converts a value of type `Source` into `T` with
+/// [`TryFrom`].
+///
+/// This is a shortcut for [`filter_map`] with the projection
+/// `|source: Source| T::try_from(source).ok()`: if the conversion succeeds,
+/// the converted value is added to the container and passed further in a
+/// handler chain, otherwise the handler returns [`ControlFlow::Continue`]
+/// with the old container.
+///
+/// # Examples
+///
+/// ```
+/// # #[tokio::main]
+/// # async fn main() {
+/// use dptree::prelude::*;
+/// use std::convert::TryFrom;
+///
+/// #[derive(Clone)]
+/// struct Port(u16);
+///
+/// impl TryFrom<&'static str> for Port {
+///     type Error = std::num::ParseIntError;
+///
+///     fn try_from(s: &'static str) -> Result<Self, Self::Error> {
+///         s.parse().map(Port)
+///     }
+/// }
+///
+/// let handler: Handler<_, u16> = dptree::entry()
+///     .try_into_filter::<&'static str, Port>()
+///     .endpoint(|port: Port| async move { port.0 });
+///
+/// assert_eq!(handler.dispatch(dptree::deps!["8080"]).await, ControlFlow::Break(8080));
+/// assert!(matches!(handler.dispatch(dptree::deps!["http"]).await, ControlFlow::Continue(_)));
+/// # }
+/// ```
+#[must_use]
+#[track_caller]
+pub fn try_into_filter<'a, Source, T, Input, Output, Descr>() -> Handler<'a, Input, Output, Descr>
+where
+    Source: 'static,
+    T: TryFrom<Source> + Send + 'static,
+    Asyncify<fn(Source) -> Option<T>>: Injectable<Input, Option<T>, (Source,)>,
+    Input: Insert<T> + Clone + Send + 'a,
+    Output: 'a,
+    Descr: HandlerDescription,
+{
+    let proj: fn(Source) -> Option<T> = |source| T::try_from(source).ok();
+    let required = <Asyncify<fn(Source) -> Option<T>> as Injectable<
+        Input,
+        Option<T>,
+        (Source,),
+    >>::dependencies();
+    let provided = [core::any::type_name::<T>()];
+    filter_map_with_description(Descr::filter_map().with_dependencies(&required, &provided), proj)
+}
+
+/// Constructs a handler that executes `handler` once per value produced by
+/// `f`.
+///
+/// For each value returned by `f`, `handler` is dispatched with a copy of the
+/// input to which this value is added. The outputs of all dispatches that
+/// break are collected, in order, into the output of the returned handler. If
+/// `f` returns no values or `handler` continues on all of them, the returned
+/// handler returns [`ControlFlow::Continue`] with the original input.
+///
+/// Since `handler` plays the role of the rest of the chain, the returned
+/// handler is [terminal](crate::Handler#terminal-handlers).
+///
+/// # Examples
+///
+/// ```
+/// # #[tokio::main]
+/// # async fn main() {
+/// use dptree::{di::DependencySupplier, prelude::*};
+///
+/// let handler: Handler<_, _> = dptree::filter_map_iter(
+///     |deps: &DependencyMap| {
+///         let s: std::sync::Arc<&'static str> = deps.get();
+///         s.split(',').map(str::to_owned).collect()
+///     },
+///     dptree::filter(|s: String| !s.is_empty()).endpoint(|s: String| async move { s.len() }),
+/// );
+///
+/// assert_eq!(handler.dispatch(dptree::deps!["a,,bcd"]).await, ControlFlow::Break(vec![1, 3]));
+/// assert!(matches!(handler.dispatch(dptree::deps![","]).await, ControlFlow::Continue(_)));
+/// # }
+/// ```
+#[must_use]
+#[track_caller]
+pub fn filter_map_iter<'a, F, Input, Output, NewType, Descr>(
+    f: F,
+    handler: Handler<'a, Input, Output, Descr>,
+) -> Handler<'a, Input, Vec<Output>, Descr>
+where
+    F: Fn(&Input) -> Vec<NewType> + Send + Sync + 'a,
+    Input: Insert<NewType> + Clone + Send + 'a,
+    Output: Send + 'a,
+    NewType: Send + 'a,
+    Descr: HandlerDescription,
+{
+    let description = Descr::filter_map()
+        .with_dependencies(&[], &[core::any::type_name::<NewType>()])
+        .merge_chain(handler.description());
+
+    from_fn_with_description(description, move |container: Input, _cont| {
+        let values = f(&container);
+        let handler = handler.clone();
+
+        async move {
+            let mut outputs = Vec::new();
+
+            for value in values {
+                let mut intermediate = container.clone();
+                intermediate.insert(value);
+                if let ControlFlow::Break(output) = handler.dispatch(intermediate).await {
+                    outputs.push(output);
+                }
+            }
+
+            if outputs.is_empty() {
+                ControlFlow::Continue(container)
+            } else {
+                ControlFlow::Break(outputs)
+            }
+        }
+    })
+}
+
+/// The streaming version of [`filter_map_iter`].
+///
+/// `handler` is dispatched once per item of the stream returned by `f`, as
+/// soon as the item is produced, which is useful for paginated or streamed
+/// enrichment. The items are handled one by one, in order, and the outputs
+/// are collected as in [`filter_map_iter`].
+///
+/// # Examples
+///
+/// ```
+/// # #[tokio::main]
+/// # async fn main() {
+/// use dptree::{di::DependencySupplier, prelude::*};
+///
+/// let handler: Handler<_, _> = dptree::filter_map_stream(
+///     |deps: &DependencyMap| {
+///         let pages: std::sync::Arc<u32> = deps.get();
+///         futures::stream::iter(1..=*pages)
+///     },
+///     dptree::endpoint(|page: u32| async move { format!("page {page}") }),
+/// );
+///
+/// assert_eq!(
+///     handler.dispatch(dptree::deps![2u32]).await,
+///     ControlFlow::Break(vec!["page 1".to_owned(), "page 2".to_owned()])
+/// );
+/// # }
+/// ```
+#[must_use]
+#[track_caller]
+pub fn filter_map_stream<'a, F, S, Input, Output, NewType, Descr>(
+    f: F,
+    handler: Handler<'a, Input, Output, Descr>,
+) -> Handler<'a, Input, Vec<Output>, Descr>
+where
+    F: Fn(&Input) -> S + Send + Sync + 'a,
+    S: Stream<Item = NewType> + Send + 'a,
+    Input: Insert<NewType> + Clone + Send + 'a,
+    Output: Send + 'a,
+    NewType: Send + 'a,
+    Descr: HandlerDescription,
+{
+    let description = Descr::filter_map_async()
+        .with_dependencies(&[], &[core::any::type_name::<NewType>()])
+        .merge_chain(handler.description());
+
+    from_fn_with_description(description, move |container: Input, _cont| {
+        let values = f(&container);
+        let handler = handler.clone();
+
+        async move {
+            futures::pin_mut!(values);
+            let mut outputs = Vec::new();
+
+            while let Some(value) = values.next().await {
+                let mut intermediate = container.clone();
+                intermediate.insert(value);
+                if let ControlFlow::Break(output) = handler.dispatch(intermediate).await {
+                    outputs.push(output);
+                }
+            }
+
+            if outputs.is_empty() {
+                ControlFlow::Continue(container)
+            } else {
+                ControlFlow::Break(outputs)
+            }
+        }
+    })
+}
+
 #[cfg(test)]
 mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
     use super::*;
-    use crate::{deps, help_inference};
+    use crate::{deps, di::DependencyMap, help_inference};
 
     #[tokio::test]
     async fn test_some() {
@@ -126,4 +829,301 @@ mod tests {
 
         assert!(result == ControlFlow::Continue(crate::deps![]));
     }
+
+    #[tokio::test]
+    async fn test_filter_map_many() {
+        let handler = help_inference(filter_map_many(|s: &'static str| {
+            let (name, age) = s.split_once(':')?;
+            Some((name.to_owned(), age.parse::<u8>().ok()?))
+        }))
+        .endpoint(|name: String, age: u8| async move { format!("{name} is {age}") });
+
+        assert_eq!(
+            handler.dispatch(deps!["alice:30"]).await,
+            ControlFlow::Break("alice is 30".to_owned())
+        );
+        assert_eq!(handler.dispatch(deps!["alice"]).await, ControlFlow::Continue(deps!["alice"]));
+    }
+
+    #[tokio::test]
+    async fn test_filter_map_many_overwrite() {
+        // The values replace the existing ones, and the rightmost value of a
+        // type remains.
+        let handler = help_inference(filter_map_many_async(|| async { Some((1u8, 2i32, 3u8)) }))
+            .endpoint(|x: u8, y: i32| async move { (x, y) });
+
+        assert_eq!(handler.dispatch(deps![0u8, 0i32]).await, ControlFlow::Break((3, 2)));
+    }
+
+    #[test]
+    fn test_filter_map_many_description() {
+        use crate::description::Tree;
+
+        let handler: Handler<DependencyMap, (), Tree> =
+            filter_map_many(|x: i32| Some((x as u8, x as i64)));
+
+        assert_eq!(handler.description().required, ["i32"]);
+        assert_eq!(handler.description().provided, ["u8", "i64"]);
+    }
+
+    #[tokio::test]
+    async fn test_try_into_filter() {
+        #[derive(Debug, Clone, PartialEq)]
+        struct Age(u8);
+
+        impl TryFrom<&'static str> for Age {
+            type Error = ();
+
+            fn try_from(s: &'static str) -> Result<Self, Self::Error> {
+                s.parse().map(Age).map_err(|_| ())
+            }
+        }
+
+        let handler = help_inference(try_into_filter::<&'static str, Age, _, _, _>())
+            .endpoint(|age: Age| async move { age });
+
+        assert_eq!(handler.dispatch(deps!["42"]).await, ControlFlow::Break(Age(42)));
+        assert_eq!(handler.dispatch(deps!["-1"]).await, ControlFlow::Continue(deps!["-1"]));
+        assert_eq!(handler.dispatch(deps!["abc"]).await, ControlFlow::Continue(deps!["abc"]));
+    }
+
+    #[tokio::test]
+    async fn test_try_into_filter_numbers() {
+        let handler = help_inference(crate::entry())
+            .try_into_filter::<i64, u8>()
+            .endpoint(|x: u8| async move { x });
+
+        assert_eq!(handler.dispatch(deps![255i64]).await, ControlFlow::Break(255));
+        assert_eq!(handler.dispatch(deps![256i64]).await, ControlFlow::Continue(deps![256i64]));
+    }
+
+    #[tokio::test]
+    async fn test_or_default_some() {
+        let result = help_inference(filter_map_or_default(|x: i32| x.checked_mul(2), 0))
+            .endpoint(|x: i32| async move { x })
+            .dispatch(deps![21])
+            .await;
+
+        assert!(result == ControlFlow::Break(42));
+    }
+
+    #[tokio::test]
+    async fn test_or_default_none() {
+        let result = help_inference(filter_map_or_default(|x: i32| x.checked_mul(2), 0))
+            .endpoint(|x: i32| async move { x })
+            .dispatch(deps![i32::MAX])
+            .await;
+
+        assert!(result == ControlFlow::Break(0));
+    }
+
+    #[tokio::test]
+    async fn test_or_default_async() {
+        let result = help_inference(filter_map_or_default_async(
+            || async { None::<&'static str> },
+            "default",
+        ))
+        .endpoint(|s: &'static str| async move { s })
+        .dispatch(deps![])
+        .await;
+
+        assert!(result == ControlFlow::Break("default"));
+    }
+
+    #[tokio::test]
+    async fn test_or_else() {
+        let handler = help_inference(filter_map_or_else(
+            |x: i32| u8::try_from(x).ok(),
+            crate::endpoint(|x: i32| async move { format!("fallback {x}") }),
+        ))
+        .endpoint(|x: u8| async move { format!("main {x}") });
+
+        assert_eq!(handler.dispatch(deps![42]).await, ControlFlow::Break("main 42".to_owned()));
+        assert_eq!(handler.dispatch(deps![-1]).await, ControlFlow::Break("fallback -1".to_owned()));
+    }
+
+    #[tokio::test]
+    async fn test_or_else_continue() {
+        // Neither the main chain nor the fallback handles the input.
+        let handler = help_inference(filter_map_or_else_async(
+            || async { None::<u8> },
+            crate::filter(|x: i32| x > 0).endpoint(|| async { "fallback" }),
+        ))
+        .endpoint(|| async { unreachable!() });
+
+        assert_eq!(handler.dispatch(deps![1]).await, ControlFlow::Break("fallback"));
+        assert_eq!(handler.dispatch(deps![-1]).await, ControlFlow::Continue(deps![-1]));
+    }
+
+    #[tokio::test]
+    async fn test_filter_map_iter() {
+        let fired = Arc::new(AtomicUsize::new(0));
+
+        let handler = filter_map_iter(
+            |_: &DependencyMap| vec![1, 2, 3],
+            help_inference(crate::endpoint({
+                let fired = Arc::clone(&fired);
+                move |x: i32| {
+                    fired.fetch_add(1, Ordering::Relaxed);
+                    async move { x * 10 }
+                }
+            })),
+        );
+
+        assert_eq!(handler.dispatch(deps![0]).await, ControlFlow::Break(vec![10, 20, 30]));
+        assert_eq!(fired.load(Ordering::Relaxed), 3);
+    }
+
+    #[tokio::test]
+    async fn test_filter_map_iter_empty() {
+        let handler = filter_map_iter(
+            |_: &DependencyMap| Vec::<i32>::new(),
+            help_inference(crate::endpoint(|| async { unreachable!() })),
+        );
+
+        let result: ControlFlow<Vec<()>, _> = handler.dispatch(deps![0]).await;
+        assert!(result == ControlFlow::Continue(deps![0]));
+    }
+
+    #[tokio::test]
+    async fn test_filter_map_iter_partial() {
+        let handler = filter_map_iter(
+            |_: &DependencyMap| vec![1, -2, 3],
+            help_inference(crate::filter(|x: i32| x > 0)).endpoint(|x: i32| async move { x }),
+        );
+
+        assert_eq!(handler.dispatch(deps![]).await, ControlFlow::Break(vec![1, 3]));
+    }
+
+    #[tokio::test]
+    async fn test_filter_map_stream() {
+        let fired = Arc::new(AtomicUsize::new(0));
+
+        let handler = filter_map_stream(
+            |_: &DependencyMap| {
+                futures::stream::iter(vec!["a", "b", "c"]).then(|s| async move {
+                    tokio::task::yield_now().await;
+                    s
+                })
+            },
+            help_inference(crate::inspect({
+                let fired = Arc::clone(&fired);
+                move || {
+                    fired.fetch_add(1, Ordering::Relaxed);
+                }
+            }))
+            .endpoint(|s: &'static str, x: i32| async move { format!("{s}{x}") }),
+        );
+
+        assert_eq!(
+            handler.dispatch(deps![1]).await,
+            ControlFlow::Break(vec!["a1".to_owned(), "b1".to_owned(), "c1".to_owned()])
+        );
+        assert_eq!(fired.load(Ordering::Relaxed), 3);
+    }
+
+    #[tokio::test]
+    async fn test_filter_map_stream_empty() {
+        let handler = filter_map_stream(
+            |_: &DependencyMap| futures::stream::empty::<i32>(),
+            help_inference(crate::endpoint(|| async { unreachable!() })),
+        );
+
+        let result: ControlFlow<Vec<()>, _> = handler.dispatch(deps![0]).await;
+        assert!(result == ControlFlow::Continue(deps![0]));
+    }
+
+    #[tokio::test]
+    async fn test_filter_map_async_ref() {
+        // A large input that cannot be cloned.
+        #[derive(Debug, PartialEq)]
+        struct Document {
+            pages: Vec<String>,
+            word_count: Option<usize>,
+        }
+
+        impl Insert<usize> for Document {
+            fn insert(&mut self, value: usize) -> Option<Arc<usize>> {
+                self.word_count.replace(value).map(Arc::new)
+            }
+        }
+
+        let handler: Handler<Document, usize> = filter_map_async_ref(|doc: &Document| {
+            Box::pin(async move {
+                tokio::task::yield_now().await;
+                let count: usize = doc.pages.iter().map(|p| p.split_whitespace().count()).sum();
+                Some(count).filter(|&n| n > 0)
+            })
+        })
+        .chain(crate::from_fn(|doc: Document, _cont| async move {
+            ControlFlow::Break(doc.word_count.unwrap())
+        }));
+
+        let doc = Document { pages: vec!["a b".to_owned(), "c".to_owned()], word_count: None };
+        assert_eq!(handler.dispatch(doc).await, ControlFlow::Break(3));
+
+        let doc = Document { pages: vec![], word_count: None };
+        assert!(matches!(
+            handler.dispatch(doc).await,
+            ControlFlow::Continue(Document { word_count: None, .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_filter_map_arc() {
+        use crate::di::DependencySupplier;
+
+        let produced = Arc::new("shared".to_owned());
+
+        let handler: Handler<DependencyMap, bool> = filter_map_arc({
+            let produced = Arc::clone(&produced);
+            move |deps: &DependencyMap| {
+                let produced = Arc::clone(&produced);
+                let flag: Arc<bool> = deps.get();
+                Box::pin(async move {
+                    if *flag {
+                        Some(produced)
+                    } else {
+                        None
+                    }
+                })
+            }
+        })
+        .chain(crate::from_fn({
+            let produced = Arc::clone(&produced);
+            move |deps: DependencyMap, _cont| {
+                let injected: Arc<String> = deps.get();
+                let same = Arc::ptr_eq(&injected, &produced);
+                async move { ControlFlow::Break(same) }
+            }
+        }));
+
+        assert_eq!(handler.dispatch(deps![true]).await, ControlFlow::Break(true));
+        match handler.dispatch(deps![false]).await {
+            ControlFlow::Continue(deps) => assert!(!deps.contains::<String>()),
+            ControlFlow::Break(_) => panic!("the handler must continue"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_filter_map_arc_continue() {
+        let handler: Handler<DependencyMap, ()> =
+            filter_map_arc(|_: &DependencyMap| Box::pin(async { Some(Arc::new(5i32)) }))
+                .filter(|| false);
+
+        // The value is not leaked to the next branches.
+        match handler.dispatch(deps![]).await {
+            ControlFlow::Continue(deps) => assert!(!deps.contains::<i32>()),
+            ControlFlow::Break(()) => panic!("the handler must continue"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_filter_map_arc_injection() {
+        let handler: Handler<DependencyMap, String> =
+            filter_map_arc(|_: &DependencyMap| Box::pin(async { Some(Arc::new(5i32)) }))
+                .endpoint(|x: i32| async move { x.to_string() });
+
+        assert_eq!(handler.dispatch(deps![]).await, ControlFlow::Break("5".to_owned()));
+    }
 }
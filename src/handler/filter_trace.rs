@@ -0,0 +1,190 @@
+use crate::{
+    description::{NodeKind, Tree},
+    Cont, Handler,
+};
+use std::{
+    cell::RefCell,
+    future::Future,
+    ops::ControlFlow,
+    pin::Pin,
+    sync::{Arc, Mutex, PoisonError},
+    task::{Context, Poll},
+};
+
+type Records = Arc<Mutex<Vec<(&'static str, bool)>>>;
+
+thread_local! {
+    // The records of the innermost `Handler::dispatch_with_trace` being
+    // polled on this thread.
+    static RECORDS: RefCell<Option<Records>> = const { RefCell::new(None) };
+}
+
+/// The filters visited during a dispatch.
+///
+/// See [`Handler::dispatch_with_trace`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Trace {
+    /// The labels of the visited filters along with whether they passed the
+    /// input further, in the order of visiting.
+    pub filters: Vec<(&'static str, bool)>,
+}
+
+impl Trace {
+    /// Returns the labels of the visited filters that rejected the input.
+    pub fn rejected(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.filters.iter().filter(|(_, passed)| !passed).map(|&(label, _)| label)
+    }
+}
+
+impl<'a, Input, Output> Handler<'a, Input, Output, Tree>
+where
+    Input: Send + 'a,
+    Output: 'a,
+{
+    /// Executes this handler, recording which filters passed the input and
+    /// which rejected it.
+    ///
+    /// Only [labeled](Handler::label) nodes of the filter kinds are recorded:
+    /// [`NodeKind::Filter`], [`NodeKind::FilterAsync`],
+    /// [`NodeKind::FilterMap`], and [`NodeKind::FilterMapAsync`]. A filter
+    /// passes the input if it executes the rest of its chain. Filters executed
+    /// in tasks spawned by the handler are not recorded.
+    ///
+    /// This is useful to find out why a dispatch has continued, i.e., which
+    /// filters rejected the input along each of the tried branches.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use dptree::{description::Tree, prelude::*};
+    ///
+    /// let handler: Handler<DependencyMap, (), Tree> = dptree::entry()
+    ///     .branch(
+    ///         dptree::filter(|s: &'static str| s.starts_with('/'))
+    ///             .label("command")
+    ///             .chain(dptree::filter(|admin: bool| admin).label("admin"))
+    ///             .endpoint(|| async {}),
+    ///     )
+    ///     .branch(
+    ///         dptree::filter(|s: &'static str| s.is_empty()).label("empty").endpoint(|| async {}),
+    ///     );
+    ///
+    /// let (result, trace) = handler.dispatch_with_trace(dptree::deps!["/ban", false]).await;
+    /// assert!(matches!(result, ControlFlow::Continue(_)));
+    /// assert_eq!(trace.filters, [("command", true), ("admin", false), ("empty", false)]);
+    /// # }
+    /// ```
+    pub async fn dispatch_with_trace(
+        &self,
+        container: Input,
+    ) -> (ControlFlow<Output, Input>, Trace) {
+        let records = Records::default();
+        let result =
+            Traced { inner: Box::pin(self.dispatch(container)), records: Arc::clone(&records) }
+                .await;
+
+        let filters = records.lock().unwrap_or_else(PoisonError::into_inner).clone();
+        (result, Trace { filters })
+    }
+}
+
+/// Records the execution of a labeled node, if a trace is being recorded and
+/// the node is a filter.
+///
+/// Returns the continuation to pass to the node.
+pub(crate) fn record<'a, Input, Output>(
+    kind: NodeKind,
+    label: &'static str,
+    cont: Cont<'a, Input, Output>,
+) -> Cont<'a, Input, Output>
+where
+    Input: 'a,
+    Output: 'a,
+{
+    let is_filter = matches!(
+        kind,
+        NodeKind::Filter | NodeKind::FilterAsync | NodeKind::FilterMap | NodeKind::FilterMapAsync
+    );
+    let records = match RECORDS.with(|records| records.borrow().clone()) {
+        Some(records) if is_filter => records,
+        _ => return cont,
+    };
+
+    let index = {
+        let mut records = records.lock().unwrap_or_else(PoisonError::into_inner);
+        records.push((label, false));
+        records.len() - 1
+    };
+
+    Box::new(move |event| {
+        records.lock().unwrap_or_else(PoisonError::into_inner)[index].1 = true;
+        cont(event)
+    })
+}
+
+/// A future that sets `RECORDS` while polling `inner`.
+struct Traced<F> {
+    inner: F,
+    records: Records,
+}
+
+impl<F> Future for Traced<F>
+where
+    F: Future + Unpin,
+{
+    type Output = F::Output;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // `dispatch_with_trace` may be called inside of another one, so the
+        // outer records are saved and restored.
+        let outer = RECORDS.with(|records| records.borrow_mut().replace(Arc::clone(&self.records)));
+        let poll = Pin::new(&mut self.inner).poll(cx);
+        RECORDS.with(|records| *records.borrow_mut() = outer);
+
+        poll
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{deps, di::DependencyMap};
+
+    fn handler() -> Handler<'static, DependencyMap, &'static str, Tree> {
+        crate::entry()
+            .branch(
+                crate::filter(|x: i32| x > 0)
+                    .label("positive")
+                    .chain(
+                        crate::filter_map(|x: i32| if x < 100 { Some(x as u8) } else { None })
+                            .label("small"),
+                    )
+                    .endpoint(|| async { "small positive" }),
+            )
+            .branch(crate::filter(|x: i32| x == 0).label("zero").endpoint(|| async { "zero" }))
+            .branch(crate::filter(|x: i32| x < 0).endpoint(|| async { "negative" }))
+    }
+
+    #[tokio::test]
+    async fn test_dispatch_with_trace() {
+        let (result, trace) = handler().dispatch_with_trace(deps![1000]).await;
+
+        assert_eq!(result, ControlFlow::Continue(deps![1000]));
+        assert_eq!(trace.filters, [("positive", true), ("small", false), ("zero", false)]);
+        assert_eq!(trace.rejected().collect::<Vec<_>>(), ["small", "zero"]);
+    }
+
+    #[tokio::test]
+    async fn test_dispatch_with_trace_break() {
+        let (result, trace) = handler().dispatch_with_trace(deps![0]).await;
+
+        assert_eq!(result, ControlFlow::Break("zero"));
+        assert_eq!(trace.filters, [("positive", false), ("zero", true)]);
+
+        // Nothing is recorded outside of `dispatch_with_trace`.
+        assert_eq!(handler().dispatch(deps![0]).await, ControlFlow::Break("zero"));
+        assert!(RECORDS.with(|records| records.borrow().is_none()));
+    }
+}
@@ -0,0 +1,313 @@
+use crate::{from_fn_with_description, Handler, HandlerDescription};
+use alloc::sync::Arc;
+use core::ops::ControlFlow;
+use futures::future::BoxFuture;
+
+impl<'a, Input, Output, Descr> Handler<'a, Input, Output, Descr>
+where
+    Input: Send + 'a,
+    Output: 'a,
+    Descr: HandlerDescription,
+{
+    /// Returns a handler that calls `before` and `after` around this handler.
+    ///
+    /// When the returned handler is executed, it first calls `before` with the
+    /// input, then executes this handler, and finally calls `after` with the
+    /// value returned by `before` and the result of the execution. Since this
+    /// handler is executed along with the rest of the chain, the result is
+    /// the one of the whole chain starting at this handler.
+    ///
+    /// `before` only borrows the input for the duration of the call, so it
+    /// cannot keep references to it; instead, it should extract what it needs
+    /// into its return value, which is kept until `after` is called. Likewise,
+    /// `after` borrows the result, which is then returned unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use dptree::{di::DependencySupplier, prelude::*};
+    /// use std::sync::{Arc, Mutex};
+    ///
+    /// let log = Arc::new(Mutex::new(Vec::new()));
+    ///
+    /// let handler: Handler<DependencyMap, i32> = dptree::filter(|x: i32| x > 0)
+    ///     .endpoint(|x: i32| async move { x * 2 })
+    ///     .around(|input: &DependencyMap| *input.get(), {
+    ///         let log = Arc::clone(&log);
+    ///         move |request: i32, result: &ControlFlow<i32, DependencyMap>| {
+    ///             log.lock().unwrap().push((request, result.clone().break_value()))
+    ///         }
+    ///     });
+    ///
+    /// let _ = handler.dispatch(dptree::deps![21]).await;
+    /// let _ = handler.dispatch(dptree::deps![-1]).await;
+    ///
+    /// assert_eq!(*log.lock().unwrap(), vec![(21, Some(42)), (-1, None)]);
+    /// # }
+    /// ```
+    #[must_use]
+    #[track_caller]
+    pub fn around<Before, After, Guard>(self, before: Before, after: After) -> Self
+    where
+        Before: Fn(&Input) -> Guard + Send + Sync + 'a,
+        After: Fn(Guard, &ControlFlow<Output, Input>) + Send + Sync + 'a,
+        Guard: Send + 'a,
+    {
+        let description = self.wrapper_description();
+        let after = Arc::new(after);
+
+        from_fn_with_description(description, move |event, cont| {
+            let this = self.clone();
+            let after = Arc::clone(&after);
+            let guard = before(&event);
+
+            async move {
+                let result = this.execute(event, cont).await;
+                after(guard, &result);
+                result
+            }
+        })
+    }
+
+    /// Returns a handler that awaits `f` on the output before breaking with it.
+    ///
+    /// When the execution of this handler (along with the rest of the chain)
+    /// breaks, `f` is called with a reference to the output, and the returned
+    /// handler breaks only after the future returned by `f` completes. The
+    /// output is not cloned: the future can borrow it until completion. This is
+    /// useful for asynchronous audit sinks. If the execution continues, `f` is
+    /// not called.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use dptree::prelude::*;
+    /// use std::sync::Arc;
+    /// use tokio::sync::Mutex;
+    ///
+    /// let sink = Arc::new(Mutex::new(Vec::new()));
+    ///
+    /// let handler: Handler<_, String> = dptree::endpoint(|x: i32| async move { x.to_string() })
+    ///     .inspect_break_async({
+    ///         let sink = Arc::clone(&sink);
+    ///         move |output: &String| {
+    ///             let sink = Arc::clone(&sink);
+    ///             Box::pin(async move { sink.lock().await.push(output.len()) })
+    ///         }
+    ///     });
+    ///
+    /// let _ = handler.dispatch(dptree::deps![12345]).await;
+    /// assert_eq!(*sink.lock().await, vec![5]);
+    /// # }
+    /// ```
+    #[must_use]
+    #[track_caller]
+    pub fn inspect_break_async<F>(self, f: F) -> Self
+    where
+        Output: Send + Sync,
+        F: for<'o> Fn(&'o Output) -> BoxFuture<'o, ()> + Send + Sync + 'a,
+    {
+        let description = self.wrapper_description();
+        let f = Arc::new(f);
+
+        from_fn_with_description(description, move |event, cont| {
+            let this = self.clone();
+            let f = Arc::clone(&f);
+
+            async move {
+                let result = this.execute(event, cont).await;
+                if let ControlFlow::Break(output) = &result {
+                    f(output).await;
+                }
+                result
+            }
+        })
+    }
+
+    /// Returns a handler that calls `on_start` and `on_end` once per dispatch
+    /// of this handler as a whole.
+    ///
+    /// This is meant for the root of a handler tree: `on_start` is called with
+    /// the input before the tree runs, and `on_end` is called with the final
+    /// result after the tree completes, which makes it a natural place for
+    /// request logging and metrics spanning the whole dispatch.
+    ///
+    /// Unlike [`Handler::around`], the hooks bracket this handler only: the
+    /// returned handler is [terminal](crate::Handler#terminal-handlers).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use dptree::prelude::*;
+    /// use std::sync::atomic::{AtomicUsize, Ordering};
+    ///
+    /// static IN_FLIGHT: AtomicUsize = AtomicUsize::new(0);
+    ///
+    /// let handler: Handler<_, _> = dptree::entry()
+    ///     .branch(dptree::filter(|x: i32| x > 0).endpoint(|| async { "positive" }))
+    ///     .branch(dptree::endpoint(|| async { "non-positive" }))
+    ///     .with_lifecycle(
+    ///         |_: &DependencyMap| {
+    ///             IN_FLIGHT.fetch_add(1, Ordering::SeqCst);
+    ///         },
+    ///         |result: &ControlFlow<&'static str, DependencyMap>| {
+    ///             IN_FLIGHT.fetch_sub(1, Ordering::SeqCst);
+    ///             println!("handled: {:?}", result.clone().break_value());
+    ///         },
+    ///     );
+    ///
+    /// assert_eq!(handler.dispatch(dptree::deps![1]).await, ControlFlow::Break("positive"));
+    /// assert_eq!(IN_FLIGHT.load(Ordering::SeqCst), 0);
+    /// # }
+    /// ```
+    #[must_use]
+    #[track_caller]
+    pub fn with_lifecycle<OnStart, OnEnd>(self, on_start: OnStart, on_end: OnEnd) -> Self
+    where
+        OnStart: Fn(&Input) + Send + Sync + 'a,
+        OnEnd: Fn(&ControlFlow<Output, Input>) + Send + Sync + 'a,
+    {
+        let description = self.wrapper_description();
+        let on_end = Arc::new(on_end);
+
+        from_fn_with_description(description, move |event, _cont| {
+            let this = self.clone();
+            let on_end = Arc::clone(&on_end);
+            on_start(&event);
+
+            async move {
+                let result = this.dispatch(event).await;
+                on_end(&result);
+                result
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+    use crate::{deps, di::DependencySupplier, help_inference, prelude::DependencyMap};
+
+    #[tokio::test]
+    async fn test_around() {
+        let log = Arc::new(Mutex::new(Vec::new()));
+
+        let handler = help_inference(crate::filter(|x: i32| x % 2 == 0))
+            .around(
+                {
+                    let log = Arc::clone(&log);
+                    move |input: &DependencyMap| {
+                        let x: Arc<i32> = input.get();
+                        log.lock().unwrap().push(format!("before {x}"));
+                        *x
+                    }
+                },
+                {
+                    let log = Arc::clone(&log);
+                    move |x: i32, result: &ControlFlow<String, DependencyMap>| {
+                        let result = match result {
+                            ControlFlow::Break(output) => output.clone(),
+                            ControlFlow::Continue(_) => "continue".to_owned(),
+                        };
+                        log.lock().unwrap().push(format!("after {x}: {result}"));
+                    }
+                },
+            )
+            .endpoint(|x: i32| async move { format!("even {x}") });
+
+        assert_eq!(handler.dispatch(deps![2]).await, ControlFlow::Break("even 2".to_owned()));
+        assert_eq!(handler.dispatch(deps![3]).await, ControlFlow::Continue(deps![3]));
+
+        assert_eq!(
+            *log.lock().unwrap(),
+            ["before 2", "after 2: even 2", "before 3", "after 3: continue"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_inspect_break_async() {
+        let sink = Arc::new(Mutex::new(Vec::new()));
+
+        let handler = help_inference(crate::filter(|x: i32| x > 0))
+            .endpoint(|x: i32| async move { format!("got {x}") })
+            .inspect_break_async({
+                let sink = Arc::clone(&sink);
+                move |output: &String| {
+                    let sink = Arc::clone(&sink);
+                    Box::pin(async move {
+                        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+                        sink.lock().unwrap().push(output.as_str().to_owned());
+                    })
+                }
+            });
+
+        assert_eq!(handler.dispatch(deps![1]).await, ControlFlow::Break("got 1".to_owned()));
+        // The sink has completed before the dispatch returned.
+        assert_eq!(*sink.lock().unwrap(), ["got 1"]);
+
+        assert_eq!(handler.dispatch(deps![-1]).await, ControlFlow::Continue(deps![-1]));
+        assert_eq!(handler.dispatch(deps![2]).await, ControlFlow::Break("got 2".to_owned()));
+        assert_eq!(*sink.lock().unwrap(), ["got 1", "got 2"]);
+    }
+
+    #[tokio::test]
+    async fn test_with_lifecycle() {
+        let log = Arc::new(Mutex::new(Vec::new()));
+
+        let handler = help_inference(crate::entry())
+            .branch(crate::filter(|x: i32| x > 0).endpoint(|x: i32| async move { x * 2 }))
+            .branch(crate::filter(|x: i32| x < 0).endpoint(|x: i32| async move { -x }))
+            .with_lifecycle(
+                {
+                    let log = Arc::clone(&log);
+                    move |input: &DependencyMap| {
+                        let x: Arc<i32> = input.get();
+                        log.lock().unwrap().push(format!("start {x}"));
+                    }
+                },
+                {
+                    let log = Arc::clone(&log);
+                    move |result: &ControlFlow<i32, DependencyMap>| {
+                        let result = match result {
+                            ControlFlow::Break(output) => output.to_string(),
+                            ControlFlow::Continue(_) => "continue".to_owned(),
+                        };
+                        log.lock().unwrap().push(format!("end {result}"));
+                    }
+                },
+            );
+
+        assert_eq!(handler.dispatch(deps![21]).await, ControlFlow::Break(42));
+        assert_eq!(handler.dispatch(deps![-5]).await, ControlFlow::Break(5));
+        assert_eq!(handler.dispatch(deps![0]).await, ControlFlow::Continue(deps![0]));
+
+        assert_eq!(
+            *log.lock().unwrap(),
+            ["start 21", "end 42", "start -5", "end 5", "start 0", "end continue"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_with_lifecycle_ignores_continuation() {
+        let ends = Arc::new(Mutex::new(0));
+
+        let handler = help_inference(crate::filter(|x: i32| x > 0))
+            .with_lifecycle(|_: &DependencyMap| {}, {
+                let ends = Arc::clone(&ends);
+                move |_: &ControlFlow<&'static str, DependencyMap>| *ends.lock().unwrap() += 1
+            })
+            .chain(crate::endpoint(|| async { "unreachable" }));
+
+        assert_eq!(handler.dispatch(deps![1]).await, ControlFlow::Continue(deps![1]));
+        assert_eq!(*ends.lock().unwrap(), 1);
+    }
+}
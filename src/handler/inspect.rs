@@ -1,9 +1,9 @@
 use crate::{
-    di::{Asyncify, Injectable},
+    di::{Asyncify, DependencyMap, Injectable, WithInput},
     from_fn_with_description, Handler, HandlerDescription,
 };
 
-use std::sync::Arc;
+use alloc::sync::Arc;
 
 /// Constructs a handler that inspects current state. Like [`map`] but does not
 /// add return value of `f` to the container.
@@ -18,7 +18,8 @@ where
     Output: 'a,
     Descr: HandlerDescription,
 {
-    inspect_with_description(Descr::inspect(), f)
+    let required = <Asyncify<F> as Injectable<Input, (), Args>>::dependencies();
+    inspect_with_description(Descr::inspect().with_dependencies(&required, &[]), f)
 }
 
 /// The asynchronous version of [`inspect`].
@@ -31,7 +32,64 @@ where
     Output: 'a,
     Descr: HandlerDescription,
 {
-    inspect_async_with_description(Descr::inspect_async(), f)
+    let required = <F as Injectable<Input, (), Args>>::dependencies();
+    inspect_async_with_description(Descr::inspect_async().with_dependencies(&required, &[]), f)
+}
+
+/// Constructs a handler that inspects current state along with the whole
+/// input.
+///
+/// Unlike [`inspect`], whose function receives only the injected values, `f`
+/// receives a reference to the input as its first parameter, followed by the
+/// injected values. This is useful for logging the full context of an event.
+///
+/// # Examples
+///
+/// ```
+/// # #[tokio::main]
+/// # async fn main() {
+/// use dptree::prelude::*;
+///
+/// let handler: Handler<_, ()> = dptree::inspect_ctx(|input: &DependencyMap, x: i32| {
+///     println!("Got {} in {:?}", x, input);
+/// });
+///
+/// assert!(matches!(handler.dispatch(dptree::deps![1]).await, ControlFlow::Continue(_)));
+/// # }
+/// ```
+#[must_use]
+#[track_caller]
+pub fn inspect_ctx<'a, F, Input, Output, Args, Descr>(f: F) -> Handler<'a, Input, Output, Descr>
+where
+    Asyncify<WithInput<F>>: Injectable<Input, (), Args> + Send + Sync + 'a,
+    Input: Send + 'a,
+    Output: 'a,
+    Descr: HandlerDescription,
+{
+    let required = <Asyncify<WithInput<F>> as Injectable<Input, (), Args>>::dependencies();
+    inspect_with_description(Descr::inspect().with_dependencies(&required, &[]), WithInput(f))
+}
+
+/// The asynchronous version of [`inspect_ctx`].
+///
+/// The returned future cannot borrow the input, so take everything you need
+/// from it before the `async` block.
+#[must_use]
+#[track_caller]
+pub fn inspect_ctx_async<'a, F, Input, Output, Args, Descr>(
+    f: F,
+) -> Handler<'a, Input, Output, Descr>
+where
+    WithInput<F>: Injectable<Input, (), Args> + Send + Sync + 'a,
+    Input: Send + 'a,
+    Output: 'a,
+    Descr: HandlerDescription,
+{
+    let required = <WithInput<F> as Injectable<Input, (), Args>>::dependencies();
+    inspect_async_with_description(
+        Descr::inspect_async().with_dependencies(&required, &[]),
+        WithInput(f),
+    )
 }
 
 /// [`inspect`] with a custom description.
@@ -74,15 +132,59 @@ where
     })
 }
 
+impl<'a, Output, Descr> Handler<'a, DependencyMap, Output, Descr>
+where
+    Output: 'a,
+    Descr: HandlerDescription,
+{
+    /// Chain this handler with `f` that receives the current container.
+    ///
+    /// The container passed to `f` contains the values inserted by the
+    /// handlers before this point, but not by the handlers after it. The
+    /// container is not modified, and the execution continues as usual. This
+    /// is useful for debugging what is available at a certain point of a tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use dptree::prelude::*;
+    ///
+    /// let handler: Handler<_, _> = dptree::entry()
+    ///     .map(|x: i32| x.to_string())
+    ///     .tap_deps(|deps| assert!(deps.contains::<String>()))
+    ///     .endpoint(|s: String| async move { s });
+    ///
+    /// assert_eq!(handler.dispatch(dptree::deps![1]).await, ControlFlow::Break("1".to_owned()));
+    /// # }
+    /// ```
+    #[must_use]
+    #[track_caller]
+    pub fn tap_deps<F>(self, f: F) -> Self
+    where
+        F: Fn(&DependencyMap) + Send + Sync + 'static,
+    {
+        self.chain(inspect_ctx(move |deps: &DependencyMap| f(deps)))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::{
         ops::ControlFlow,
-        sync::atomic::{AtomicBool, Ordering},
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Mutex,
+        },
     };
 
     use super::*;
-    use crate::{deps, help_inference};
+    use crate::{
+        deps,
+        di::{DependencyMap, DependencySupplier},
+        help_inference,
+    };
 
     #[tokio::test]
     async fn test_inspect() {
@@ -100,4 +202,49 @@ mod tests {
         assert!(matches!(result, ControlFlow::Continue(_)));
         assert!(inspect_passed.load(Ordering::Relaxed));
     }
+
+    #[tokio::test]
+    async fn test_inspect_ctx() {
+        let log = Arc::new(Mutex::new(Vec::new()));
+
+        let handler = help_inference(inspect_ctx({
+            let log = Arc::clone(&log);
+            move |input: &DependencyMap, x: i32| {
+                let has_string = input.contains::<&str>();
+                log.lock().unwrap().push(format!("{} {}", x, has_string));
+            }
+        }))
+        .chain(inspect_ctx_async({
+            let log = Arc::clone(&log);
+            move |input: &DependencyMap, x: i32| {
+                let s: Arc<&str> = input.get();
+                let log = Arc::clone(&log);
+                async move { log.lock().unwrap().push(format!("{} {}", x, s)) }
+            }
+        }))
+        .endpoint(|| async {});
+
+        assert_eq!(handler.dispatch(deps![1, "abc"]).await, ControlFlow::Break(()));
+        assert_eq!(*log.lock().unwrap(), vec!["1 true", "1 abc"]);
+    }
+
+    #[tokio::test]
+    async fn test_tap_deps() {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+
+        let handler = help_inference(crate::entry())
+            .map(|x: i32| x as u8)
+            .tap_deps({
+                let seen = Arc::clone(&seen);
+                move |deps| {
+                    seen.lock().unwrap().push((deps.contains::<u8>(), deps.contains::<bool>()))
+                }
+            })
+            .map(|| true)
+            .endpoint(|| async {});
+
+        assert_eq!(handler.dispatch(deps![1]).await, ControlFlow::Break(()));
+        // The value inserted upstream is visible, the one inserted downstream is not.
+        assert_eq!(*seen.lock().unwrap(), vec![(true, false)]);
+    }
 }
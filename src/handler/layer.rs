@@ -0,0 +1,167 @@
+use crate::{description, Handler, HandlerDescription};
+use alloc::{boxed::Box, vec::Vec};
+
+/// A middleware that wraps a handler into another handler.
+///
+/// A layer typically returns a handler that does something before and/or after
+/// executing the wrapped one, e.g., with [`Handler::around`]. Any function
+/// from a handler to a handler is a layer.
+///
+/// See [`Handler::layer`] and [`Handler::with_middleware_stack`].
+pub trait Layer<'a, Input, Output, Descr> {
+    /// Wraps `handler` into a new handler.
+    fn layer(
+        &self,
+        handler: Handler<'a, Input, Output, Descr>,
+    ) -> Handler<'a, Input, Output, Descr>;
+}
+
+impl<'a, F, Input, Output, Descr> Layer<'a, Input, Output, Descr> for F
+where
+    F: Fn(Handler<'a, Input, Output, Descr>) -> Handler<'a, Input, Output, Descr>,
+{
+    fn layer(
+        &self,
+        handler: Handler<'a, Input, Output, Descr>,
+    ) -> Handler<'a, Input, Output, Descr> {
+        self(handler)
+    }
+}
+
+/// A boxed [`Layer`], as accepted by [`Handler::with_middleware_stack`].
+pub type BoxLayer<'a, Input, Output, Descr = description::Unspecified> =
+    Box<dyn Layer<'a, Input, Output, Descr> + 'a>;
+
+impl<'a, Input, Output, Descr> Handler<'a, Input, Output, Descr>
+where
+    Input: Send + 'a,
+    Output: 'a,
+    Descr: HandlerDescription,
+{
+    /// Wraps this handler into `layer`.
+    ///
+    /// This is the same as `layer.layer(self)`, but reads better in method
+    /// chains.
+    #[must_use]
+    pub fn layer<L>(self, layer: L) -> Self
+    where
+        L: Layer<'a, Input, Output, Descr>,
+    {
+        layer.layer(self)
+    }
+
+    /// Wraps this handler into each of `layers`.
+    ///
+    /// The first layer is the outermost one: when the returned handler is
+    /// executed, the first layer runs first, then the second one, and so on up
+    /// to this handler, after which the layers finish in the reverse order.
+    /// Thus, `handler.with_middleware_stack(vec![a, b])` is the same as
+    /// `handler.layer(b).layer(a)`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use dptree::{prelude::*, BoxLayer};
+    /// use std::sync::{Arc, Mutex};
+    ///
+    /// let log = Arc::new(Mutex::new(Vec::new()));
+    /// let logging = |name: &'static str| -> BoxLayer<DependencyMap, ()> {
+    ///     let log = Arc::clone(&log);
+    ///     Box::new(move |handler: Handler<'static, DependencyMap, ()>| {
+    ///         let (before, after) = (Arc::clone(&log), Arc::clone(&log));
+    ///         handler.around(
+    ///             move |_: &DependencyMap| before.lock().unwrap().push(format!("enter {name}")),
+    ///             move |(), _: &ControlFlow<(), DependencyMap>| {
+    ///                 after.lock().unwrap().push(format!("exit {name}"))
+    ///             },
+    ///         )
+    ///     })
+    /// };
+    ///
+    /// let handler = dptree::endpoint(|| async {})
+    ///     .with_middleware_stack(vec![logging("auth"), logging("metrics")]);
+    /// let _ = handler.dispatch(dptree::deps![]).await;
+    ///
+    /// assert_eq!(*log.lock().unwrap(), ["enter auth", "enter metrics", "exit metrics", "exit auth"]);
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn with_middleware_stack(self, layers: Vec<BoxLayer<'a, Input, Output, Descr>>) -> Self {
+        layers.into_iter().rev().fold(self, |handler, layer| layer.layer(handler))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+    use crate::{deps, di::DependencyMap};
+    use core::ops::ControlFlow;
+
+    type Log = Arc<Mutex<Vec<&'static str>>>;
+
+    fn logging(
+        log: &Log,
+        enter: &'static str,
+        exit: &'static str,
+    ) -> impl Fn(Handler<'static, DependencyMap, i32>) -> Handler<'static, DependencyMap, i32> {
+        let log = Arc::clone(log);
+        move |handler: Handler<'static, DependencyMap, i32>| {
+            let (before, after) = (Arc::clone(&log), Arc::clone(&log));
+            handler.around(
+                move |_: &DependencyMap| before.lock().unwrap().push(enter),
+                move |(), _: &ControlFlow<i32, DependencyMap>| after.lock().unwrap().push(exit),
+            )
+        }
+    }
+
+    #[tokio::test]
+    async fn test_with_middleware_stack() {
+        let log = Log::default();
+
+        let handler = crate::endpoint({
+            let log = Arc::clone(&log);
+            move || {
+                log.lock().unwrap().push("handler");
+                async { 42 }
+            }
+        })
+        .with_middleware_stack(vec![
+            Box::new(logging(&log, "enter 1", "exit 1")),
+            Box::new(logging(&log, "enter 2", "exit 2")),
+            Box::new(logging(&log, "enter 3", "exit 3")),
+        ]);
+
+        assert_eq!(handler.dispatch(deps![]).await, ControlFlow::Break(42));
+        assert_eq!(
+            *log.lock().unwrap(),
+            ["enter 1", "enter 2", "enter 3", "handler", "exit 3", "exit 2", "exit 1"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_layer() {
+        let log = Log::default();
+
+        let handler = crate::endpoint(|| async { 42 })
+            .layer(logging(&log, "enter inner", "exit inner"))
+            .layer(logging(&log, "enter outer", "exit outer"));
+
+        assert_eq!(handler.dispatch(deps![]).await, ControlFlow::Break(42));
+        assert_eq!(
+            *log.lock().unwrap(),
+            ["enter outer", "enter inner", "exit inner", "exit outer"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_empty_middleware_stack() {
+        let handler: Handler<DependencyMap, i32> =
+            crate::endpoint(|| async { 42 }).with_middleware_stack(Vec::new());
+
+        assert_eq!(handler.dispatch(deps![]).await, ControlFlow::Break(42));
+    }
+}
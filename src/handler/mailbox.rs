@@ -0,0 +1,118 @@
+use crate::{Handler, HandlerDescription};
+use core::ops::ControlFlow;
+use futures::{Stream, StreamExt};
+
+/// Dispatches every input received from `mailbox` through `handler`.
+///
+/// Inputs are dispatched one by one, in the order they are received. If
+/// `handler` continues on an input, the input is passed to `on_continue`; you
+/// can either drop it by passing [`std::mem::drop`] or log it with a custom
+/// closure. Outputs produced by `handler` are discarded, so the handlers
+/// themselves should perform the effects you need (e.g., reply through a
+/// channel carried by the input).
+///
+/// The returned future completes when `mailbox` is exhausted. It is
+/// cancellation-safe in the sense that dropping it stops the loop, but an input
+/// being dispatched at that moment is dropped along with it. To stop the loop
+/// gracefully on an external signal, use [`StreamExt::take_until`] on your
+/// mailbox: the input being currently dispatched will be completed first.
+///
+/// # Examples
+///
+/// ```
+/// # #[tokio::main]
+/// # async fn main() {
+/// use dptree::prelude::*;
+/// use std::sync::{Arc, Mutex};
+///
+/// let log = Arc::new(Mutex::new(Vec::new()));
+///
+/// let handler: Handler<_, ()> = dptree::filter(|x: i32| x > 0).endpoint({
+///     let log = Arc::clone(&log);
+///     move |x: i32| {
+///         let log = Arc::clone(&log);
+///         async move { log.lock().unwrap().push(x) }
+///     }
+/// });
+///
+/// let mailbox = futures::stream::iter([1, -2, 3].map(|x| dptree::deps![x]));
+/// dptree::run_mailbox(handler, mailbox, std::mem::drop).await;
+///
+/// assert_eq!(*log.lock().unwrap(), vec![1, 3]);
+/// # }
+/// ```
+pub async fn run_mailbox<'a, Input, Output, Descr, Mailbox, OnContinue>(
+    handler: Handler<'a, Input, Output, Descr>,
+    mailbox: Mailbox,
+    mut on_continue: OnContinue,
+) where
+    Input: Send + 'a,
+    Output: 'a,
+    Descr: HandlerDescription,
+    Mailbox: Stream<Item = Input>,
+    OnContinue: FnMut(Input),
+{
+    futures::pin_mut!(mailbox);
+
+    while let Some(input) = mailbox.next().await {
+        match handler.dispatch(input).await {
+            ControlFlow::Continue(input) => on_continue(input),
+            ControlFlow::Break(_) => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{deps, di::DependencyMap, help_inference};
+    use futures::stream;
+    use tokio::sync::mpsc;
+
+    fn receiver_stream<T>(rx: mpsc::Receiver<T>) -> impl Stream<Item = T> {
+        stream::unfold(rx, |mut rx| async move { rx.recv().await.map(|x| (x, rx)) })
+    }
+
+    #[tokio::test]
+    async fn test_run_mailbox() {
+        let (input_tx, input_rx) = mpsc::channel(8);
+        let (output_tx, mut output_rx) = mpsc::unbounded_channel();
+
+        let handler = help_inference(crate::filter(|x: i32| x % 2 == 0)).endpoint(move |x: i32| {
+            let output_tx = output_tx.clone();
+            async move { output_tx.send(x * 10).unwrap() }
+        });
+
+        for x in 1..=4 {
+            input_tx.send(deps![x]).await.unwrap();
+        }
+        drop(input_tx);
+
+        let mut continued = Vec::new();
+        run_mailbox(handler, receiver_stream(input_rx), |input: DependencyMap| {
+            continued.push(input)
+        })
+        .await;
+
+        assert_eq!(continued.len(), 2);
+        assert_eq!(output_rx.recv().await, Some(20));
+        assert_eq!(output_rx.recv().await, Some(40));
+        assert_eq!(output_rx.recv().await, None);
+    }
+
+    #[tokio::test]
+    async fn test_run_mailbox_take_until() {
+        let (input_tx, input_rx) = mpsc::channel(8);
+        let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel::<()>();
+
+        let handler = help_inference(crate::endpoint(|| async {}));
+        let mailbox = receiver_stream(input_rx).take_until(shutdown_rx);
+
+        input_tx.send(deps![1]).await.unwrap();
+        shutdown_tx.send(()).unwrap();
+
+        // The loop stops even though the mailbox is still open.
+        run_mailbox(handler, mailbox, std::mem::drop).await;
+        drop(input_tx);
+    }
+}
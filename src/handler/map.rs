@@ -1,8 +1,9 @@
 use crate::{
-    di::{Asyncify, Injectable, Insert},
+    di::{Asyncify, DependencySupplier, Injectable, Insert},
     from_fn_with_description, Handler, HandlerDescription,
 };
-use std::{ops::ControlFlow, sync::Arc};
+use alloc::{sync::Arc, vec::Vec};
+use core::{future::Future, ops::ControlFlow};
 
 /// Constructs a handler that passes a value of a new type further.
 ///
@@ -23,10 +24,32 @@ where
     Descr: HandlerDescription,
     NewType: Send,
 {
-    map_with_description(Descr::map(), proj)
+    let required = <Asyncify<Projection> as Injectable<Input, NewType, Args>>::dependencies();
+    let provided = [core::any::type_name::<NewType>()];
+    map_with_description(Descr::map().with_dependencies(&required, &provided), proj)
 }
 
 /// The asynchronous version of [`map`].
+///
+/// Unlike [`crate::filter_map_async`], the projection cannot fail: whatever it
+/// returns is always added to the container.
+///
+/// # Examples
+///
+/// ```
+/// # #[tokio::main]
+/// # async fn main() {
+/// use dptree::prelude::*;
+///
+/// let handler: Handler<_, _> = dptree::map_async(|x: i32| async move { x.to_string() })
+///     .endpoint(|x: i32, s: String| async move { format!("{x} is {s:?}") });
+///
+/// assert_eq!(
+///     handler.dispatch(dptree::deps![42]).await,
+///     ControlFlow::Break("42 is \"42\"".to_owned())
+/// );
+/// # }
+/// ```
 #[must_use]
 #[track_caller]
 pub fn map_async<'a, Projection, Input, Output, NewType, Args, Descr>(
@@ -40,7 +63,223 @@ where
     Descr: HandlerDescription,
     NewType: Send,
 {
-    map_async_with_description(Descr::map_async(), proj)
+    let required = <Projection as Injectable<Input, NewType, Args>>::dependencies();
+    let provided = [core::any::type_name::<NewType>()];
+    map_async_with_description(Descr::map_async().with_dependencies(&required, &provided), proj)
+}
+
+/// Constructs a handler that combines two values added by the previous
+/// handlers into a value of a new type.
+///
+/// This is the same as [`map`] with a projection taking `A` and `B`, except
+/// that the values are passed as they are stored in the container, without
+/// cloning them, and that the handler is described as
+/// [`HandlerDescription::map_using`], so that it stands out in the handler
+/// tree as a node depending on the results of the previous handlers.
+///
+/// # Examples
+///
+/// ```
+/// # #[tokio::main]
+/// # async fn main() {
+/// use dptree::prelude::*;
+/// use std::sync::Arc;
+///
+/// struct User {
+///     name: String,
+/// }
+///
+/// struct Settings {
+///     greeting: String,
+/// }
+///
+/// let handler: Handler<_, _> = dptree::entry()
+///     .map(|id: u64| User { name: format!("user{id}") })
+///     .map(|| Settings { greeting: "Hello".to_owned() })
+///     .map_using(|user: Arc<User>, settings: Arc<Settings>| {
+///         format!("{}, {}!", settings.greeting, user.name)
+///     })
+///     .endpoint(|reply: String| async move { reply });
+///
+/// assert_eq!(
+///     handler.dispatch(dptree::deps![1u64]).await,
+///     ControlFlow::Break("Hello, user1!".to_owned())
+/// );
+/// # }
+/// ```
+#[must_use]
+#[track_caller]
+pub fn map_using<'a, A, B, F, Input, Output, NewType, Descr>(
+    f: F,
+) -> Handler<'a, Input, Output, Descr>
+where
+    F: Fn(Arc<A>, Arc<B>) -> NewType + Send + Sync + 'a,
+    Input: DependencySupplier<A> + DependencySupplier<B> + Insert<NewType> + Clone + Send + 'a,
+    Output: 'a,
+    Descr: HandlerDescription,
+    NewType: Send + 'a,
+{
+    let required = [core::any::type_name::<A>(), core::any::type_name::<B>()];
+    let provided = [core::any::type_name::<NewType>()];
+    let f = Arc::new(f);
+
+    from_fn_with_description(
+        Descr::map_using().with_dependencies(&required, &provided),
+        move |container: Input, cont| {
+            let res = f(container.get(), container.get());
+
+            async move {
+                let mut intermediate = container.clone();
+                intermediate.insert(res);
+                match cont(intermediate).await {
+                    ControlFlow::Continue(_) => ControlFlow::Continue(container),
+                    ControlFlow::Break(result) => ControlFlow::Break(result),
+                }
+            }
+        },
+    )
+}
+
+/// Constructs a handler that passes a value of a new type further, if there
+/// is one.
+///
+/// If `proj` returns `Some(v)`, then `v` is added to the container, as in
+/// [`map`]; if it returns `None`, the execution continues further in a
+/// handler chain with the container unchanged. That is, this handler is meant
+/// for optional enrichment and never gates the input: the next handlers
+/// should check for the value with
+/// [`DependencyMap::contains`](crate::di::DependencyMap::contains) rather than
+/// require it.
+///
+/// Unlike [`filter_map`](crate::filter_map), which returns
+/// [`ControlFlow::Continue`] on `None` and thus skips the rest of the chain,
+/// this handler is described by
+/// [`HandlerDescription::map_or_continue`], so that introspection does not
+/// mistake it for a filter.
+///
+/// # Examples
+///
+/// ```
+/// # #[tokio::main]
+/// # async fn main() {
+/// use dptree::prelude::*;
+///
+/// struct Nickname(String);
+///
+/// let handler: Handler<_, _> =
+///     dptree::map_or_continue(|id: u32| (id == 1).then(|| Nickname("admin".to_owned()))).chain(
+///         dptree::from_fn(|deps: DependencyMap, _cont| async move {
+///             ControlFlow::Break(deps.contains::<Nickname>())
+///         }),
+///     );
+///
+/// assert_eq!(handler.dispatch(dptree::deps![1u32]).await, ControlFlow::Break(true));
+/// assert_eq!(handler.dispatch(dptree::deps![2u32]).await, ControlFlow::Break(false));
+/// # }
+/// ```
+#[must_use]
+#[track_caller]
+pub fn map_or_continue<'a, Projection, Input, Output, NewType, Args, Descr>(
+    proj: Projection,
+) -> Handler<'a, Input, Output, Descr>
+where
+    Input: Clone,
+    Asyncify<Projection>: Injectable<Input, Option<NewType>, Args> + Send + Sync + 'a,
+    Input: Insert<NewType> + Send + 'a,
+    Output: 'a,
+    Descr: HandlerDescription,
+    NewType: Send,
+{
+    let required =
+        <Asyncify<Projection> as Injectable<Input, Option<NewType>, Args>>::dependencies();
+    let proj = Arc::new(Asyncify(proj));
+
+    // `NewType` may be absent, so it is not described as provided.
+    from_fn_with_description(
+        Descr::map_or_continue().with_dependencies(&required, &[]),
+        move |container: Input, cont| {
+            let proj = Arc::clone(&proj);
+
+            async move {
+                let proj = proj.inject(&container);
+                let res = proj().await;
+                core::mem::drop(proj);
+
+                // Without a value, the rest of the chain is executed with the
+                // container as is.
+                let value = match res {
+                    Some(value) => value,
+                    None => return cont(container).await,
+                };
+
+                let mut intermediate = container.clone();
+                intermediate.insert(value);
+                match cont(intermediate).await {
+                    ControlFlow::Continue(_) => ControlFlow::Continue(container),
+                    ControlFlow::Break(result) => ControlFlow::Break(result),
+                }
+            }
+        },
+    )
+}
+
+/// Constructs a handler that passes the outputs of several futures further.
+///
+/// `f` returns a list of futures, which are awaited concurrently. Their
+/// outputs are collected into a `Vec<T>`, in the order of the futures, and
+/// the vector is added to the container and passed further in a handler
+/// chain. This is useful for enriching an input from several sources at once.
+///
+/// # Examples
+///
+/// ```
+/// # #[tokio::main]
+/// # async fn main() {
+/// use dptree::{di::DependencySupplier, prelude::*};
+///
+/// let handler: Handler<_, _> = dptree::map_join(|deps: &DependencyMap| {
+///     let ids: std::sync::Arc<Vec<u32>> = deps.get();
+///     ids.iter().map(|&id| async move { format!("user{id}") }).collect()
+/// })
+/// .endpoint(|users: Vec<String>| async move { users.join(", ") });
+///
+/// assert_eq!(
+///     handler.dispatch(dptree::deps![vec![1u32, 2]]).await,
+///     ControlFlow::Break("user1, user2".to_owned())
+/// );
+/// # }
+/// ```
+#[must_use]
+#[track_caller]
+pub fn map_join<'a, F, Fut, Input, Output, T, Descr>(f: F) -> Handler<'a, Input, Output, Descr>
+where
+    F: Fn(&Input) -> Vec<Fut> + Send + Sync + 'a,
+    Fut: Future<Output = T> + Send + 'a,
+    Input: Insert<Vec<T>> + Clone + Send + 'a,
+    Output: 'a,
+    Descr: HandlerDescription,
+    T: Send + 'a,
+{
+    let provided = [core::any::type_name::<Vec<T>>()];
+    let f = Arc::new(f);
+
+    from_fn_with_description(
+        Descr::map_async().with_dependencies(&[], &provided),
+        move |container: Input, cont| {
+            let futures = f(&container);
+
+            async move {
+                let values = futures::future::join_all(futures).await;
+
+                let mut intermediate = container.clone();
+                intermediate.insert(values);
+                match cont(intermediate).await {
+                    ControlFlow::Continue(_) => ControlFlow::Continue(container),
+                    ControlFlow::Break(result) => ControlFlow::Break(result),
+                }
+            }
+        },
+    )
 }
 
 /// [`map`] with a custom description.
@@ -82,7 +321,7 @@ where
         async move {
             let proj = proj.inject(&container);
             let res = proj().await;
-            std::mem::drop(proj);
+            core::mem::drop(proj);
 
             let mut intermediate = container.clone();
             intermediate.insert(res);
@@ -113,4 +352,135 @@ mod tests {
 
         assert!(result == ControlFlow::Break(value));
     }
+
+    #[tokio::test]
+    async fn test_map_async() {
+        let result = help_inference(map_async(|x: i32| async move { x.to_string() }))
+            .map_async(|s: String| async move { s.len() })
+            .endpoint(|s: String, len: usize| async move { format!("{s}:{len}") })
+            .dispatch(deps![123])
+            .await;
+
+        assert!(result == ControlFlow::Break("123:3".to_owned()));
+    }
+
+    #[test]
+    fn test_map_async_description() {
+        use crate::description::{NodeKind, Tree};
+
+        let handler: Handler<crate::di::DependencyMap, (), Tree> =
+            map_async(|| async { 1 }).endpoint(|| async {});
+
+        let kinds: Vec<_> = handler.description().children.iter().map(|node| node.kind).collect();
+        assert_eq!(kinds, [NodeKind::MapAsync, NodeKind::Endpoint]);
+    }
+
+    #[tokio::test]
+    async fn test_map_using() {
+        let result = help_inference(map(|x: i32| x * 2))
+            .map(|s: &'static str| s.len())
+            .map_using(|x: Arc<i32>, len: Arc<usize>| format!("{x}/{len}"))
+            .endpoint(|s: String| async move { s })
+            .dispatch(deps![21, "abc"])
+            .await;
+
+        assert!(result == ControlFlow::Break("42/3".to_owned()));
+    }
+
+    #[tokio::test]
+    async fn test_map_or_continue() {
+        let handler =
+            help_inference(map_or_continue(|x: i32| if x > 0 { Some(x as u32) } else { None }))
+                .chain(crate::from_fn(|deps: crate::di::DependencyMap, _cont| async move {
+                    ControlFlow::Break(deps.contains::<u32>())
+                }));
+
+        // `Some` inserts the value.
+        assert_eq!(handler.dispatch(deps![1]).await, ControlFlow::Break(true));
+        // `None` continues down the chain without inserting anything.
+        assert_eq!(handler.dispatch(deps![-1]).await, ControlFlow::Break(false));
+    }
+
+    #[tokio::test]
+    async fn test_map_or_continue_continue() {
+        let handler = help_inference(map_or_continue(|| Some(1u8)))
+            .filter(|x: u8| x == 2)
+            .endpoint(|| async {});
+
+        // The inserted value is not returned.
+        assert!(
+            matches!(handler.dispatch(deps![0]).await, ControlFlow::Continue(deps) if !deps.contains::<u8>())
+        );
+    }
+
+    #[test]
+    fn test_map_or_continue_description() {
+        use crate::description::{NodeKind, Tree};
+
+        let handler: Handler<crate::di::DependencyMap, (), Tree> =
+            map_or_continue(|x: i32| Some(x as u8));
+
+        let node = handler.description();
+        assert_eq!(node.kind, NodeKind::MapOrContinue);
+        assert_eq!(node.required, ["i32"]);
+        assert!(node.provided.is_empty());
+        assert_eq!(node.kind.to_string(), "map_or_continue");
+    }
+
+    #[tokio::test]
+    async fn test_map_join() {
+        use core::sync::atomic::{AtomicUsize, Ordering};
+
+        let awaited = Arc::new(AtomicUsize::new(0));
+        let handler = help_inference(map_join({
+            let awaited = Arc::clone(&awaited);
+            move |_: &crate::di::DependencyMap| {
+                (1..=3)
+                    .map(|x: i32| {
+                        let awaited = Arc::clone(&awaited);
+                        async move {
+                            // Finish in the reverse order.
+                            for _ in x..3 {
+                                tokio::task::yield_now().await;
+                            }
+                            awaited.fetch_add(1, Ordering::Relaxed);
+                            x * 10
+                        }
+                    })
+                    .collect()
+            }
+        }))
+        .endpoint(|values: Vec<i32>| async move { values });
+
+        assert_eq!(handler.dispatch(deps![]).await, ControlFlow::Break(vec![10, 20, 30]));
+        assert_eq!(awaited.load(Ordering::Relaxed), 3);
+    }
+
+    #[tokio::test]
+    async fn test_map_join_continue() {
+        let handler = help_inference(map_join(|_: &crate::di::DependencyMap| {
+            Vec::<core::future::Ready<u8>>::new()
+        }))
+        .filter(|values: Vec<u8>| !values.is_empty())
+        .endpoint(|| async {});
+
+        // The input is returned without the vector.
+        assert!(matches!(
+            handler.dispatch(deps![1]).await,
+            ControlFlow::Continue(deps) if !deps.contains::<Vec<u8>>()
+        ));
+    }
+
+    #[test]
+    fn test_map_using_description() {
+        use crate::description::{NodeKind, Tree};
+
+        let handler: Handler<crate::di::DependencyMap, (), Tree> =
+            map_using(|x: Arc<i32>, y: Arc<u8>| *x + *y as i32).endpoint(|| async {});
+
+        let node = &handler.description().children[0];
+        assert_eq!(node.kind, NodeKind::MapUsing);
+        assert_eq!(node.required, ["i32", "u8"]);
+        assert_eq!(node.provided, ["i32"]);
+    }
 }
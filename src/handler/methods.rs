@@ -1,7 +1,10 @@
 use crate::{
-    di::{Asyncify, Injectable, Insert},
+    di::{Asyncify, Injectable, Insert, InsertArc, InsertMany, WithInput},
     Handler, HandlerDescription,
 };
+use alloc::vec::Vec;
+use core::{convert::TryFrom, future::Future};
+use futures::future::BoxFuture;
 
 impl<'a, Input, Output, Descr> Handler<'a, Input, Output, Descr>
 where
@@ -29,31 +32,590 @@ where
         self.chain(crate::filter_async(pred))
     }
 
+    /// Chain this handler with the validation predicate `pred` that breaks
+    /// with `err(&input)` on failure.
+    #[must_use]
+    #[track_caller]
+    pub fn validate<Pred, Err, FnArgs>(
+        self,
+        pred: Pred,
+        err: Err,
+    ) -> Handler<'a, Input, Output, Descr>
+    where
+        Asyncify<Pred>: Injectable<Input, bool, FnArgs> + Send + Sync + 'a,
+        Err: Fn(&Input) -> Output + Send + Sync + 'a,
+    {
+        self.chain(crate::validate(pred, err))
+    }
+
+    /// Chain this handler with the async validation predicate `pred` that
+    /// breaks with `err(&input)` on failure.
+    #[must_use]
+    #[track_caller]
+    pub fn validate_async<Pred, Err, FnArgs>(
+        self,
+        pred: Pred,
+        err: Err,
+    ) -> Handler<'a, Input, Output, Descr>
+    where
+        Pred: Injectable<Input, bool, FnArgs> + Send + Sync + 'a,
+        Err: Fn(&Input) -> Output + Send + Sync + 'a,
+    {
+        self.chain(crate::validate_async(pred, err))
+    }
+
+    /// Chain this handler with the async predicate `pred` that breaks with the
+    /// output of `reject(&input)` on failure.
+    #[must_use]
+    #[track_caller]
+    pub fn guard_async<Pred, Reject, FnArgs>(
+        self,
+        pred: Pred,
+        reject: Reject,
+    ) -> Handler<'a, Input, Output, Descr>
+    where
+        Input: Sync,
+        Pred: Injectable<Input, bool, FnArgs> + Send + Sync + 'a,
+        Reject: for<'i> Fn(&'i Input) -> BoxFuture<'i, Output> + Send + Sync + 'a,
+    {
+        self.chain(crate::guard_async(pred, reject))
+    }
+
+    /// Chain this handler with the filter passing the input if any of `preds`
+    /// returns `true`.
+    #[must_use]
+    #[track_caller]
+    pub fn filter_any<Pred>(self, preds: Vec<Pred>) -> Handler<'a, Input, Output, Descr>
+    where
+        Pred: Fn(&Input) -> bool + Send + Sync + 'a,
+    {
+        self.chain(crate::filter_any(preds))
+    }
+
+    /// Chain this handler with the filter passing the input if all of `preds`
+    /// return `true`.
+    #[must_use]
+    #[track_caller]
+    pub fn filter_all<Pred>(self, preds: Vec<Pred>) -> Handler<'a, Input, Output, Descr>
+    where
+        Pred: Fn(&Input) -> bool + Send + Sync + 'a,
+    {
+        self.chain(crate::filter_all(preds))
+    }
+
+    /// Chain this handler with the stateful filter predicate `pred`.
+    #[cfg(feature = "std")]
+    #[must_use]
+    #[track_caller]
+    pub fn filter_stateful<State, Pred>(
+        self,
+        initial: State,
+        pred: Pred,
+    ) -> Handler<'a, Input, Output, Descr>
+    where
+        State: Send + 'a,
+        Pred: Fn(&mut State, &Input) -> bool + Send + Sync + 'a,
+    {
+        self.chain(crate::filter_stateful(initial, pred))
+    }
+
+    /// Chain this handler with the stateful async projection `f`.
+    #[cfg(feature = "std")]
+    #[must_use]
+    #[track_caller]
+    pub fn filter_map_stateful<State, F, Fut, NewType>(
+        self,
+        initial: State,
+        f: F,
+    ) -> Handler<'a, Input, Output, Descr>
+    where
+        State: Send + 'a,
+        F: Fn(std::sync::Arc<std::sync::Mutex<State>>, &Input) -> Fut + Send + Sync + 'a,
+        Fut: core::future::Future<Output = Option<NewType>> + Send + 'a,
+        Input: Insert<NewType> + Clone,
+        NewType: Send,
+    {
+        self.chain(crate::filter_map_stateful(initial, f))
+    }
+
+    /// Chain this handler with a filter letting through only the first input
+    /// per key within `window`.
+    #[cfg(feature = "std")]
+    #[must_use]
+    #[track_caller]
+    pub fn debounce<KeyFn, Key, C, Args>(
+        self,
+        key_fn: KeyFn,
+        window: std::time::Duration,
+        clock: C,
+    ) -> Handler<'a, Input, Output, Descr>
+    where
+        Asyncify<KeyFn>: Injectable<Input, Key, Args> + Send + Sync + 'a,
+        Key: core::hash::Hash + Eq + Send + 'a,
+        C: crate::Clock + 'a,
+    {
+        self.chain(crate::debounce(key_fn, window, clock))
+    }
+
+    /// Chain this handler with a token-bucket filter letting inputs through at
+    /// a limited rate.
+    #[cfg(feature = "std")]
+    #[must_use]
+    #[track_caller]
+    pub fn rate_limit<C>(
+        self,
+        capacity: u32,
+        refill_per_sec: f64,
+        clock: C,
+    ) -> Handler<'a, Input, Output, Descr>
+    where
+        C: crate::Clock + 'a,
+    {
+        self.chain(crate::rate_limit(capacity, refill_per_sec, clock))
+    }
+
     /// Chain this handler with the filter projection `proj`.
     #[must_use]
     #[track_caller]
-    pub fn filter_map<Proj, NewType, Args>(self, proj: Proj) -> Handler<'a, Input, Output, Descr>
+    pub fn filter_map<Proj, NewType, Args>(self, proj: Proj) -> Handler<'a, Input, Output, Descr>
+    where
+        Input: Insert<NewType> + Clone,
+        Asyncify<Proj>: Injectable<Input, Option<NewType>, Args> + Send + Sync + 'a,
+        NewType: Send,
+    {
+        self.chain(crate::filter_map(proj))
+    }
+
+    /// Chain this handler with the async filter projection `proj`.
+    #[must_use]
+    #[track_caller]
+    pub fn filter_map_async<Proj, NewType, Args>(
+        self,
+        proj: Proj,
+    ) -> Handler<'a, Input, Output, Descr>
+    where
+        Input: Insert<NewType> + Clone,
+        Proj: Injectable<Input, Option<NewType>, Args> + Send + Sync + 'a,
+        NewType: Send,
+    {
+        self.chain(crate::filter_map_async(proj))
+    }
+
+    /// Chain this handler with the async projection `f` that borrows the
+    /// input.
+    #[must_use]
+    #[track_caller]
+    pub fn filter_map_async_ref<F, NewType>(self, f: F) -> Handler<'a, Input, Output, Descr>
+    where
+        F: for<'i> Fn(&'i Input) -> BoxFuture<'i, Option<NewType>> + Send + Sync + 'a,
+        Input: Insert<NewType> + Sync,
+        NewType: Send,
+    {
+        self.chain(crate::filter_map_async_ref(f))
+    }
+
+    /// Chain this handler with the async projection `f` that borrows the input
+    /// and produces a shared value.
+    #[must_use]
+    #[track_caller]
+    pub fn filter_map_arc<F, NewType>(self, f: F) -> Handler<'a, Input, Output, Descr>
+    where
+        F: for<'i> Fn(&'i Input) -> BoxFuture<'i, Option<alloc::sync::Arc<NewType>>>
+            + Send
+            + Sync
+            + 'a,
+        Input: InsertArc<NewType> + Clone + Sync,
+        NewType: Send + Sync,
+    {
+        self.chain(crate::filter_map_arc(f))
+    }
+
+    /// Chain this handler with the projection `proj` whose results are cached
+    /// by `key`.
+    #[cfg(feature = "std")]
+    #[must_use]
+    #[track_caller]
+    pub fn filter_map_cached<KeyFn, Proj, Key, NewType, Args>(
+        self,
+        key: KeyFn,
+        proj: Proj,
+        cache: crate::Cache<Key, NewType>,
+    ) -> Handler<'a, Input, Output, Descr>
+    where
+        KeyFn: Fn(&Input) -> Key + Send + Sync + 'a,
+        Asyncify<Proj>: Injectable<Input, Option<NewType>, Args> + Send + Sync + 'a,
+        Key: std::hash::Hash + Eq + Send + 'a,
+        Input: Insert<NewType> + Clone,
+        NewType: Clone + Send + 'a,
+    {
+        self.chain(crate::filter_map_cached(key, proj, cache))
+    }
+
+    /// Chain this handler with the async projection `proj` whose results are
+    /// cached by `key`.
+    #[cfg(feature = "std")]
+    #[must_use]
+    #[track_caller]
+    pub fn filter_map_cached_async<KeyFn, Proj, Key, NewType, Args>(
+        self,
+        key: KeyFn,
+        proj: Proj,
+        cache: crate::Cache<Key, NewType>,
+    ) -> Handler<'a, Input, Output, Descr>
+    where
+        KeyFn: Fn(&Input) -> Key + Send + Sync + 'a,
+        Proj: Injectable<Input, Option<NewType>, Args> + Send + Sync + 'a,
+        Key: std::hash::Hash + Eq + Send + 'a,
+        Input: Insert<NewType> + Clone,
+        NewType: Clone + Send + 'a,
+    {
+        self.chain(crate::filter_map_cached_async(key, proj, cache))
+    }
+
+    /// Chain this handler with the projection `proj` whose results are cached
+    /// by `key` for `ttl`.
+    #[cfg(feature = "std")]
+    #[must_use]
+    #[track_caller]
+    pub fn filter_map_cached_ttl<KeyFn, Proj, Key, C, NewType, Args>(
+        self,
+        key: KeyFn,
+        proj: Proj,
+        ttl: std::time::Duration,
+        clock: C,
+        cache: crate::Cache<Key, NewType>,
+    ) -> Handler<'a, Input, Output, Descr>
+    where
+        KeyFn: Fn(&Input) -> Key + Send + Sync + 'a,
+        Asyncify<Proj>: Injectable<Input, Option<NewType>, Args> + Send + Sync + 'a,
+        Key: std::hash::Hash + Eq + Send + 'a,
+        C: crate::Clock + 'static,
+        Input: Insert<NewType> + Clone,
+        NewType: Clone + Send + 'a,
+    {
+        self.chain(crate::filter_map_cached_ttl(key, proj, ttl, clock, cache))
+    }
+
+    /// Chain this handler with the async projection `proj` whose results are
+    /// cached by `key` for `ttl`.
+    #[cfg(feature = "std")]
+    #[must_use]
+    #[track_caller]
+    pub fn filter_map_cached_ttl_async<KeyFn, Proj, Key, C, NewType, Args>(
+        self,
+        key: KeyFn,
+        proj: Proj,
+        ttl: std::time::Duration,
+        clock: C,
+        cache: crate::Cache<Key, NewType>,
+    ) -> Handler<'a, Input, Output, Descr>
+    where
+        KeyFn: Fn(&Input) -> Key + Send + Sync + 'a,
+        Proj: Injectable<Input, Option<NewType>, Args> + Send + Sync + 'a,
+        Key: std::hash::Hash + Eq + Send + 'a,
+        C: crate::Clock + 'static,
+        Input: Insert<NewType> + Clone,
+        NewType: Clone + Send + 'a,
+    {
+        self.chain(crate::filter_map_cached_ttl_async(key, proj, ttl, clock, cache))
+    }
+
+    /// Chain this handler with the projection `proj` that is called on the
+    /// first execution only.
+    #[cfg(feature = "std")]
+    #[must_use]
+    #[track_caller]
+    pub fn filter_map_once<Proj, NewType, Args>(
+        self,
+        proj: Proj,
+    ) -> Handler<'a, Input, Output, Descr>
+    where
+        Asyncify<Proj>: Injectable<Input, Option<NewType>, Args> + Send + Sync + 'a,
+        Input: Insert<NewType> + Clone,
+        NewType: Clone + Send + 'a,
+    {
+        self.chain(crate::filter_map_once(proj))
+    }
+
+    /// Chain this handler with the async projection `proj` that is called on
+    /// the first execution only.
+    #[cfg(feature = "std")]
+    #[must_use]
+    #[track_caller]
+    pub fn filter_map_once_async<Proj, NewType, Args>(
+        self,
+        proj: Proj,
+    ) -> Handler<'a, Input, Output, Descr>
+    where
+        Proj: Injectable<Input, Option<NewType>, Args> + Send + Sync + 'a,
+        Input: Insert<NewType> + Clone,
+        NewType: Clone + Send + 'a,
+    {
+        self.chain(crate::filter_map_once_async(proj))
+    }
+
+    /// Chain this handler with the projection `proj` that falls back to
+    /// `default`.
+    #[must_use]
+    #[track_caller]
+    pub fn filter_map_or_default<Proj, NewType, Args>(
+        self,
+        proj: Proj,
+        default: NewType,
+    ) -> Handler<'a, Input, Output, Descr>
+    where
+        Input: Insert<NewType> + Clone,
+        Asyncify<Proj>: Injectable<Input, Option<NewType>, Args> + Send + Sync + 'a,
+        NewType: Clone + Send + Sync + 'a,
+    {
+        self.chain(crate::filter_map_or_default(proj, default))
+    }
+
+    /// Chain this handler with the async projection `proj` that falls back to
+    /// `default`.
+    #[must_use]
+    #[track_caller]
+    pub fn filter_map_or_default_async<Proj, NewType, Args>(
+        self,
+        proj: Proj,
+        default: NewType,
+    ) -> Handler<'a, Input, Output, Descr>
+    where
+        Input: Insert<NewType> + Clone,
+        Proj: Injectable<Input, Option<NewType>, Args> + Send + Sync + 'a,
+        NewType: Clone + Send + Sync + 'a,
+    {
+        self.chain(crate::filter_map_or_default_async(proj, default))
+    }
+
+    /// Chain this handler with the filter projection `proj` providing several
+    /// values.
+    #[must_use]
+    #[track_caller]
+    pub fn filter_map_many<Proj, Values, Args>(
+        self,
+        proj: Proj,
+    ) -> Handler<'a, Input, Output, Descr>
+    where
+        Input: InsertMany<Values> + Clone,
+        Asyncify<Proj>: Injectable<Input, Option<Values>, Args> + Send + Sync + 'a,
+        Values: Send,
+    {
+        self.chain(crate::filter_map_many(proj))
+    }
+
+    /// Chain this handler with the async filter projection `proj` providing
+    /// several values.
+    #[must_use]
+    #[track_caller]
+    pub fn filter_map_many_async<Proj, Values, Args>(
+        self,
+        proj: Proj,
+    ) -> Handler<'a, Input, Output, Descr>
+    where
+        Input: InsertMany<Values> + Clone,
+        Proj: Injectable<Input, Option<Values>, Args> + Send + Sync + 'a,
+        Values: Send,
+    {
+        self.chain(crate::filter_map_many_async(proj))
+    }
+
+    /// Chain this handler with the projection `proj`, dispatching
+    /// `else_branch` when it returns `None`.
+    #[must_use]
+    #[track_caller]
+    pub fn filter_map_or_else<Proj, NewType, Args>(
+        self,
+        proj: Proj,
+        else_branch: Handler<'a, Input, Output, Descr>,
+    ) -> Handler<'a, Input, Output, Descr>
+    where
+        Input: Insert<NewType> + Clone,
+        Asyncify<Proj>: Injectable<Input, Option<NewType>, Args> + Send + Sync + 'a,
+        NewType: Send,
+    {
+        self.chain(crate::filter_map_or_else(proj, else_branch))
+    }
+
+    /// Chain this handler with the async projection `proj`, dispatching
+    /// `else_branch` when it returns `None`.
+    #[must_use]
+    #[track_caller]
+    pub fn filter_map_or_else_async<Proj, NewType, Args>(
+        self,
+        proj: Proj,
+        else_branch: Handler<'a, Input, Output, Descr>,
+    ) -> Handler<'a, Input, Output, Descr>
+    where
+        Input: Insert<NewType> + Clone,
+        Proj: Injectable<Input, Option<NewType>, Args> + Send + Sync + 'a,
+        NewType: Send,
+    {
+        self.chain(crate::filter_map_or_else_async(proj, else_branch))
+    }
+
+    /// Chain this handler with the [`TryFrom`] conversion of `Source` into
+    /// `T`.
+    #[must_use]
+    #[track_caller]
+    pub fn try_into_filter<Source, T>(self) -> Handler<'a, Input, Output, Descr>
+    where
+        Source: 'static,
+        T: TryFrom<Source> + Send + 'static,
+        Asyncify<fn(Source) -> Option<T>>: Injectable<Input, Option<T>, (Source,)>,
+        Input: Insert<T> + Clone,
+    {
+        self.chain(crate::try_into_filter::<Source, T, Input, Output, Descr>())
+    }
+
+    /// Chain this handler with the fallible projection `proj` that passes
+    /// either a value or an error further.
+    #[must_use]
+    #[track_caller]
+    pub fn filter_map_result_insert<Proj, T, E, Args>(
+        self,
+        proj: Proj,
+    ) -> Handler<'a, Input, Output, Descr>
+    where
+        Input: Insert<T> + Insert<E> + Clone,
+        Asyncify<Proj>: Injectable<Input, Result<T, E>, Args> + Send + Sync + 'a,
+        T: Send,
+        E: Send,
+    {
+        self.chain(crate::filter_map_result_insert(proj))
+    }
+
+    /// Chain this handler with the async fallible projection `proj` that
+    /// passes either a value or an error further.
+    #[must_use]
+    #[track_caller]
+    pub fn filter_map_result_insert_async<Proj, T, E, Args>(
+        self,
+        proj: Proj,
+    ) -> Handler<'a, Input, Output, Descr>
+    where
+        Input: Insert<T> + Insert<E> + Clone,
+        Proj: Injectable<Input, Result<T, E>, Args> + Send + Sync + 'a,
+        T: Send,
+        E: Send,
+    {
+        self.chain(crate::filter_map_result_insert_async(proj))
+    }
+
+    /// Chain this handler with the fallible projection `proj` that recovers
+    /// from errors with `recover`.
+    #[must_use]
+    #[track_caller]
+    pub fn filter_map_result_recover<Proj, Recover, Fut, T, E, Args>(
+        self,
+        proj: Proj,
+        recover: Recover,
+    ) -> Handler<'a, Input, Output, Descr>
+    where
+        Input: Insert<T> + Clone,
+        Asyncify<Proj>: Injectable<Input, Result<T, E>, Args> + Send + Sync + 'a,
+        Recover: Fn(E) -> Fut + Send + Sync + 'a,
+        Fut: Future<Output = Option<Output>> + Send + 'a,
+        T: Send,
+        E: Send,
+    {
+        self.chain(crate::filter_map_result_recover(proj, recover))
+    }
+
+    /// Chain this handler with the async fallible projection `proj` that
+    /// recovers from errors with `recover`.
+    #[must_use]
+    #[track_caller]
+    pub fn filter_map_result_recover_async<Proj, Recover, Fut, T, E, Args>(
+        self,
+        proj: Proj,
+        recover: Recover,
+    ) -> Handler<'a, Input, Output, Descr>
+    where
+        Input: Insert<T> + Clone,
+        Proj: Injectable<Input, Result<T, E>, Args> + Send + Sync + 'a,
+        Recover: Fn(E) -> Fut + Send + Sync + 'a,
+        Fut: Future<Output = Option<Output>> + Send + 'a,
+        T: Send,
+        E: Send,
+    {
+        self.chain(crate::filter_map_result_recover_async(proj, recover))
+    }
+
+    /// Chain this handler with the fallible projection `proj` that reports
+    /// errors to `log`.
+    #[must_use]
+    #[track_caller]
+    pub fn filter_map_logged<Proj, Log, T, Reason, Args>(
+        self,
+        proj: Proj,
+        log: Log,
+    ) -> Handler<'a, Input, Output, Descr>
+    where
+        Input: Insert<T> + Clone,
+        Asyncify<Proj>: Injectable<Input, Result<T, Reason>, Args> + Send + Sync + 'a,
+        Log: Fn(Reason) + Send + Sync + 'a,
+        T: Send + 'static,
+        Reason: Send + 'static,
+    {
+        self.chain(crate::filter_map_logged(proj, log))
+    }
+
+    /// Chain this handler with the async fallible projection `proj` that
+    /// reports errors to `log`.
+    #[must_use]
+    #[track_caller]
+    pub fn filter_map_logged_async<Proj, Log, T, Reason, Args>(
+        self,
+        proj: Proj,
+        log: Log,
+    ) -> Handler<'a, Input, Output, Descr>
     where
-        Input: Insert<NewType> + Clone,
-        Asyncify<Proj>: Injectable<Input, Option<NewType>, Args> + Send + Sync + 'a,
-        NewType: Send,
+        Input: Insert<T> + Clone,
+        Proj: Injectable<Input, Result<T, Reason>, Args> + Send + Sync + 'a,
+        Log: Fn(Reason) + Send + Sync + 'a,
+        T: Send + 'static,
+        Reason: Send + 'static,
     {
-        self.chain(crate::filter_map(proj))
+        self.chain(crate::filter_map_logged_async(proj, log))
     }
 
-    /// Chain this handler with the async filter projection `proj`.
+    /// Chain this handler with the fallible projection `proj` that collects
+    /// errors into `sink`.
+    #[cfg(feature = "std")]
     #[must_use]
     #[track_caller]
-    pub fn filter_map_async<Proj, NewType, Args>(
+    pub fn filter_map_collecting_errors<Proj, T, E, Args>(
         self,
         proj: Proj,
+        sink: crate::ErrorSink<E>,
     ) -> Handler<'a, Input, Output, Descr>
     where
-        Input: Insert<NewType> + Clone,
-        Proj: Injectable<Input, Option<NewType>, Args> + Send + Sync + 'a,
-        NewType: Send,
+        Input: Insert<T> + Clone,
+        Asyncify<Proj>: Injectable<Input, Result<T, E>, Args> + Send + Sync + 'a,
+        T: Send,
+        E: Send + 'a,
     {
-        self.chain(crate::filter_map_async(proj))
+        self.chain(crate::filter_map_collecting_errors(proj, sink))
+    }
+
+    /// Chain this handler with the async fallible projection `proj` that
+    /// collects errors into `sink`.
+    #[cfg(feature = "std")]
+    #[must_use]
+    #[track_caller]
+    pub fn filter_map_collecting_errors_async<Proj, T, E, Args>(
+        self,
+        proj: Proj,
+        sink: crate::ErrorSink<E>,
+    ) -> Handler<'a, Input, Output, Descr>
+    where
+        Input: Insert<T> + Clone,
+        Proj: Injectable<Input, Result<T, E>, Args> + Send + Sync + 'a,
+        T: Send,
+        E: Send + 'a,
+    {
+        self.chain(crate::filter_map_collecting_errors_async(proj, sink))
     }
 
     /// Chain this handler with the map projection `proj`.
@@ -80,6 +642,104 @@ where
         self.chain(crate::map_async(proj))
     }
 
+    /// Chain this handler with the map combining the previously added values
+    /// of `A` and `B` with `f`.
+    #[must_use]
+    #[track_caller]
+    pub fn map_using<A, B, F, NewType>(self, f: F) -> Handler<'a, Input, Output, Descr>
+    where
+        F: Fn(alloc::sync::Arc<A>, alloc::sync::Arc<B>) -> NewType + Send + Sync + 'a,
+        Input: crate::di::DependencySupplier<A>
+            + crate::di::DependencySupplier<B>
+            + Insert<NewType>
+            + Clone,
+        NewType: Send + 'a,
+    {
+        self.chain(crate::map_using(f))
+    }
+
+    /// Chain this handler with the optional map projection `proj`.
+    #[must_use]
+    #[track_caller]
+    pub fn map_or_continue<Proj, NewType, Args>(
+        self,
+        proj: Proj,
+    ) -> Handler<'a, Input, Output, Descr>
+    where
+        Input: Insert<NewType> + Clone,
+        Asyncify<Proj>: Injectable<Input, Option<NewType>, Args> + Send + Sync + 'a,
+        NewType: Send,
+    {
+        self.chain(crate::map_or_continue(proj))
+    }
+
+    /// Chain this handler with the map awaiting all futures returned by `f`.
+    #[must_use]
+    #[track_caller]
+    pub fn map_join<F, Fut, T>(self, f: F) -> Handler<'a, Input, Output, Descr>
+    where
+        F: Fn(&Input) -> Vec<Fut> + Send + Sync + 'a,
+        Fut: Future<Output = T> + Send + 'a,
+        Input: Insert<Vec<T>> + Clone,
+        T: Send + 'a,
+    {
+        self.chain(crate::map_join(f))
+    }
+
+    /// Chain this handler with the lazily created value from `factory`.
+    #[cfg(feature = "std")]
+    #[must_use]
+    #[track_caller]
+    pub fn provide_async<Factory, Fut, NewType>(
+        self,
+        factory: Factory,
+    ) -> Handler<'a, Input, Output, Descr>
+    where
+        Input: Insert<NewType> + Clone,
+        Factory: Fn() -> Fut + Send + Sync + 'a,
+        Fut: core::future::Future<Output = NewType> + Send + 'a,
+        NewType: Clone + Send + Sync + 'a,
+    {
+        self.chain(crate::provide_async(factory))
+    }
+
+    /// Chain this handler with the async filter-map function `proj`, which
+    /// gives up after `duration`.
+    #[cfg(feature = "tokio")]
+    #[must_use]
+    #[track_caller]
+    pub fn filter_map_timeout<Proj, NewType, Args>(
+        self,
+        proj: Proj,
+        duration: std::time::Duration,
+    ) -> Handler<'a, Input, Output, Descr>
+    where
+        Input: Insert<NewType> + Clone,
+        Proj: Injectable<Input, Option<NewType>, Args> + Send + Sync + 'a,
+        NewType: Send + 'static,
+    {
+        self.chain(crate::filter_map_timeout(proj, duration))
+    }
+
+    /// Chain this handler with the async filter projection `proj`, which falls
+    /// back to `default` after `duration`.
+    #[cfg(feature = "tokio")]
+    #[must_use]
+    #[track_caller]
+    pub fn filter_map_timeout_default<Proj, NewType, Args>(
+        self,
+        proj: Proj,
+        duration: std::time::Duration,
+        default: NewType,
+    ) -> Handler<'a, Input, Output, Descr>
+    where
+        Input: Insert<NewType> + Clone,
+        Proj: Injectable<Input, Option<NewType>, Args> + Send + Sync + 'a,
+        NewType: Clone + Send + Sync + 'static,
+    {
+        self.chain(crate::filter_map_timeout_default(proj, duration, default))
+    }
+
     /// Chain this handler with the inspection function `f`.
     #[must_use]
     #[track_caller]
@@ -100,6 +760,28 @@ where
         self.chain(crate::inspect_async(f))
     }
 
+    /// Chain this handler with the inspection function `f` that also receives
+    /// the input.
+    #[must_use]
+    #[track_caller]
+    pub fn inspect_ctx<F, Args>(self, f: F) -> Handler<'a, Input, Output, Descr>
+    where
+        Asyncify<WithInput<F>>: Injectable<Input, (), Args> + Send + Sync + 'a,
+    {
+        self.chain(crate::inspect_ctx(f))
+    }
+
+    /// Chain this handler with the async inspection function `f` that also
+    /// receives the input.
+    #[must_use]
+    #[track_caller]
+    pub fn inspect_ctx_async<F, Args>(self, f: F) -> Handler<'a, Input, Output, Descr>
+    where
+        WithInput<F>: Injectable<Input, (), Args> + Send + Sync + 'a,
+    {
+        self.chain(crate::inspect_ctx_async(f))
+    }
+
     /// Chain this handler with the endpoint handler `f`.
     #[must_use]
     #[track_caller]
@@ -109,13 +791,25 @@ where
     {
         self.chain(crate::endpoint(f))
     }
+
+    /// Chain this handler with the endpoint handler `f` that borrows the
+    /// input.
+    #[must_use]
+    #[track_caller]
+    pub fn endpoint_ref<F>(self, f: F) -> Handler<'a, Input, Output, Descr>
+    where
+        F: for<'i> Fn(&'i Input) -> BoxFuture<'i, Output> + Send + Sync + 'a,
+        Input: Sync,
+    {
+        self.chain(crate::endpoint_ref(f))
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use std::ops::ControlFlow;
+    use std::{ops::ControlFlow, sync::Arc};
 
-    use crate::{deps, help_inference};
+    use crate::{deps, di::DependencyMap, help_inference};
 
     // Test that these methods just do compile.
     #[tokio::test]
@@ -130,6 +824,55 @@ mod tests {
             .dispatch(deps![value])
             .await;
 
+        let _: ControlFlow<(), _> = help_inference(crate::entry())
+            .validate(|| true, |_: &DependencyMap| ())
+            .dispatch(deps![value])
+            .await;
+
+        let _: ControlFlow<(), _> = help_inference(crate::entry())
+            .validate_async(|| async { true }, |_: &DependencyMap| ())
+            .dispatch(deps![value])
+            .await;
+
+        let _: ControlFlow<(), _> = help_inference(crate::entry())
+            .guard_async(|| async { true }, |_: &DependencyMap| Box::pin(async {}))
+            .dispatch(deps![value])
+            .await;
+
+        let _: ControlFlow<(), _> = help_inference(crate::entry())
+            .filter_any(vec![|_: &DependencyMap| true])
+            .dispatch(deps![value])
+            .await;
+
+        let _: ControlFlow<(), _> = help_inference(crate::entry())
+            .filter_all(vec![|_: &DependencyMap| true])
+            .dispatch(deps![value])
+            .await;
+
+        #[cfg(feature = "std")]
+        let _: ControlFlow<(), _> = help_inference(crate::entry())
+            .filter_stateful(0, |_: &mut i32, _: &_| true)
+            .dispatch(deps![value])
+            .await;
+
+        #[cfg(feature = "std")]
+        let _: ControlFlow<(), _> = help_inference(crate::entry())
+            .filter_map_stateful(0, |_, _: &_| async { Some("abc") })
+            .dispatch(deps![value])
+            .await;
+
+        #[cfg(feature = "std")]
+        let _: ControlFlow<(), _> = help_inference(crate::entry())
+            .debounce(|x: i32| x, std::time::Duration::from_secs(1), crate::SystemClock)
+            .dispatch(deps![value])
+            .await;
+
+        #[cfg(feature = "std")]
+        let _: ControlFlow<(), _> = help_inference(crate::entry())
+            .rate_limit(1, 1.0, crate::SystemClock)
+            .dispatch(deps![value])
+            .await;
+
         let _: ControlFlow<(), _> =
             help_inference(crate::entry()).filter_map(|| Some("abc")).dispatch(deps![value]).await;
 
@@ -138,6 +881,149 @@ mod tests {
             .dispatch(deps![value])
             .await;
 
+        let _: ControlFlow<(), _> = help_inference(crate::entry())
+            .filter_map_async_ref(|_: &DependencyMap| Box::pin(async { Some("abc") }))
+            .dispatch(deps![value])
+            .await;
+
+        let _: ControlFlow<(), _> = help_inference(crate::entry())
+            .filter_map_arc(|_: &DependencyMap| Box::pin(async { Some(Arc::new("abc")) }))
+            .dispatch(deps![value])
+            .await;
+
+        #[cfg(feature = "std")]
+        let _: ControlFlow<(), _> = help_inference(crate::entry())
+            .filter_map_cached(|_: &DependencyMap| (), || Some("abc"), crate::Cache::new())
+            .dispatch(deps![value])
+            .await;
+
+        #[cfg(feature = "std")]
+        let _: ControlFlow<(), _> = help_inference(crate::entry())
+            .filter_map_cached_async(
+                |_: &DependencyMap| (),
+                || async { Some("abc") },
+                crate::Cache::new(),
+            )
+            .dispatch(deps![value])
+            .await;
+
+        #[cfg(feature = "std")]
+        let _: ControlFlow<(), _> = help_inference(crate::entry())
+            .filter_map_cached_ttl(
+                |_: &DependencyMap| (),
+                || Some("abc"),
+                std::time::Duration::from_secs(1),
+                crate::SystemClock,
+                crate::Cache::new(),
+            )
+            .dispatch(deps![value])
+            .await;
+
+        #[cfg(feature = "std")]
+        let _: ControlFlow<(), _> = help_inference(crate::entry())
+            .filter_map_cached_ttl_async(
+                |_: &DependencyMap| (),
+                || async { Some("abc") },
+                std::time::Duration::from_secs(1),
+                crate::SystemClock,
+                crate::Cache::new(),
+            )
+            .dispatch(deps![value])
+            .await;
+
+        #[cfg(feature = "std")]
+        let _: ControlFlow<(), _> = help_inference(crate::entry())
+            .filter_map_once(|| Some("abc"))
+            .dispatch(deps![value])
+            .await;
+
+        #[cfg(feature = "std")]
+        let _: ControlFlow<(), _> = help_inference(crate::entry())
+            .filter_map_once_async(|| async { Some("abc") })
+            .dispatch(deps![value])
+            .await;
+
+        let _: ControlFlow<(), _> = help_inference(crate::entry())
+            .try_into_filter::<i32, u8>()
+            .dispatch(deps![value])
+            .await;
+
+        let _: ControlFlow<(), _> = help_inference(crate::entry())
+            .filter_map_or_default(|| Some("abc"), "def")
+            .dispatch(deps![value])
+            .await;
+
+        let _: ControlFlow<(), _> = help_inference(crate::entry())
+            .filter_map_or_default_async(|| async { Some("abc") }, "def")
+            .dispatch(deps![value])
+            .await;
+
+        let _: ControlFlow<(), _> = help_inference(crate::entry())
+            .filter_map_many(|| Some(("abc", 1u8)))
+            .dispatch(deps![value])
+            .await;
+
+        let _: ControlFlow<(), _> = help_inference(crate::entry())
+            .filter_map_many_async(|| async { Some(("abc", 1u8)) })
+            .dispatch(deps![value])
+            .await;
+
+        let _: ControlFlow<(), _> = help_inference(crate::entry())
+            .filter_map_or_else(|| Some("abc"), crate::endpoint(|| async {}))
+            .dispatch(deps![value])
+            .await;
+
+        let _: ControlFlow<(), _> = help_inference(crate::entry())
+            .filter_map_or_else_async(|| async { Some("abc") }, crate::endpoint(|| async {}))
+            .dispatch(deps![value])
+            .await;
+
+        let _: ControlFlow<(), _> = help_inference(crate::entry())
+            .filter_map_result_insert(|| Ok::<_, &str>(1u8))
+            .dispatch(deps![value])
+            .await;
+
+        let _: ControlFlow<(), _> = help_inference(crate::entry())
+            .filter_map_result_insert_async(|| async { Err::<u8, _>("abc") })
+            .dispatch(deps![value])
+            .await;
+
+        let _: ControlFlow<(), _> = help_inference(crate::entry())
+            .filter_map_result_recover(|| Ok::<_, &str>(1u8), |_| async { Some(()) })
+            .dispatch(deps![value])
+            .await;
+
+        let _: ControlFlow<(), _> = help_inference(crate::entry())
+            .filter_map_result_recover_async(|| async { Err::<u8, _>("abc") }, |_| async { None })
+            .dispatch(deps![value])
+            .await;
+
+        let _: ControlFlow<(), _> = help_inference(crate::entry())
+            .filter_map_logged(|| Ok::<_, &str>(1u8), |_| {})
+            .dispatch(deps![value])
+            .await;
+
+        let _: ControlFlow<(), _> = help_inference(crate::entry())
+            .filter_map_logged_async(|| async { Err::<u8, _>("abc") }, |_| {})
+            .dispatch(deps![value])
+            .await;
+
+        #[cfg(feature = "std")]
+        {
+            let _: ControlFlow<(), _> = help_inference(crate::entry())
+                .filter_map_collecting_errors(|| Ok::<_, &str>(1u8), crate::ErrorSink::new())
+                .dispatch(deps![value])
+                .await;
+
+            let _: ControlFlow<(), _> = help_inference(crate::entry())
+                .filter_map_collecting_errors_async(
+                    || async { Err::<u8, _>("abc") },
+                    crate::ErrorSink::new(),
+                )
+                .dispatch(deps![value])
+                .await;
+        }
+
         let _: ControlFlow<(), _> =
             help_inference(crate::entry()).map(|| "abc").dispatch(deps![value]).await;
 
@@ -146,13 +1032,61 @@ mod tests {
             .dispatch(deps![value])
             .await;
 
+        let _: ControlFlow<(), _> = help_inference(crate::entry())
+            .map_using(|x: Arc<i32>, y: Arc<i32>| *x + *y)
+            .dispatch(deps![value])
+            .await;
+
+        let _: ControlFlow<(), _> = help_inference(crate::entry())
+            .map_join(|_: &DependencyMap| vec![async { 1 }])
+            .dispatch(deps![value])
+            .await;
+
+        let _: ControlFlow<(), _> = help_inference(crate::entry())
+            .map_or_continue(|| Some("abc"))
+            .dispatch(deps![value])
+            .await;
+
+        #[cfg(feature = "std")]
+        let _: ControlFlow<(), _> = help_inference(crate::entry())
+            .provide_async(|| async { "abc" })
+            .dispatch(deps![value])
+            .await;
+
+        #[cfg(feature = "tokio")]
+        let _: ControlFlow<(), _> = help_inference(crate::entry())
+            .filter_map_timeout(|| async { Some(1) }, std::time::Duration::from_secs(1))
+            .dispatch(deps![value])
+            .await;
+
+        #[cfg(feature = "tokio")]
+        let _: ControlFlow<(), _> = help_inference(crate::entry())
+            .filter_map_timeout_default(|| async { Some(1) }, std::time::Duration::from_secs(1), 0)
+            .dispatch(deps![value])
+            .await;
+
         let _: ControlFlow<(), _> =
             help_inference(crate::entry()).inspect(|| {}).dispatch(deps![value]).await;
 
         let _: ControlFlow<(), _> =
             help_inference(crate::entry()).inspect_async(|| async {}).dispatch(deps![value]).await;
 
+        let _: ControlFlow<(), _> = help_inference(crate::entry())
+            .inspect_ctx(|_: &DependencyMap| {})
+            .dispatch(deps![value])
+            .await;
+
+        let _: ControlFlow<(), _> = help_inference(crate::entry())
+            .inspect_ctx_async(|_: &DependencyMap| async {})
+            .dispatch(deps![value])
+            .await;
+
         let _: ControlFlow<(), _> =
             help_inference(crate::entry()).endpoint(|| async {}).dispatch(deps![value]).await;
+
+        let _: ControlFlow<(), _> = help_inference(crate::entry())
+            .endpoint_ref(|_: &DependencyMap| Box::pin(async {}))
+            .dispatch(deps![value])
+            .await;
     }
 }
@@ -0,0 +1,168 @@
+use crate::{from_fn_with_description, Handler, HandlerDescription};
+use alloc::sync::Arc;
+use core::{
+    ops::ControlFlow,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+impl<'a, Input, Output, Descr> Handler<'a, Input, Output, Descr>
+where
+    Input: Send + 'a,
+    Output: 'a,
+    Descr: HandlerDescription,
+{
+    /// Returns a handler that executes this one at most once.
+    ///
+    /// The first time the execution reaches the returned handler, this handler
+    /// is executed as usual. After that, the returned handler always returns
+    /// [`ControlFlow::Continue`] without executing this handler, even if the
+    /// first execution has not completed yet. This holds for all clones of the
+    /// returned handler.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use dptree::prelude::*;
+    ///
+    /// let handler: Handler<_, _> = dptree::endpoint(|| async { "initialized" }).only_once();
+    ///
+    /// assert_eq!(handler.dispatch(dptree::deps![]).await, ControlFlow::Break("initialized"));
+    /// assert_eq!(handler.dispatch(dptree::deps![]).await, ControlFlow::Continue(dptree::deps![]));
+    /// # }
+    /// ```
+    #[must_use]
+    #[track_caller]
+    pub fn only_once(self) -> Self {
+        let executed = Arc::new(AtomicBool::new(false));
+        let description = self.wrapper_description();
+
+        from_fn_with_description(description, move |event, cont| {
+            let this = self.clone();
+            let executed = Arc::clone(&executed);
+
+            async move {
+                if executed.swap(true, Ordering::AcqRel) {
+                    ControlFlow::Continue(event)
+                } else {
+                    this.execute(event, cont).await
+                }
+            }
+        })
+    }
+
+    /// Returns a handler that stops executing this one after it breaks.
+    ///
+    /// Until this handler returns [`ControlFlow::Break`], the returned handler
+    /// executes it as usual. After that, the returned handler always returns
+    /// [`ControlFlow::Continue`] without executing this handler. In contrast to
+    /// [`Handler::only_once`], executions that continue do not spend the
+    /// handler. This holds for all clones of the returned handler.
+    ///
+    /// Executions that have already started when this handler breaks for the
+    /// first time are completed as usual, so several of them can break if they
+    /// run concurrently.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use dptree::prelude::*;
+    ///
+    /// let handler: Handler<_, _> =
+    ///     dptree::filter(|x: i32| x > 0).endpoint(|x: i32| async move { x }).fuse();
+    ///
+    /// assert_eq!(handler.dispatch(dptree::deps![-1]).await, ControlFlow::Continue(dptree::deps![-1]));
+    /// assert_eq!(handler.dispatch(dptree::deps![1]).await, ControlFlow::Break(1));
+    /// assert_eq!(handler.dispatch(dptree::deps![2]).await, ControlFlow::Continue(dptree::deps![2]));
+    /// # }
+    /// ```
+    #[must_use]
+    #[track_caller]
+    pub fn fuse(self) -> Self {
+        let blown = Arc::new(AtomicBool::new(false));
+        let description = self.wrapper_description();
+
+        from_fn_with_description(description, move |event, cont| {
+            let this = self.clone();
+            let blown = Arc::clone(&blown);
+
+            async move {
+                if blown.load(Ordering::Acquire) {
+                    return ControlFlow::Continue(event);
+                }
+
+                let result = this.execute(event, cont).await;
+                if result.is_break() {
+                    blown.store(true, Ordering::Release);
+                }
+
+                result
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicUsize;
+
+    use super::*;
+    use crate::{deps, help_inference};
+
+    #[tokio::test]
+    async fn test_only_once() {
+        let counter = Arc::new(AtomicUsize::new(0));
+
+        let handler = help_inference(crate::inspect({
+            let counter = Arc::clone(&counter);
+            move || {
+                counter.fetch_add(1, Ordering::Relaxed);
+            }
+        }))
+        .only_once()
+        .chain(crate::endpoint(|| async { "done" }));
+
+        assert_eq!(handler.dispatch(deps![]).await, ControlFlow::Break("done"));
+        assert_eq!(handler.dispatch(deps![]).await, ControlFlow::Continue(deps![]));
+        assert_eq!(handler.clone().dispatch(deps![]).await, ControlFlow::Continue(deps![]));
+        assert_eq!(counter.load(Ordering::Relaxed), 1);
+    }
+
+    #[tokio::test]
+    async fn test_only_once_counts_continues() {
+        let handler = help_inference(crate::filter(|x: i32| x > 0))
+            .endpoint(|| async { "positive" })
+            .only_once();
+
+        // The first execution continues, but it is an execution nonetheless.
+        assert_eq!(handler.dispatch(deps![-1]).await, ControlFlow::Continue(deps![-1]));
+        assert_eq!(handler.dispatch(deps![1]).await, ControlFlow::Continue(deps![1]));
+    }
+
+    #[tokio::test]
+    async fn test_fuse() {
+        let handler = help_inference(crate::filter(|x: i32| x % 2 == 0))
+            .endpoint(|x: i32| async move { x })
+            .fuse();
+
+        assert_eq!(handler.dispatch(deps![1]).await, ControlFlow::Continue(deps![1]));
+        assert_eq!(handler.dispatch(deps![3]).await, ControlFlow::Continue(deps![3]));
+        assert_eq!(handler.dispatch(deps![2]).await, ControlFlow::Break(2));
+        assert_eq!(handler.dispatch(deps![4]).await, ControlFlow::Continue(deps![4]));
+        assert_eq!(handler.clone().dispatch(deps![6]).await, ControlFlow::Continue(deps![6]));
+    }
+
+    #[tokio::test]
+    async fn test_fuse_in_branch() {
+        let handler = help_inference(crate::entry())
+            .branch(crate::endpoint(|| async { "first" }).fuse())
+            .branch(crate::endpoint(|| async { "rest" }));
+
+        assert_eq!(handler.dispatch(deps![]).await, ControlFlow::Break("first"));
+        assert_eq!(handler.dispatch(deps![]).await, ControlFlow::Break("rest"));
+        assert_eq!(handler.dispatch(deps![]).await, ControlFlow::Break("rest"));
+    }
+}
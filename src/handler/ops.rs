@@ -0,0 +1,88 @@
+use crate::{Handler, HandlerDescription};
+use core::ops::{BitOr, Shr};
+
+/// `a >> b` is the same as [`a.chain(b)`](Handler::chain).
+///
+/// Note that `>>` has a higher precedence than `|`, so `a >> b | c` means
+/// `a.chain(b).branch(c)`.
+///
+/// # Examples
+///
+/// ```
+/// # #[tokio::main]
+/// # async fn main() {
+/// use dptree::prelude::*;
+///
+/// let with_methods: Handler<_, _> = dptree::entry()
+///     .branch(dptree::filter(|x: i32| x > 0).chain(dptree::endpoint(|| async { "positive" })))
+///     .branch(dptree::endpoint(|| async { "other" }));
+///
+/// let with_operators: Handler<_, _> = dptree::entry()
+///     | dptree::filter(|x: i32| x > 0) >> dptree::endpoint(|| async { "positive" })
+///     | dptree::endpoint(|| async { "other" });
+///
+/// for x in [-1, 0, 1] {
+///     assert_eq!(
+///         with_methods.dispatch(dptree::deps![x]).await,
+///         with_operators.dispatch(dptree::deps![x]).await
+///     );
+/// }
+/// # }
+/// ```
+impl<'a, Input, Output, Descr> Shr for Handler<'a, Input, Output, Descr>
+where
+    Input: Send + 'a,
+    Output: 'a,
+    Descr: HandlerDescription,
+{
+    type Output = Self;
+
+    #[track_caller]
+    fn shr(self, next: Self) -> Self {
+        self.chain(next)
+    }
+}
+
+/// `a | b` is the same as [`a.branch(b)`](Handler::branch).
+///
+/// See the [`Shr`] implementation for an example.
+impl<'a, Input, Output, Descr> BitOr for Handler<'a, Input, Output, Descr>
+where
+    Input: Send + 'a,
+    Output: Send + 'a,
+    Descr: HandlerDescription,
+{
+    type Output = Self;
+
+    #[track_caller]
+    fn bitor(self, next: Self) -> Self {
+        self.branch(next)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ops::ControlFlow;
+
+    use crate::{deps, description::Tree, di::DependencyMap, Handler};
+
+    fn positive() -> Handler<'static, DependencyMap, &'static str, Tree> {
+        crate::filter(|x: i32| x > 0)
+    }
+
+    fn endpoint(output: &'static str) -> Handler<'static, DependencyMap, &'static str, Tree> {
+        crate::endpoint(move || async move { output })
+    }
+
+    #[tokio::test]
+    async fn test_operators() {
+        let with_methods =
+            crate::entry().branch(positive().chain(endpoint("positive"))).branch(endpoint("other"));
+        let with_operators =
+            crate::entry() | positive() >> endpoint("positive") | endpoint("other");
+
+        assert_eq!(with_methods.description(), with_operators.description());
+        assert_eq!(with_operators.dispatch(deps![1]).await, ControlFlow::Break("positive"));
+        assert_eq!(with_operators.dispatch(deps![0]).await, ControlFlow::Break("other"));
+    }
+}
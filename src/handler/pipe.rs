@@ -0,0 +1,238 @@
+use crate::{from_fn_with_description, Handler, HandlerDescription};
+use core::{
+    any::{type_name, TypeId},
+    fmt::{self, Display, Formatter},
+    ops::ControlFlow,
+};
+
+/// The input and output types of a handler.
+///
+/// See [`Handler::stage_types`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct StageTypes {
+    /// The [`TypeId`] of the input type.
+    pub input: TypeId,
+
+    /// The name of the input type.
+    pub input_name: &'static str,
+
+    /// The [`TypeId`] of the output type.
+    pub output: TypeId,
+
+    /// The name of the output type.
+    pub output_name: &'static str,
+}
+
+impl StageTypes {
+    /// Checks that the output of this stage can be the input of `next`, as in
+    /// [`Handler::pipe`].
+    pub fn check_pipe(&self, next: &StageTypes) -> Result<(), PipeMismatch> {
+        if self.output == next.input {
+            Ok(())
+        } else {
+            Err(PipeMismatch { produced: self.output_name, consumed: next.input_name })
+        }
+    }
+}
+
+/// An error returned by [`StageTypes::check_pipe`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PipeMismatch {
+    /// The name of the output type of the producing stage.
+    pub produced: &'static str,
+
+    /// The name of the input type of the consuming stage.
+    pub consumed: &'static str,
+}
+
+impl Display for PipeMismatch {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "a stage producing `{}` cannot be piped into a stage consuming `{}`",
+            self.produced, self.consumed
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for PipeMismatch {}
+
+impl<'a, Input, Output, Descr> Handler<'a, Input, Output, Descr>
+where
+    Input: Send + 'a,
+    Output: Send + 'a,
+    Descr: HandlerDescription,
+{
+    /// Returns a handler that feeds the output of this handler into `next`.
+    ///
+    /// The returned handler dispatches this handler with its input. If this
+    /// handler breaks with an output, `next` is dispatched with this output as
+    /// its input, and the returned handler breaks with the output of `next`.
+    /// If either of the two handlers continues, the returned handler returns
+    /// [`ControlFlow::Continue`] with the original input, so that the next
+    /// branches can try to handle it.
+    ///
+    /// Since the two handlers form a complete pipeline, the returned handler
+    /// is [terminal](crate::Handler#terminal-handlers).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use dptree::prelude::*;
+    ///
+    /// let parse: Handler<_, DependencyMap> = dptree::endpoint(|s: &'static str| async move {
+    ///     let n: i32 = s.parse().unwrap_or_default();
+    ///     dptree::deps![n]
+    /// });
+    /// let render: Handler<_, String> =
+    ///     dptree::filter(|n: i32| n > 0).endpoint(|n: i32| async move { format!("got {n}") });
+    ///
+    /// let handler = parse.pipe(render);
+    ///
+    /// assert_eq!(
+    ///     handler.dispatch(dptree::deps!["42"]).await,
+    ///     ControlFlow::Break("got 42".to_owned())
+    /// );
+    /// assert!(matches!(handler.dispatch(dptree::deps!["-1"]).await, ControlFlow::Continue(_)));
+    /// # }
+    /// ```
+    #[must_use]
+    #[track_caller]
+    pub fn pipe<Output2>(
+        self,
+        next: Handler<'a, Output, Output2, Descr>,
+    ) -> Handler<'a, Input, Output2, Descr>
+    where
+        Input: Clone,
+        Output2: 'a,
+    {
+        let description = self.description().merge_chain(next.description());
+
+        from_fn_with_description(description, move |event: Input, _cont| {
+            let this = self.clone();
+            let next = next.clone();
+
+            async move {
+                match this.dispatch(event.clone()).await {
+                    ControlFlow::Break(output) => match next.dispatch(output).await {
+                        ControlFlow::Break(result) => ControlFlow::Break(result),
+                        ControlFlow::Continue(_) => ControlFlow::Continue(event),
+                    },
+                    ControlFlow::Continue(_) => ControlFlow::Continue(event),
+                }
+            }
+        })
+    }
+}
+
+impl<'a, Input, Output, Descr> Handler<'a, Input, Output, Descr>
+where
+    Input: 'static,
+    Output: 'static,
+{
+    /// Returns the input and output types of this handler.
+    ///
+    /// The types of handlers composed by [`Handler::pipe`] are checked at
+    /// compile time; this method is meant for tooling that composes stages
+    /// dynamically, e.g., from a configuration, and validates them with
+    /// [`StageTypes::check_pipe`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dptree::prelude::*;
+    ///
+    /// let parse: Handler<DependencyMap, DependencyMap> =
+    ///     dptree::endpoint(|| async { dptree::deps![] });
+    /// let render: Handler<DependencyMap, String> = dptree::endpoint(|| async { String::new() });
+    ///
+    /// assert_eq!(parse.stage_types().check_pipe(&render.stage_types()), Ok(()));
+    ///
+    /// let error = render.stage_types().check_pipe(&render.stage_types()).unwrap_err();
+    /// assert_eq!(error.produced, "alloc::string::String");
+    /// ```
+    #[must_use]
+    pub fn stage_types(&self) -> StageTypes {
+        StageTypes {
+            input: TypeId::of::<Input>(),
+            input_name: type_name::<Input>(),
+            output: TypeId::of::<Output>(),
+            output_name: type_name::<Output>(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{deps, di::DependencyMap};
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    enum Command {
+        Start,
+        Help,
+    }
+
+    fn parse() -> Handler<'static, DependencyMap, DependencyMap> {
+        crate::entry()
+            .branch(
+                crate::filter(|s: &'static str| s == "/start")
+                    .endpoint(|| async { deps![Command::Start] }),
+            )
+            .branch(
+                crate::filter(|s: &'static str| s == "/help")
+                    .endpoint(|| async { deps![Command::Help] }),
+            )
+    }
+
+    fn render() -> Handler<'static, DependencyMap, &'static str> {
+        crate::filter(|cmd: Command| cmd == Command::Start).endpoint(|| async { "Welcome!" })
+    }
+
+    #[tokio::test]
+    async fn test_pipe() {
+        let handler = parse().pipe(render());
+
+        assert_eq!(handler.dispatch(deps!["/start"]).await, ControlFlow::Break("Welcome!"));
+    }
+
+    #[tokio::test]
+    async fn test_pipe_continue() {
+        let handler = crate::entry()
+            .branch(parse().pipe(render()))
+            .branch(crate::endpoint(|| async { "Unknown command" }));
+
+        // `parse` continues.
+        assert_eq!(handler.dispatch(deps!["/stop"]).await, ControlFlow::Break("Unknown command"));
+        // `render` continues.
+        assert_eq!(handler.dispatch(deps!["/help"]).await, ControlFlow::Break("Unknown command"));
+    }
+
+    #[test]
+    fn test_stage_types() {
+        let types = parse().stage_types();
+        assert_eq!(types.input, TypeId::of::<DependencyMap>());
+        assert_eq!(types.output, TypeId::of::<DependencyMap>());
+
+        let types = render().stage_types();
+        assert_eq!(types.input, TypeId::of::<DependencyMap>());
+        assert_eq!(types.output, TypeId::of::<&'static str>());
+        assert_eq!(types.output_name, "&str");
+    }
+
+    #[test]
+    fn test_check_pipe() {
+        assert_eq!(parse().stage_types().check_pipe(&render().stage_types()), Ok(()));
+
+        let error = render().stage_types().check_pipe(&parse().stage_types()).unwrap_err();
+        assert_eq!(error, PipeMismatch { produced: "&str", consumed: "dptree::di::DependencyMap" });
+        assert_eq!(
+            error.to_string(),
+            "a stage producing `&str` cannot be piped into a stage consuming \
+             `dptree::di::DependencyMap`"
+        );
+    }
+}
@@ -0,0 +1,108 @@
+use crate::{di::Insert, from_fn_with_description, Handler, HandlerDescription};
+use futures::{
+    future::{BoxFuture, Shared},
+    FutureExt,
+};
+use std::{
+    future::Future,
+    ops::ControlFlow,
+    sync::{Arc, Mutex, PoisonError},
+};
+
+/// Constructs a handler that provides a lazily created value to the next
+/// handlers.
+///
+/// The first time the handler is executed, it calls `factory` and awaits the
+/// returned future; the resulting value is then cached and reused by all
+/// subsequent executions of the handler and its clones. If several executions
+/// happen before the value is ready, they all await the same future. The value
+/// is added to the container and passed further in a handler chain.
+///
+/// This is useful for dependencies that are expensive to create, such as
+/// database connection pools.
+///
+/// # Examples
+///
+/// ```
+/// # #[tokio::main]
+/// # async fn main() {
+/// use dptree::prelude::*;
+///
+/// #[derive(Clone)]
+/// struct Pool {
+///     size: usize,
+/// }
+///
+/// let handler: Handler<_, _> = dptree::provide_async(|| async { Pool { size: 8 } })
+///     .endpoint(|pool: Pool| async move { pool.size });
+///
+/// assert_eq!(handler.dispatch(dptree::deps![]).await, ControlFlow::Break(8));
+/// # }
+/// ```
+#[must_use]
+#[track_caller]
+pub fn provide_async<'a, Factory, Fut, Input, Output, NewType, Descr>(
+    factory: Factory,
+) -> Handler<'a, Input, Output, Descr>
+where
+    Factory: Fn() -> Fut + Send + Sync + 'a,
+    Fut: Future<Output = NewType> + Send + 'a,
+    Input: Insert<NewType> + Clone + Send + 'a,
+    Output: 'a,
+    NewType: Clone + Send + Sync + 'a,
+    Descr: HandlerDescription,
+{
+    let cell: Arc<Mutex<Option<Shared<BoxFuture<'a, NewType>>>>> = Arc::new(Mutex::new(None));
+    let description =
+        Descr::map_async().with_dependencies(&[], &[core::any::type_name::<NewType>()]);
+
+    from_fn_with_description(description, move |container: Input, cont| {
+        let value = cell
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get_or_insert_with(|| factory().boxed().shared())
+            .clone();
+
+        async move {
+            let mut intermediate = container.clone();
+            intermediate.insert(value.await);
+            match cont(intermediate).await {
+                ControlFlow::Continue(_) => ControlFlow::Continue(container),
+                ControlFlow::Break(result) => ControlFlow::Break(result),
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+    use crate::{deps, help_inference};
+
+    #[tokio::test]
+    async fn test_provide_async() {
+        let created = Arc::new(AtomicUsize::new(0));
+
+        let handler = help_inference(provide_async({
+            let created = Arc::clone(&created);
+            move || {
+                let created = Arc::clone(&created);
+                async move {
+                    tokio::task::yield_now().await;
+                    created.fetch_add(1, Ordering::Relaxed) + 100
+                }
+            }
+        }))
+        .branch(crate::filter(|x: i32| x == 1).endpoint(|pool: usize| async move { pool + 1 }))
+        .branch(crate::endpoint(|pool: usize| async move { pool + 2 }));
+
+        let (a, b) = tokio::join!(handler.dispatch(deps![1]), handler.dispatch(deps![2]));
+        assert_eq!(a, ControlFlow::Break(101));
+        assert_eq!(b, ControlFlow::Break(102));
+        assert_eq!(handler.dispatch(deps![1]).await, ControlFlow::Break(101));
+
+        assert_eq!(created.load(Ordering::Relaxed), 1);
+    }
+}
@@ -0,0 +1,149 @@
+use crate::{from_fn_with_description, Clock, Handler, HandlerDescription};
+use std::{
+    ops::ControlFlow,
+    sync::{Arc, Mutex, PoisonError},
+    time::Instant,
+};
+
+/// Constructs a handler that lets inputs through at a limited rate, using a
+/// token bucket.
+///
+/// The bucket holds up to `capacity` tokens and is initially full. Each input
+/// consumes a token, after which a continuation of the handler will be called.
+/// If the bucket is empty, the input is skipped and the handler returns
+/// [`ControlFlow::Continue`], so the next branches can handle it. The bucket is
+/// refilled continuously at `refill_per_sec` tokens per second, as measured by
+/// `clock`, so up to `capacity` inputs can pass in a burst.
+///
+/// The bucket is shared by all clones of the handler and is safe to use from
+/// concurrent dispatches.
+///
+/// # Panics
+///
+/// Panics if `refill_per_sec` is negative or not finite.
+///
+/// # Examples
+///
+/// ```
+/// # #[tokio::main]
+/// # async fn main() {
+/// use dptree::{prelude::*, ManualClock};
+/// use std::time::Duration;
+///
+/// let clock = ManualClock::new();
+/// let handler: Handler<_, _> = dptree::entry()
+///     .branch(dptree::rate_limit(2, 1.0, clock.clone()).endpoint(|| async { "handled" }))
+///     .branch(dptree::endpoint(|| async { "too many requests" }));
+///
+/// assert_eq!(handler.dispatch(dptree::deps![]).await, ControlFlow::Break("handled"));
+/// assert_eq!(handler.dispatch(dptree::deps![]).await, ControlFlow::Break("handled"));
+/// assert_eq!(handler.dispatch(dptree::deps![]).await, ControlFlow::Break("too many requests"));
+///
+/// clock.advance(Duration::from_secs(1));
+/// assert_eq!(handler.dispatch(dptree::deps![]).await, ControlFlow::Break("handled"));
+/// # }
+/// ```
+#[must_use]
+#[track_caller]
+pub fn rate_limit<'a, C, Input, Output, Descr>(
+    capacity: u32,
+    refill_per_sec: f64,
+    clock: C,
+) -> Handler<'a, Input, Output, Descr>
+where
+    C: Clock + 'a,
+    Input: Send + 'a,
+    Output: 'a,
+    Descr: HandlerDescription,
+{
+    assert!(
+        refill_per_sec.is_finite() && refill_per_sec >= 0.0,
+        "the refill rate must be finite and non-negative"
+    );
+
+    let capacity = f64::from(capacity);
+    let bucket = Arc::new(Mutex::new(Bucket { tokens: capacity, refilled_at: clock.now() }));
+    let clock = Arc::new(clock);
+
+    from_fn_with_description(Descr::filter(), move |event, cont| {
+        let cond = {
+            let now = clock.now();
+            let mut bucket = bucket.lock().unwrap_or_else(PoisonError::into_inner);
+            let elapsed = now.saturating_duration_since(bucket.refilled_at);
+            bucket.tokens = (bucket.tokens + elapsed.as_secs_f64() * refill_per_sec).min(capacity);
+            bucket.refilled_at = now;
+
+            if bucket.tokens >= 1.0 {
+                bucket.tokens -= 1.0;
+                true
+            } else {
+                false
+            }
+        };
+
+        async move {
+            if cond {
+                cont(event).await
+            } else {
+                ControlFlow::Continue(event)
+            }
+        }
+    })
+}
+
+struct Bucket {
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{deps, di::DependencyMap, help_inference, ManualClock};
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_rate_limit() {
+        let clock = ManualClock::new();
+        let handler =
+            help_inference(rate_limit(3, 2.0, clock.clone())).endpoint(|| async { "handled" });
+        let passed = |handler: Handler<'static, _, &'static str>| async move {
+            matches!(handler.dispatch(deps![]).await, ControlFlow::Break(_))
+        };
+
+        // The bucket is initially full.
+        for _ in 0..3 {
+            assert!(passed(handler.clone()).await);
+        }
+        assert!(!passed(handler.clone()).await);
+
+        // One token is refilled in half a second.
+        clock.advance(Duration::from_millis(250));
+        assert!(!passed(handler.clone()).await);
+        clock.advance(Duration::from_millis(250));
+        assert!(passed(handler.clone()).await);
+        assert!(!passed(handler.clone()).await);
+
+        // The bucket does not hold more than its capacity.
+        clock.advance(Duration::from_secs(60));
+        for _ in 0..3 {
+            assert!(passed(handler.clone()).await);
+        }
+        assert!(!passed(handler.clone()).await);
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_shared_between_clones() {
+        let clock = ManualClock::new();
+        let handler = help_inference(rate_limit(1, 1.0, clock)).endpoint(|| async {});
+
+        assert_eq!(handler.clone().dispatch(deps![]).await, ControlFlow::Break(()));
+        assert_eq!(handler.dispatch(deps![]).await, ControlFlow::Continue(deps![]));
+    }
+
+    #[test]
+    #[should_panic(expected = "the refill rate must be finite and non-negative")]
+    fn test_rate_limit_nan_refill() {
+        let _: Handler<DependencyMap, ()> = rate_limit(1, f64::NAN, ManualClock::new());
+    }
+}
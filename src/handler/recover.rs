@@ -0,0 +1,133 @@
+use crate::{from_fn_with_description, Handler, HandlerDescription};
+use futures::FutureExt;
+use std::{any::Any, ops::ControlFlow, panic::AssertUnwindSafe};
+
+impl<'a, Input, Output, Descr> Handler<'a, Input, Output, Descr>
+where
+    Input: Send + 'a,
+    Output: 'a,
+    Descr: HandlerDescription,
+{
+    /// Returns a handler that turns panics of this handler into
+    /// [`ControlFlow::Continue`].
+    ///
+    /// If this handler or its continuation panics, the panic is caught and the
+    /// returned handler continues with a copy of the original input, so that
+    /// the next branches (or the next event in your event loop) can be handled
+    /// as usual. The panic payload is logged with `tracing` if the `tracing`
+    /// feature is enabled.
+    ///
+    /// Note that the input is not required to be [`UnwindSafe`]: values shared
+    /// between the copy and the panicked handler (e.g., those behind an
+    /// [`Arc`] in a [`DependencyMap`]) may be observed in a broken state by
+    /// the next handlers.
+    ///
+    /// [`UnwindSafe`]: std::panic::UnwindSafe
+    /// [`Arc`]: std::sync::Arc
+    /// [`DependencyMap`]: crate::di::DependencyMap
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use dptree::prelude::*;
+    ///
+    /// let handler: Handler<_, _> = dptree::endpoint(|x: i32| async move { 100 / x }).recover_panic();
+    ///
+    /// assert_eq!(handler.dispatch(dptree::deps![5]).await, ControlFlow::Break(20));
+    /// assert_eq!(handler.dispatch(dptree::deps![0]).await, ControlFlow::Continue(dptree::deps![0]));
+    /// # }
+    /// ```
+    #[must_use]
+    #[track_caller]
+    pub fn recover_panic(self) -> Self
+    where
+        Input: Clone,
+    {
+        let description = self.wrapper_description();
+
+        from_fn_with_description(description, move |event: Input, cont| {
+            let this = self.clone();
+
+            async move {
+                match AssertUnwindSafe(this.execute(event.clone(), cont)).catch_unwind().await {
+                    Ok(result) => result,
+                    Err(payload) => {
+                        log_panic(&*payload);
+                        ControlFlow::Continue(event)
+                    }
+                }
+            }
+        })
+    }
+}
+
+#[cfg(feature = "tracing")]
+fn log_panic(payload: &(dyn Any + Send)) {
+    let message = payload
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("Box<dyn Any>");
+
+    tracing::error!(panic = message, "a handler panicked, continuing");
+}
+
+#[cfg(not(feature = "tracing"))]
+fn log_panic(_payload: &(dyn Any + Send)) {}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    use super::*;
+    use crate::{deps, help_inference};
+
+    #[tokio::test]
+    async fn test_recover_panic() {
+        let handled = Arc::new(AtomicUsize::new(0));
+
+        let handler = help_inference(crate::entry())
+            .branch(
+                crate::filter(|x: i32| x % 2 == 0)
+                    .endpoint(|x: i32| async move { panic!("cannot handle {}", x) })
+                    .recover_panic(),
+            )
+            .branch(crate::endpoint({
+                let handled = Arc::clone(&handled);
+                move |x: i32| {
+                    handled.fetch_add(1, Ordering::Relaxed);
+                    async move { x }
+                }
+            }));
+
+        // The loop survives the panics and the next branch handles every event.
+        for x in 0..4 {
+            assert_eq!(handler.dispatch(deps![x]).await, ControlFlow::Break(x));
+        }
+        assert_eq!(handled.load(Ordering::Relaxed), 4);
+    }
+
+    #[tokio::test]
+    async fn test_recover_panic_passes_through() {
+        let handler = help_inference(crate::filter(|x: i32| x > 0))
+            .endpoint(|x: i32| async move { x })
+            .recover_panic();
+
+        assert_eq!(handler.dispatch(deps![1]).await, ControlFlow::Break(1));
+        assert_eq!(handler.dispatch(deps![-1]).await, ControlFlow::Continue(deps![-1]));
+    }
+
+    #[tokio::test]
+    async fn test_recover_panic_in_continuation() {
+        let handler = help_inference(crate::entry())
+            .recover_panic()
+            .chain(crate::endpoint(|| async { panic!("in the continuation") }));
+
+        assert_eq!(handler.dispatch(deps![1]).await, ControlFlow::Continue(deps![1]));
+    }
+}
@@ -0,0 +1,350 @@
+use crate::{Handler, HandlerDescription, HandlerResult};
+use std::{
+    cell::Cell,
+    fmt::{self, Display, Formatter},
+    future::Future,
+    ops::ControlFlow,
+    pin::Pin,
+    sync::atomic::{AtomicU64, Ordering},
+    task::{Context, Poll},
+};
+
+// When less than `RED_ZONE` bytes of the stack are left, handlers are executed
+// on a new stack segment of `STACK_SIZE` bytes.
+const RED_ZONE: usize = 128 * 1024;
+const STACK_SIZE: usize = 1024 * 1024;
+
+thread_local! {
+    // The number of chained handlers being polled on this thread.
+    static DEPTH: Cell<usize> = const { Cell::new(0) };
+    // The identifier and the limit of the innermost
+    // `Handler::dispatch_with_limit` being polled.
+    static LIMIT: Cell<Option<(u64, usize)>> = const { Cell::new(None) };
+    // Whether `LIMIT` has been exceeded.
+    static EXCEEDED: Cell<bool> = const { Cell::new(false) };
+}
+
+/// An error returned by [`Handler::dispatch_with_limit`] when chained handlers
+/// nest deeper than the limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RecursionLimit {
+    /// The limit that has been exceeded.
+    pub limit: usize,
+}
+
+impl Display for RecursionLimit {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "the recursion limit of {} nested handlers has been exceeded", self.limit)
+    }
+}
+
+impl std::error::Error for RecursionLimit {}
+
+impl<'a, Input, Output, Descr> Handler<'a, Input, Output, Descr>
+where
+    Input: Send + 'a,
+    Output: 'a,
+    Descr: HandlerDescription,
+{
+    /// Executes this handler, failing if chained handlers nest deeper than
+    /// `limit`.
+    ///
+    /// Each handler of a chain executes the rest of the chain inside of its
+    /// own execution, so a long chain needs a deep stack to be dispatched.
+    /// [`Handler::dispatch`] grows the stack as needed, so it handles chains of
+    /// any length, but a handler that keeps dispatching itself recursively
+    /// would then consume memory until it runs out. This method tracks how
+    /// deep the handlers of [chains](Handler::chain) are nested and, once
+    /// `limit` is exceeded, stops the execution and returns
+    /// [`RecursionLimit`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use dptree::{prelude::*, RecursionLimit};
+    ///
+    /// let mut handler: Handler<DependencyMap, ()> = dptree::entry();
+    /// for _ in 0..100 {
+    ///     handler = handler.inspect(|| {});
+    /// }
+    /// let handler = handler.endpoint(|| async {});
+    ///
+    /// assert_eq!(
+    ///     handler.dispatch_with_limit(dptree::deps![], 1000).await,
+    ///     Ok(ControlFlow::Break(()))
+    /// );
+    /// assert_eq!(
+    ///     handler.dispatch_with_limit(dptree::deps![], 10).await,
+    ///     Err(RecursionLimit { limit: 10 })
+    /// );
+    /// # }
+    /// ```
+    pub async fn dispatch_with_limit(
+        &self,
+        container: Input,
+        limit: usize,
+    ) -> Result<ControlFlow<Output, Input>, RecursionLimit> {
+        static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+        let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+        Limited { inner: Box::pin(self.dispatch(container)), id, limit }.await
+    }
+}
+
+/// Calls `f`, on a new stack segment if the current one is almost exhausted.
+pub(crate) fn grow<R>(f: impl FnOnce() -> R) -> R {
+    stacker::maybe_grow(RED_ZONE, STACK_SIZE, f)
+}
+
+/// Guards the execution of a chained handler: grows the stack while it is
+/// polled or dropped, and counts it towards the limit of the
+/// `Handler::dispatch_with_limit` being polled, if any.
+pub(crate) fn guard<'a, Input, Output>(
+    fut: HandlerResult<'a, Input, Output>,
+) -> HandlerResult<'a, Input, Output>
+where
+    Input: 'a,
+    Output: 'a,
+{
+    let limit_id = LIMIT.with(Cell::get).map(|(id, _)| id);
+    Box::pin(Guarded { inner: Some(fut), limit_id })
+}
+
+/// Grows the stack while `fut` is polled or dropped, as [`guard`] does, but
+/// without counting it towards a limit.
+pub(crate) fn grow_future<'a, Input, Output>(
+    fut: HandlerResult<'a, Input, Output>,
+) -> HandlerResult<'a, Input, Output>
+where
+    Input: 'a,
+    Output: 'a,
+{
+    Box::pin(Guarded { inner: Some(fut), limit_id: None })
+}
+
+/// A future that grows the stack while being polled or dropped and counts
+/// itself in `DEPTH` while being polled by the `Limited` that created it.
+struct Guarded<F> {
+    // `None` only while being dropped.
+    inner: Option<F>,
+    limit_id: Option<u64>,
+}
+
+impl<F> Future for Guarded<F>
+where
+    F: Future + Unpin,
+{
+    type Output = F::Output;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // The future may be polled outside of the `Limited` that has created
+        // it, e.g., if it is moved to another task; the limit does not apply
+        // then.
+        let limit = match (LIMIT.with(Cell::get), self.limit_id) {
+            (Some((id, limit)), Some(own_id)) if id == own_id => Some(limit),
+            _ => None,
+        };
+        let _depth = match limit {
+            Some(limit) => {
+                let depth = DepthGuard::enter();
+                if depth.0 > limit {
+                    // `Limited` stops the execution right after this poll;
+                    // the waker is only a precaution against hanging.
+                    EXCEEDED.with(|exceeded| exceeded.set(true));
+                    cx.waker().wake_by_ref();
+                    return Poll::Pending;
+                }
+                Some(depth)
+            }
+            None => None,
+        };
+
+        let inner = self.inner.as_mut().expect("`Guarded` is polled after being dropped");
+        grow(|| Pin::new(inner).poll(cx))
+    }
+}
+
+impl<F> Drop for Guarded<F> {
+    fn drop(&mut self) {
+        // Dropping a pending chain drops all of its nested handlers.
+        if let Some(inner) = self.inner.take() {
+            grow(|| drop(inner));
+        }
+    }
+}
+
+/// Increments `DEPTH` until dropped, even if the poll panics.
+struct DepthGuard(usize);
+
+impl DepthGuard {
+    fn enter() -> Self {
+        let depth = DEPTH.with(|depth| depth.get()) + 1;
+        DEPTH.with(|cell| cell.set(depth));
+        Self(depth)
+    }
+}
+
+impl Drop for DepthGuard {
+    fn drop(&mut self) {
+        DEPTH.with(|depth| depth.set(self.0 - 1));
+    }
+}
+
+/// A future that sets `LIMIT` while polling `inner`.
+struct Limited<F> {
+    inner: F,
+    id: u64,
+    limit: usize,
+}
+
+impl<F> Future for Limited<F>
+where
+    F: Future + Unpin,
+{
+    type Output = Result<F::Output, RecursionLimit>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // `dispatch_with_limit` may be called inside of another one, so the
+        // outer state is saved and restored.
+        let outer = (
+            DEPTH.with(|depth| depth.replace(0)),
+            LIMIT.with(|limit| limit.replace(Some((self.id, self.limit)))),
+            EXCEEDED.with(|exceeded| exceeded.replace(false)),
+        );
+
+        let poll = Pin::new(&mut self.inner).poll(cx);
+        let exceeded = EXCEEDED.with(Cell::get);
+
+        DEPTH.with(|depth| depth.set(outer.0));
+        LIMIT.with(|limit| limit.set(outer.1));
+        EXCEEDED.with(|exceeded| exceeded.set(outer.2));
+
+        if exceeded {
+            Poll::Ready(Err(RecursionLimit { limit: self.limit }))
+        } else {
+            poll.map(Ok)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{deps, di::DependencyMap};
+    use futures::{future, FutureExt};
+    use std::sync::{Arc, Mutex};
+
+    fn deep_chain(depth: usize) -> Handler<'static, DependencyMap, usize> {
+        let mut handler = crate::entry();
+        for _ in 0..depth {
+            handler = handler.filter(|| true).map(|x: usize| x + 1);
+        }
+        handler.endpoint(|x: usize| async move { x })
+    }
+
+    #[tokio::test]
+    async fn test_dispatch_with_limit() {
+        let handler = deep_chain(100);
+
+        assert_eq!(
+            handler.dispatch_with_limit(deps![0usize], 1000).await,
+            Ok(ControlFlow::Break(100))
+        );
+        assert_eq!(
+            handler.dispatch_with_limit(deps![0usize], 100).await,
+            Err(RecursionLimit { limit: 100 })
+        );
+        // The state is reset after the limit is exceeded.
+        assert_eq!(handler.dispatch(deps![0usize]).await, ControlFlow::Break(100));
+    }
+
+    #[tokio::test]
+    async fn test_deep_chain() {
+        let handler = deep_chain(10_000);
+
+        assert_eq!(handler.dispatch(deps![0usize]).await, ControlFlow::Break(10_000));
+        assert_eq!(handler.clone().dispatch_owned(deps![0usize]).await, ControlFlow::Break(10_000));
+        assert_eq!(
+            handler.dispatch_with_limit(deps![0usize], 1000).await,
+            Err(RecursionLimit { limit: 1000 })
+        );
+        assert_eq!(handler.freeze().dispatch(deps![0usize]).await, ControlFlow::Break(10_000));
+    }
+
+    #[tokio::test]
+    async fn test_deep_chain_sync_links() {
+        // These links execute the rest of the chain right away, rather than
+        // when polled.
+        let mut handler = crate::entry();
+        for _ in 0..10_000 {
+            handler = handler.chain(crate::from_fn(|event, cont| cont(event)));
+        }
+        let handler: Handler<DependencyMap, i32> = handler.endpoint(|| async { 1 });
+
+        assert_eq!(handler.dispatch(deps![]).await, ControlFlow::Break(1));
+    }
+
+    #[tokio::test]
+    async fn test_deep_chain_drop() {
+        // The chain is dropped while pending in its deepest handler.
+        let mut handler = crate::entry();
+        for _ in 0..10_000 {
+            handler = handler.inspect(|| {});
+        }
+        let handler: Handler<DependencyMap, ()> =
+            handler.chain(crate::from_fn(|_, _| future::pending()));
+
+        let dispatch = handler.dispatch(deps![]);
+        assert!(tokio::time::timeout(std::time::Duration::from_millis(10), dispatch)
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_guarded_outside_of_limit() {
+        // A chain started under one limit and resumed under another one counts
+        // only towards the first one.
+        let (tx, rx) = futures::channel::oneshot::channel::<()>();
+        let rx = rx.shared();
+        let mut handler = crate::entry();
+        for _ in 0..100 {
+            handler = handler.inspect(|| {});
+        }
+        let handler: Handler<DependencyMap, ()> = handler.chain(crate::from_fn(move |_, _| {
+            let rx = rx.clone();
+            async move {
+                let _ = rx.await;
+                ControlFlow::Break(())
+            }
+        }));
+
+        let dispatch = Arc::new(Mutex::new(handler.dispatch_owned(deps![]).boxed()));
+        let poll_once: Handler<DependencyMap, bool> = crate::from_fn(move |_, _| {
+            let dispatch = Arc::clone(&dispatch);
+            async move {
+                let ready = future::poll_fn(|cx| {
+                    Poll::Ready(dispatch.lock().unwrap().poll_unpin(cx).is_ready())
+                })
+                .await;
+                ControlFlow::Break(ready)
+            }
+        });
+
+        assert_eq!(
+            poll_once.dispatch_with_limit(deps![], 1000).await,
+            Ok(ControlFlow::Break(false))
+        );
+        tx.send(()).unwrap();
+        assert_eq!(poll_once.dispatch_with_limit(deps![], 10).await, Ok(ControlFlow::Break(true)));
+    }
+
+    #[test]
+    fn test_recursion_limit_display() {
+        assert_eq!(
+            RecursionLimit { limit: 10 }.to_string(),
+            "the recursion limit of 10 nested handlers has been exceeded"
+        );
+    }
+}
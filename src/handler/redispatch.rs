@@ -0,0 +1,209 @@
+use crate::{
+    di::{Asyncify, Injectable},
+    from_fn_with_description, Handler, HandlerDescription,
+};
+use alloc::sync::Arc;
+use core::ops::ControlFlow;
+
+/// An output of a handler that can request another dispatch.
+///
+/// See [`Handler::with_redispatch`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Redispatch<Input, Output> {
+    /// The handler has produced the final output.
+    Done(Output),
+
+    /// The handler requests to dispatch this input from the top.
+    Again(Input),
+}
+
+/// Constructs a handler that requests another dispatch with a new input.
+///
+/// The result of `f` becomes the input of the next pass of the dispatch. This
+/// handler always breaks, so it is the last one in its chain, like an
+/// [`endpoint`](fn@crate::endpoint). See [`Handler::with_redispatch`] for how
+/// requests are handled.
+#[must_use]
+#[track_caller]
+pub fn redispatch<'a, F, Input, Output, Args, Descr>(
+    f: F,
+) -> Handler<'a, Input, Redispatch<Input, Output>, Descr>
+where
+    Asyncify<F>: Injectable<Input, Input, Args> + Send + Sync + 'a,
+    Input: Send + 'a,
+    Output: 'a,
+    Descr: HandlerDescription,
+{
+    let required = <Asyncify<F> as Injectable<Input, Input, Args>>::dependencies();
+    redispatch_with_description(Descr::endpoint().with_dependencies(&required, &[]), Asyncify(f))
+}
+
+/// The asynchronous version of [`redispatch`].
+#[must_use]
+#[track_caller]
+pub fn redispatch_async<'a, F, Input, Output, Args, Descr>(
+    f: F,
+) -> Handler<'a, Input, Redispatch<Input, Output>, Descr>
+where
+    F: Injectable<Input, Input, Args> + Send + Sync + 'a,
+    Input: Send + 'a,
+    Output: 'a,
+    Descr: HandlerDescription,
+{
+    let required = <F as Injectable<Input, Input, Args>>::dependencies();
+    redispatch_with_description(Descr::endpoint().with_dependencies(&required, &[]), f)
+}
+
+fn redispatch_with_description<'a, F, Input, Output, Args, Descr>(
+    description: Descr,
+    f: F,
+) -> Handler<'a, Input, Redispatch<Input, Output>, Descr>
+where
+    F: Injectable<Input, Input, Args> + Send + Sync + 'a,
+    Input: Send + 'a,
+    Output: 'a,
+{
+    let f = Arc::new(f);
+
+    from_fn_with_description(description, move |event, _cont| {
+        let f = Arc::clone(&f);
+
+        async move {
+            let f = f.inject(&event);
+            let input = f().await;
+            ControlFlow::Break(Redispatch::Again(input))
+        }
+    })
+}
+
+impl<'a, Input, Output, Descr> Handler<'a, Input, Redispatch<Input, Output>, Descr>
+where
+    Input: Send + 'a,
+    Output: Send + 'a,
+    Descr: HandlerDescription,
+{
+    /// Returns a handler that dispatches this one again when requested.
+    ///
+    /// When this handler breaks with [`Redispatch::Done`], the returned
+    /// handler breaks with its payload. When this handler breaks with
+    /// [`Redispatch::Again`], e.g., by reaching [`redispatch`], it is
+    /// dispatched from the top with the new input.
+    ///
+    /// To guard against endless loops, at most `max_passes` additional
+    /// dispatches are made. If this handler requests a dispatch once more, or
+    /// continues on some pass, the returned handler passes the original input
+    /// further, as if it continued; the inputs of the redispatches are
+    /// discarded.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use dptree::{prelude::*, Redispatch};
+    ///
+    /// let handler: Handler<_, _> = dptree::entry()
+    ///     .branch(
+    ///         dptree::filter(|s: &'static str| s.starts_with('/'))
+    ///             .endpoint(|s: &'static str| async move { Redispatch::Done(s.len()) }),
+    ///     )
+    ///     .branch(dptree::redispatch(|| dptree::deps!["/help"]))
+    ///     .with_redispatch(1);
+    ///
+    /// assert_eq!(handler.dispatch(dptree::deps!["hello"]).await, ControlFlow::Break(5));
+    /// # }
+    /// ```
+    #[must_use]
+    #[track_caller]
+    pub fn with_redispatch(self, max_passes: usize) -> Handler<'a, Input, Output, Descr>
+    where
+        Input: Clone,
+    {
+        let description = self.wrapper_description();
+
+        from_fn_with_description(description, move |event: Input, cont| {
+            let this = self.clone();
+
+            async move {
+                let mut input = event.clone();
+                let mut passes = 0;
+
+                loop {
+                    match this.dispatch(input).await {
+                        ControlFlow::Break(Redispatch::Done(output)) => {
+                            return ControlFlow::Break(output)
+                        }
+                        ControlFlow::Break(Redispatch::Again(next)) if passes < max_passes => {
+                            passes += 1;
+                            input = next;
+                        }
+                        ControlFlow::Break(Redispatch::Again(_)) | ControlFlow::Continue(_) => {
+                            return cont(event).await
+                        }
+                    }
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{deps, di::DependencyMap};
+
+    #[derive(Debug, Clone, PartialEq)]
+    enum Event {
+        Raw(&'static str),
+        Parsed(i32),
+    }
+
+    fn handler() -> Handler<'static, DependencyMap, Redispatch<DependencyMap, i32>> {
+        crate::entry()
+            .branch(
+                crate::case![Event::Parsed(x)]
+                    .endpoint(|x: i32| async move { Redispatch::Done(x) }),
+            )
+            .branch(crate::case![Event::Raw(s)].chain(crate::redispatch(|s: &'static str| {
+                deps![Event::Parsed(s.parse().unwrap_or(0))]
+            })))
+    }
+
+    #[tokio::test]
+    async fn test_redispatch() {
+        let handler = handler().with_redispatch(1);
+
+        assert_eq!(handler.dispatch(deps![Event::Parsed(1)]).await, ControlFlow::Break(1));
+        assert_eq!(handler.dispatch(deps![Event::Raw("42")]).await, ControlFlow::Break(42));
+    }
+
+    #[tokio::test]
+    async fn test_redispatch_limit() {
+        let counting: Handler<DependencyMap, Redispatch<DependencyMap, ()>> =
+            crate::redispatch(|x: i32| deps![x + 1]);
+
+        // The original input is passed further, not the last requested one.
+        match counting.with_redispatch(3).dispatch(deps![0]).await {
+            ControlFlow::Continue(input) => {
+                let x: Arc<i32> = crate::di::DependencySupplier::get(&input);
+                assert_eq!(*x, 0);
+            }
+            ControlFlow::Break(()) => panic!("the handler must continue"),
+        }
+
+        // Without redispatches allowed, the first request is passed further.
+        let result = handler().with_redispatch(0).dispatch(deps![Event::Raw("1")]).await;
+        assert!(matches!(result, ControlFlow::Continue(_)));
+    }
+
+    #[tokio::test]
+    async fn test_redispatch_continue() {
+        let handler: Handler<DependencyMap, Redispatch<DependencyMap, i32>> =
+            crate::filter(|x: i32| x == 0).chain(crate::redispatch(|| deps![1]));
+
+        // The second pass continues, and so does the handler, with the
+        // original input.
+        let handler = handler.with_redispatch(5).endpoint(|x: i32| async move { x + 10 });
+        assert_eq!(handler.dispatch(deps![0]).await, ControlFlow::Break(10));
+    }
+}
@@ -0,0 +1,1043 @@
+use crate::{
+    di::{Asyncify, CompiledFn, Injectable, Insert},
+    filter_map_async_with_description, from_fn_with_description, Handler, HandlerDescription,
+};
+use alloc::{boxed::Box, sync::Arc, vec::Vec};
+use core::{future::Future, marker::PhantomData, ops::ControlFlow};
+
+/// Constructs a handler that passes either a value or an error further.
+///
+/// If the `proj` function returns `Ok(v)`, then `v` will be added to the
+/// container; if it returns `Err(e)`, then `e` will be added instead. In both
+/// cases, the execution continues further in a handler chain, so the next
+/// handlers can dispatch on the presence of either type (see
+/// [`DependencyMap::contains`]).
+///
+/// Note that the container may already have a value of the other type, e.g.,
+/// from a previous handler, so do not request both types in the same handler.
+///
+/// See also: [`crate::filter_map`].
+///
+/// # Examples
+///
+/// ```
+/// # #[tokio::main]
+/// # async fn main() {
+/// use dptree::prelude::*;
+/// use std::num::ParseIntError;
+///
+/// let handler: Handler<_, _> =
+///     dptree::filter_map_result_insert(|s: &'static str| s.parse::<i32>())
+///         .branch(
+///             dptree::from_fn(|deps: DependencyMap, cont| async move {
+///                 if deps.contains::<i32>() {
+///                     cont(deps).await
+///                 } else {
+///                     ControlFlow::Continue(deps)
+///                 }
+///             })
+///             .endpoint(|x: i32| async move { format!("parsed {x}") }),
+///         )
+///         .endpoint(|e: ParseIntError| async move { format!("failed: {e}") });
+///
+/// assert_eq!(
+///     handler.dispatch(dptree::deps!["7"]).await,
+///     ControlFlow::Break("parsed 7".to_owned())
+/// );
+/// assert_eq!(
+///     handler.dispatch(dptree::deps!["x"]).await,
+///     ControlFlow::Break("failed: invalid digit found in string".to_owned())
+/// );
+/// # }
+/// ```
+///
+/// [`DependencyMap::contains`]: crate::di::DependencyMap::contains
+#[must_use]
+#[track_caller]
+pub fn filter_map_result_insert<'a, Projection, Input, Output, T, E, Args, Descr>(
+    proj: Projection,
+) -> Handler<'a, Input, Output, Descr>
+where
+    Input: Clone,
+    Asyncify<Projection>: Injectable<Input, Result<T, E>, Args> + Send + Sync + 'a,
+    Input: Insert<T> + Insert<E> + Send + 'a,
+    Output: 'a,
+    Descr: HandlerDescription,
+    T: Send,
+    E: Send,
+{
+    let required = <Asyncify<Projection> as Injectable<Input, Result<T, E>, Args>>::dependencies();
+    // Only one of `T` and `E` is added, so neither is described as provided.
+    filter_map_result_insert_with_description(
+        Descr::map().with_dependencies(&required, &[]),
+        Asyncify(proj),
+    )
+}
+
+/// The asynchronous version of [`filter_map_result_insert`].
+#[must_use]
+#[track_caller]
+pub fn filter_map_result_insert_async<'a, Projection, Input, Output, T, E, Args, Descr>(
+    proj: Projection,
+) -> Handler<'a, Input, Output, Descr>
+where
+    Input: Clone,
+    Projection: Injectable<Input, Result<T, E>, Args> + Send + Sync + 'a,
+    Input: Insert<T> + Insert<E> + Send + 'a,
+    Output: 'a,
+    Descr: HandlerDescription,
+    T: Send,
+    E: Send,
+{
+    let required = <Projection as Injectable<Input, Result<T, E>, Args>>::dependencies();
+    // Only one of `T` and `E` is added, so neither is described as provided.
+    filter_map_result_insert_with_description(
+        Descr::map_async().with_dependencies(&required, &[]),
+        proj,
+    )
+}
+
+fn filter_map_result_insert_with_description<'a, Projection, Input, Output, T, E, Args, Descr>(
+    description: Descr,
+    proj: Projection,
+) -> Handler<'a, Input, Output, Descr>
+where
+    Input: Clone,
+    Projection: Injectable<Input, Result<T, E>, Args> + Send + Sync + 'a,
+    Input: Insert<T> + Insert<E> + Send + 'a,
+    Output: 'a,
+    T: Send,
+    E: Send,
+{
+    let proj = Arc::new(proj);
+
+    from_fn_with_description(description, move |container: Input, cont| {
+        let proj = Arc::clone(&proj);
+
+        async move {
+            let proj = proj.inject(&container);
+            let res = proj().await;
+            core::mem::drop(proj);
+
+            let mut intermediate = container.clone();
+            match res {
+                Ok(value) => {
+                    Insert::<T>::insert(&mut intermediate, value);
+                }
+                Err(error) => {
+                    Insert::<E>::insert(&mut intermediate, error);
+                }
+            }
+            match cont(intermediate).await {
+                ControlFlow::Continue(_) => ControlFlow::Continue(container),
+                ControlFlow::Break(result) => ControlFlow::Break(result),
+            }
+        }
+    })
+}
+
+/// Constructs a handler that passes a value further or recovers from an
+/// error.
+///
+/// If the `proj` function returns `Ok(v)`, then `v` will be added to the
+/// container and passed further in a handler chain. If it returns `Err(e)`,
+/// then the future returned by `recover(e)` is awaited: if it resolves to
+/// `Some(output)`, the handler breaks with `output`, otherwise it returns
+/// [`ControlFlow::Continue`] with the old container, so that the next
+/// branches can try to handle it.
+///
+/// This packages the "parse, or handle the error" pattern into one node.
+///
+/// # Examples
+///
+/// ```
+/// # #[tokio::main]
+/// # async fn main() {
+/// use dptree::prelude::*;
+/// use std::num::ParseIntError;
+///
+/// let handler: Handler<_, String> = dptree::filter_map_result_recover(
+///     |s: &'static str| s.parse::<i32>(),
+///     |e: ParseIntError| async move { Some(format!("bad number: {e}")) },
+/// )
+/// .endpoint(|x: i32| async move { format!("got {x}") });
+///
+/// assert_eq!(handler.dispatch(dptree::deps!["7"]).await, ControlFlow::Break("got 7".to_owned()));
+/// assert_eq!(
+///     handler.dispatch(dptree::deps!["x"]).await,
+///     ControlFlow::Break("bad number: invalid digit found in string".to_owned())
+/// );
+/// # }
+/// ```
+#[must_use]
+#[track_caller]
+pub fn filter_map_result_recover<'a, Projection, Recover, Fut, Input, Output, T, E, Args, Descr>(
+    proj: Projection,
+    recover: Recover,
+) -> Handler<'a, Input, Output, Descr>
+where
+    Input: Clone,
+    Asyncify<Projection>: Injectable<Input, Result<T, E>, Args> + Send + Sync + 'a,
+    Recover: Fn(E) -> Fut + Send + Sync + 'a,
+    Fut: Future<Output = Option<Output>> + Send + 'a,
+    Input: Insert<T> + Send + 'a,
+    Output: 'a,
+    Descr: HandlerDescription,
+    T: Send,
+    E: Send,
+{
+    let required = <Asyncify<Projection> as Injectable<Input, Result<T, E>, Args>>::dependencies();
+    let provided = [core::any::type_name::<T>()];
+    filter_map_result_recover_with_description(
+        Descr::filter_map().with_dependencies(&required, &provided),
+        Asyncify(proj),
+        recover,
+    )
+}
+
+/// The asynchronous version of [`filter_map_result_recover`].
+#[must_use]
+#[track_caller]
+pub fn filter_map_result_recover_async<
+    'a,
+    Projection,
+    Recover,
+    Fut,
+    Input,
+    Output,
+    T,
+    E,
+    Args,
+    Descr,
+>(
+    proj: Projection,
+    recover: Recover,
+) -> Handler<'a, Input, Output, Descr>
+where
+    Input: Clone,
+    Projection: Injectable<Input, Result<T, E>, Args> + Send + Sync + 'a,
+    Recover: Fn(E) -> Fut + Send + Sync + 'a,
+    Fut: Future<Output = Option<Output>> + Send + 'a,
+    Input: Insert<T> + Send + 'a,
+    Output: 'a,
+    Descr: HandlerDescription,
+    T: Send,
+    E: Send,
+{
+    let required = <Projection as Injectable<Input, Result<T, E>, Args>>::dependencies();
+    let provided = [core::any::type_name::<T>()];
+    filter_map_result_recover_with_description(
+        Descr::filter_map_async().with_dependencies(&required, &provided),
+        proj,
+        recover,
+    )
+}
+
+fn filter_map_result_recover_with_description<
+    'a,
+    Projection,
+    Recover,
+    Fut,
+    Input,
+    Output,
+    T,
+    E,
+    Args,
+    Descr,
+>(
+    description: Descr,
+    proj: Projection,
+    recover: Recover,
+) -> Handler<'a, Input, Output, Descr>
+where
+    Input: Clone,
+    Projection: Injectable<Input, Result<T, E>, Args> + Send + Sync + 'a,
+    Recover: Fn(E) -> Fut + Send + Sync + 'a,
+    Fut: Future<Output = Option<Output>> + Send + 'a,
+    Input: Insert<T> + Send + 'a,
+    Output: 'a,
+    T: Send,
+    E: Send,
+{
+    let proj = Arc::new(proj);
+    let recover = Arc::new(recover);
+
+    from_fn_with_description(description, move |container: Input, cont| {
+        let proj = Arc::clone(&proj);
+        let recover = Arc::clone(&recover);
+
+        async move {
+            let proj = proj.inject(&container);
+            let res = proj().await;
+            core::mem::drop(proj);
+
+            match res {
+                Ok(value) => {
+                    let mut intermediate = container.clone();
+                    intermediate.insert(value);
+                    match cont(intermediate).await {
+                        ControlFlow::Continue(_) => ControlFlow::Continue(container),
+                        ControlFlow::Break(result) => ControlFlow::Break(result),
+                    }
+                }
+                Err(error) => match recover(error).await {
+                    Some(output) => ControlFlow::Break(output),
+                    None => ControlFlow::Continue(container),
+                },
+            }
+        }
+    })
+}
+
+/// Constructs a handler that optionally passes a value further, reporting why
+/// it did not.
+///
+/// This is the same as [`filter_map`](crate::filter_map), except that `proj`
+/// returns a [`Result`] instead of an [`Option`]: if it returns `Ok(v)`, then
+/// `v` will be added to the container and passed further in a handler chain;
+/// if it returns `Err(reason)`, then `log(reason)` is called, and the handler
+/// returns [`ControlFlow::Continue`] with the old container. The reason helps
+/// to diagnose why the handler has not matched; `log` can, e.g., emit a
+/// `tracing` event.
+///
+/// # Examples
+///
+/// ```
+/// # #[tokio::main]
+/// # async fn main() {
+/// use dptree::prelude::*;
+///
+/// let handler: Handler<_, String> = dptree::filter_map_logged(
+///     |s: &'static str| s.strip_prefix('/').ok_or("not a command"),
+///     |reason: &'static str| println!("skipped: {reason}"),
+/// )
+/// .endpoint(|command: &'static str| async move { command.to_uppercase() });
+///
+/// assert_eq!(
+///     handler.dispatch(dptree::deps!["/start"]).await,
+///     ControlFlow::Break("START".to_owned())
+/// );
+/// // Prints "skipped: not a command".
+/// assert!(matches!(handler.dispatch(dptree::deps!["hello"]).await, ControlFlow::Continue(_)));
+/// # }
+/// ```
+#[must_use]
+#[track_caller]
+pub fn filter_map_logged<'a, Projection, Log, Input, Output, T, Reason, Args, Descr>(
+    proj: Projection,
+    log: Log,
+) -> Handler<'a, Input, Output, Descr>
+where
+    Input: Clone,
+    Asyncify<Projection>: Injectable<Input, Result<T, Reason>, Args> + Send + Sync + 'a,
+    Log: Fn(Reason) + Send + Sync + 'a,
+    Input: Insert<T> + Send + 'a,
+    Output: 'a,
+    Descr: HandlerDescription,
+    T: Send + 'static,
+    Reason: Send + 'static,
+{
+    let required =
+        <Asyncify<Projection> as Injectable<Input, Result<T, Reason>, Args>>::dependencies();
+    let provided = [core::any::type_name::<T>()];
+    filter_map_async_with_description(
+        Descr::filter_map().with_dependencies(&required, &provided),
+        Logged { proj: Asyncify(proj), log, _reason: PhantomData },
+    )
+}
+
+/// The asynchronous version of [`filter_map_logged`].
+#[must_use]
+#[track_caller]
+pub fn filter_map_logged_async<'a, Projection, Log, Input, Output, T, Reason, Args, Descr>(
+    proj: Projection,
+    log: Log,
+) -> Handler<'a, Input, Output, Descr>
+where
+    Input: Clone,
+    Projection: Injectable<Input, Result<T, Reason>, Args> + Send + Sync + 'a,
+    Log: Fn(Reason) + Send + Sync + 'a,
+    Input: Insert<T> + Send + 'a,
+    Output: 'a,
+    Descr: HandlerDescription,
+    T: Send + 'static,
+    Reason: Send + 'static,
+{
+    let required = <Projection as Injectable<Input, Result<T, Reason>, Args>>::dependencies();
+    let provided = [core::any::type_name::<T>()];
+    filter_map_async_with_description(
+        Descr::filter_map_async().with_dependencies(&required, &provided),
+        Logged { proj, log, _reason: PhantomData },
+    )
+}
+
+/// A projection that results in `None` on an error, reporting the error to
+/// `log`.
+struct Logged<Projection, Log, Reason> {
+    proj: Projection,
+    log: Log,
+    _reason: PhantomData<fn(Reason)>,
+}
+
+impl<Projection, Log, Input, T, Reason, Args> Injectable<Input, Option<T>, Args>
+    for Logged<Projection, Log, Reason>
+where
+    Projection: Injectable<Input, Result<T, Reason>, Args>,
+    Log: Fn(Reason) + Send + Sync,
+    T: Send + 'static,
+    Reason: Send + 'static,
+{
+    fn inject<'a>(&'a self, container: &'a Input) -> CompiledFn<'a, Option<T>> {
+        let proj = self.proj.inject(container);
+        let log = &self.log;
+
+        Arc::new(move || {
+            let fut = proj();
+            Box::pin(async move {
+                match fut.await {
+                    Ok(value) => Some(value),
+                    Err(reason) => {
+                        log(reason);
+                        None
+                    }
+                }
+            })
+        })
+    }
+
+    fn dependencies() -> Vec<&'static str> {
+        Projection::dependencies()
+    }
+}
+
+/// A shared accumulator of errors.
+///
+/// All clones of a sink share the same errors, so a sink can be given to
+/// [`filter_map_collecting_errors`] and, e.g., added to the container, so that
+/// the handlers after it can inspect the errors collected so far.
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub struct ErrorSink<E> {
+    errors: Arc<std::sync::Mutex<Vec<E>>>,
+}
+
+#[cfg(feature = "std")]
+impl<E> ErrorSink<E> {
+    /// Constructs an empty sink.
+    #[must_use]
+    pub fn new() -> Self {
+        Self { errors: Arc::new(std::sync::Mutex::new(Vec::new())) }
+    }
+
+    /// Appends `error` to this sink.
+    pub fn push(&self, error: E) {
+        self.lock().push(error);
+    }
+
+    /// Returns the number of errors in this sink.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Returns `true` if this sink has no errors.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    /// Removes all errors from this sink and returns them, in the order they
+    /// were pushed.
+    #[must_use]
+    pub fn take(&self) -> Vec<E> {
+        core::mem::take(&mut *self.lock())
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<E>> {
+        self.errors.lock().unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+}
+
+// `#[derive(Clone)]` obligates `E` to satisfy `Clone` as well.
+#[cfg(feature = "std")]
+impl<E> Clone for ErrorSink<E> {
+    fn clone(&self) -> Self {
+        Self { errors: Arc::clone(&self.errors) }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<E> Default for ErrorSink<E> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Constructs a handler that passes a value further, collecting errors
+/// instead of stopping.
+///
+/// If the `proj` function returns `Ok(v)`, then `v` will be added to the
+/// container; if it returns `Err(e)`, then `e` is pushed to `sink`, and
+/// nothing is added. In both cases, the execution continues further in a
+/// handler chain, so one failed step does not stop the processing. Since the
+/// value may be absent, the next handlers should not require `T`
+/// unconditionally; check it with
+/// [`DependencyMap::contains`](crate::di::DependencyMap::contains) first.
+///
+/// # Examples
+///
+/// ```
+/// # #[tokio::main]
+/// # async fn main() {
+/// use dptree::{prelude::*, ErrorSink};
+/// use std::num::ParseIntError;
+///
+/// let sink = ErrorSink::new();
+/// let handler: Handler<_, bool> =
+///     dptree::filter_map_collecting_errors(|s: &'static str| s.parse::<i32>(), sink.clone())
+///         .endpoint(|| async { true });
+///
+/// assert_eq!(handler.dispatch(dptree::deps!["1"]).await, ControlFlow::Break(true));
+/// assert_eq!(handler.dispatch(dptree::deps!["x"]).await, ControlFlow::Break(true));
+///
+/// let errors: Vec<ParseIntError> = sink.take();
+/// assert_eq!(errors.len(), 1);
+/// # }
+/// ```
+#[cfg(feature = "std")]
+#[must_use]
+#[track_caller]
+pub fn filter_map_collecting_errors<'a, Projection, Input, Output, T, E, Args, Descr>(
+    proj: Projection,
+    sink: ErrorSink<E>,
+) -> Handler<'a, Input, Output, Descr>
+where
+    Input: Clone,
+    Asyncify<Projection>: Injectable<Input, Result<T, E>, Args> + Send + Sync + 'a,
+    Input: Insert<T> + Send + 'a,
+    Output: 'a,
+    Descr: HandlerDescription,
+    T: Send,
+    E: Send + 'a,
+{
+    let required = <Asyncify<Projection> as Injectable<Input, Result<T, E>, Args>>::dependencies();
+    // `T` is not added on errors, so it is not described as provided.
+    filter_map_collecting_errors_with_description(
+        Descr::map().with_dependencies(&required, &[]),
+        Asyncify(proj),
+        sink,
+    )
+}
+
+/// The asynchronous version of [`filter_map_collecting_errors`].
+#[cfg(feature = "std")]
+#[must_use]
+#[track_caller]
+pub fn filter_map_collecting_errors_async<'a, Projection, Input, Output, T, E, Args, Descr>(
+    proj: Projection,
+    sink: ErrorSink<E>,
+) -> Handler<'a, Input, Output, Descr>
+where
+    Input: Clone,
+    Projection: Injectable<Input, Result<T, E>, Args> + Send + Sync + 'a,
+    Input: Insert<T> + Send + 'a,
+    Output: 'a,
+    Descr: HandlerDescription,
+    T: Send,
+    E: Send + 'a,
+{
+    let required = <Projection as Injectable<Input, Result<T, E>, Args>>::dependencies();
+    // `T` is not added on errors, so it is not described as provided.
+    filter_map_collecting_errors_with_description(
+        Descr::map_async().with_dependencies(&required, &[]),
+        proj,
+        sink,
+    )
+}
+
+#[cfg(feature = "std")]
+fn filter_map_collecting_errors_with_description<'a, Projection, Input, Output, T, E, Args, Descr>(
+    description: Descr,
+    proj: Projection,
+    sink: ErrorSink<E>,
+) -> Handler<'a, Input, Output, Descr>
+where
+    Input: Clone,
+    Projection: Injectable<Input, Result<T, E>, Args> + Send + Sync + 'a,
+    Input: Insert<T> + Send + 'a,
+    Output: 'a,
+    T: Send,
+    E: Send + 'a,
+{
+    let proj = Arc::new(proj);
+
+    from_fn_with_description(description, move |container: Input, cont| {
+        let proj = Arc::clone(&proj);
+        let sink = sink.clone();
+
+        async move {
+            let proj = proj.inject(&container);
+            let res = proj().await;
+            core::mem::drop(proj);
+
+            let mut intermediate = container.clone();
+            match res {
+                Ok(value) => {
+                    intermediate.insert(value);
+                }
+                Err(error) => sink.push(error),
+            }
+            match cont(intermediate).await {
+                ControlFlow::Continue(_) => ControlFlow::Continue(container),
+                ControlFlow::Break(result) => ControlFlow::Break(result),
+            }
+        }
+    })
+}
+
+/// A nested [`Result`] that can be flattened.
+///
+/// See [`Handler::flatten_result`].
+pub trait NestedResult {
+    /// The flattened result type.
+    type Flattened;
+
+    /// Flattens this result.
+    fn flatten(self) -> Self::Flattened;
+
+    /// Nests a flattened result back, so that `Self::nest(r).flatten() == r`.
+    fn nest(flattened: Self::Flattened) -> Self;
+}
+
+impl<T, E> NestedResult for Result<Result<T, E>, E> {
+    type Flattened = Result<T, E>;
+
+    fn flatten(self) -> Self::Flattened {
+        self.and_then(core::convert::identity)
+    }
+
+    fn nest(flattened: Self::Flattened) -> Self {
+        Ok(flattened)
+    }
+}
+
+impl<'a, Input, Output, Descr> Handler<'a, Input, Output, Descr>
+where
+    Input: Send + 'a,
+    Output: NestedResult + Send + 'a,
+    Output::Flattened: Send + 'a,
+    Descr: HandlerDescription,
+{
+    /// Flattens the nested [`Result`] this handler breaks with.
+    ///
+    /// When this handler breaks with `Ok(Ok(x))`, the returned handler breaks
+    /// with `Ok(x)`; when it breaks with `Ok(Err(e))` or `Err(e)`, the
+    /// returned handler breaks with `Err(e)`. Outputs of the handlers chained
+    /// after this one are passed through as they are.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use dptree::prelude::*;
+    ///
+    /// let handler: Handler<_, Result<Result<i32, String>, String>> =
+    ///     dptree::endpoint(|s: &'static str| async move {
+    ///         let n = s.parse::<i32>().map_err(|e| e.to_string())?;
+    ///         Ok(if n > 0 { Ok(n) } else { Err("not positive".to_owned()) })
+    ///     });
+    /// let handler = handler.flatten_result();
+    ///
+    /// assert_eq!(handler.dispatch(dptree::deps!["1"]).await, ControlFlow::Break(Ok(1)));
+    /// assert_eq!(
+    ///     handler.dispatch(dptree::deps!["-1"]).await,
+    ///     ControlFlow::Break(Err("not positive".to_owned()))
+    /// );
+    /// # }
+    /// ```
+    #[must_use]
+    #[track_caller]
+    pub fn flatten_result(self) -> Handler<'a, Input, Output::Flattened, Descr> {
+        let description = self.wrapper_description();
+
+        from_fn_with_description(description, move |event, cont| {
+            let this = self.clone();
+
+            async move {
+                let result = this
+                    .execute(event, |event| async move {
+                        match cont(event).await {
+                            ControlFlow::Continue(event) => ControlFlow::Continue(event),
+                            ControlFlow::Break(result) => ControlFlow::Break(Output::nest(result)),
+                        }
+                    })
+                    .await;
+
+                match result {
+                    ControlFlow::Continue(event) => ControlFlow::Continue(event),
+                    ControlFlow::Break(output) => ControlFlow::Break(output.flatten()),
+                }
+            }
+        })
+    }
+}
+
+impl<'a, Input, T, E, Descr> Handler<'a, Input, Result<T, E>, Descr>
+where
+    Input: Send + 'a,
+    T: Send + 'a,
+    E: Send + 'a,
+    Descr: HandlerDescription,
+{
+    /// Transforms the `Ok` value this handler breaks with.
+    ///
+    /// When this handler breaks with `Ok(x)`, the returned handler breaks with
+    /// `Ok(f(x))`; errors are passed through as they are. This is
+    /// [`Result::map`] at the handler level.
+    ///
+    /// Since `f` cannot be reverted, outputs of a continuation cannot be
+    /// converted to the output type of this handler. Therefore, this handler
+    /// is executed as a complete handler, and the returned handler is
+    /// [terminal](crate::Handler#terminal-handlers).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use dptree::prelude::*;
+    ///
+    /// let handler: Handler<_, Result<i32, std::num::ParseIntError>> =
+    ///     dptree::endpoint(|s: &'static str| async move { s.parse::<i32>() });
+    /// let handler = handler.map_ok(|n| n.to_string().len());
+    ///
+    /// assert_eq!(handler.dispatch(dptree::deps!["123"]).await, ControlFlow::Break(Ok(3)));
+    /// # }
+    /// ```
+    #[must_use]
+    #[track_caller]
+    pub fn map_ok<T2, F>(self, f: F) -> Handler<'a, Input, Result<T2, E>, Descr>
+    where
+        F: Fn(T) -> T2 + Send + Sync + 'a,
+        T2: 'a,
+    {
+        self.map_result(move |result| result.map(&f))
+    }
+
+    /// Transforms the `Err` value this handler breaks with.
+    ///
+    /// When this handler breaks with `Err(e)`, the returned handler breaks
+    /// with `Err(f(e))`; `Ok` values are passed through as they are. This is
+    /// [`Result::map_err`] at the handler level.
+    ///
+    /// Like [`Handler::map_ok`], the returned handler never executes its own
+    /// continuation.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use dptree::prelude::*;
+    ///
+    /// let handler: Handler<_, Result<i32, std::num::ParseIntError>> =
+    ///     dptree::endpoint(|s: &'static str| async move { s.parse::<i32>() });
+    /// let handler = handler.map_err(|e| format!("invalid number: {e}"));
+    ///
+    /// assert_eq!(
+    ///     handler.dispatch(dptree::deps!["abc"]).await,
+    ///     ControlFlow::Break(Err("invalid number: invalid digit found in string".to_owned()))
+    /// );
+    /// # }
+    /// ```
+    #[must_use]
+    #[track_caller]
+    pub fn map_err<E2, F>(self, f: F) -> Handler<'a, Input, Result<T, E2>, Descr>
+    where
+        F: Fn(E) -> E2 + Send + Sync + 'a,
+        E2: 'a,
+    {
+        self.map_result(move |result| result.map_err(&f))
+    }
+
+    #[track_caller]
+    fn map_result<Output2, F>(self, f: F) -> Handler<'a, Input, Output2, Descr>
+    where
+        F: Fn(Result<T, E>) -> Output2 + Send + Sync + 'a,
+        Output2: 'a,
+    {
+        let description = self.wrapper_description();
+        let f = Arc::new(f);
+
+        from_fn_with_description(description, move |event, _cont| {
+            let this = self.clone();
+            let f = Arc::clone(&f);
+
+            async move {
+                match this.dispatch(event).await {
+                    ControlFlow::Continue(event) => ControlFlow::Continue(event),
+                    ControlFlow::Break(result) => ControlFlow::Break(f(result)),
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::ParseIntError;
+
+    use super::*;
+    use crate::{deps, di::DependencyMap, help_inference, Handler};
+
+    fn contains<T: Send + Sync + 'static>() -> Handler<'static, DependencyMap, String> {
+        crate::from_fn(|deps: DependencyMap, cont| async move {
+            if deps.contains::<T>() {
+                cont(deps).await
+            } else {
+                ControlFlow::Continue(deps)
+            }
+        })
+    }
+
+    #[tokio::test]
+    async fn test_filter_map_result_insert() {
+        let handler = help_inference(filter_map_result_insert(|s: &'static str| s.parse::<i32>()))
+            .branch(contains::<i32>().endpoint(|x: i32| async move { format!("ok {x}") }))
+            .branch(
+                contains::<ParseIntError>()
+                    .endpoint(|e: ParseIntError| async move { format!("err {e}") }),
+            );
+
+        assert_eq!(handler.dispatch(deps!["42"]).await, ControlFlow::Break("ok 42".to_owned()));
+        assert_eq!(
+            handler.dispatch(deps!["abc"]).await,
+            ControlFlow::Break("err invalid digit found in string".to_owned())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_filter_map_result_insert_async() {
+        let handler =
+            help_inference(filter_map_result_insert_async(|| async { Err::<i32, _>("failure") }))
+                .endpoint(|e: &'static str| async move { e.len() });
+
+        assert_eq!(handler.dispatch(deps![]).await, ControlFlow::Break(7));
+    }
+
+    #[test]
+    fn test_filter_map_result_insert_description() {
+        use crate::description::Tree;
+
+        let handler: Handler<DependencyMap, (), Tree> =
+            filter_map_result_insert(|s: &'static str| s.parse::<u8>()).endpoint(|_: u8| async {});
+
+        // `u8` is absent on errors, so the endpoint's dependency is reported.
+        assert_eq!(handler.validate_deps(&deps!["1"]).unwrap_err()[0].type_name, "u8");
+        assert!(handler.description().provided.is_empty());
+    }
+
+    fn recover_negative(
+        proj: fn(i32) -> Result<u32, i32>,
+    ) -> Handler<'static, DependencyMap, String> {
+        filter_map_result_recover(proj, |x: i32| async move {
+            if x < -10 {
+                Some(format!("too small: {x}"))
+            } else {
+                None
+            }
+        })
+        .endpoint(|x: u32| async move { format!("ok {x}") })
+    }
+
+    #[tokio::test]
+    async fn test_filter_map_result_recover() {
+        let handler = help_inference(crate::entry())
+            .branch(recover_negative(|x| if x >= 0 { Ok(x as u32) } else { Err(x) }))
+            .branch(crate::endpoint(|x: i32| async move { format!("fallback {x}") }));
+
+        // `Ok`: the value is passed further.
+        assert_eq!(handler.dispatch(deps![5]).await, ControlFlow::Break("ok 5".to_owned()));
+        // `Err`, and the recovery breaks.
+        assert_eq!(
+            handler.dispatch(deps![-20]).await,
+            ControlFlow::Break("too small: -20".to_owned())
+        );
+        // `Err`, and the recovery continues.
+        assert_eq!(handler.dispatch(deps![-5]).await, ControlFlow::Break("fallback -5".to_owned()));
+    }
+
+    #[tokio::test]
+    async fn test_filter_map_result_recover_async() {
+        let handler = help_inference(filter_map_result_recover_async(
+            |s: &'static str| async move { s.parse::<i32>() },
+            |_: ParseIntError| async { None },
+        ))
+        .endpoint(|x: i32| async move { x });
+
+        assert_eq!(handler.dispatch(deps!["1"]).await, ControlFlow::Break(1));
+        assert_eq!(handler.dispatch(deps!["a"]).await, ControlFlow::Continue(deps!["a"]));
+    }
+
+    #[tokio::test]
+    async fn test_flatten_result() {
+        let handler: Handler<
+            'static,
+            DependencyMap,
+            Result<Result<i32, &'static str>, &'static str>,
+        > = crate::filter(|x: i32| x != 0).endpoint(|x: i32| async move {
+            match x {
+                1 => Ok(Ok(1)),
+                2 => Ok(Err("inner")),
+                _ => Err("outer"),
+            }
+        });
+        let handler = handler.flatten_result().chain(crate::endpoint(|| async { Err("chained") }));
+
+        assert_eq!(handler.dispatch(deps![1]).await, ControlFlow::Break(Ok(1)));
+        assert_eq!(handler.dispatch(deps![2]).await, ControlFlow::Break(Err("inner")));
+        assert_eq!(handler.dispatch(deps![3]).await, ControlFlow::Break(Err("outer")));
+        assert_eq!(handler.dispatch(deps![0]).await, ControlFlow::Continue(deps![0]));
+    }
+
+    #[tokio::test]
+    async fn test_flatten_result_passes_chained_outputs() {
+        let handler: Handler<
+            'static,
+            DependencyMap,
+            Result<Result<(), &'static str>, &'static str>,
+        > = crate::entry();
+        let handler = handler.flatten_result().chain(crate::endpoint(|| async { Err("chained") }));
+
+        assert_eq!(handler.dispatch(deps![]).await, ControlFlow::Break(Err("chained")));
+    }
+
+    #[tokio::test]
+    async fn test_map_ok_map_err() {
+        let handler: Handler<'static, DependencyMap, Result<i32, ParseIntError>> =
+            crate::filter(|s: &'static str| !s.is_empty())
+                .endpoint(|s: &'static str| async move { s.parse::<i32>() });
+
+        let ok = handler.clone().map_ok(|n| n * 2);
+        assert_eq!(ok.dispatch(deps!["21"]).await, ControlFlow::Break(Ok(42)));
+        assert!(matches!(ok.dispatch(deps!["a"]).await, ControlFlow::Break(Err(_))));
+
+        let err = handler.map_err(|e| e.to_string());
+        assert_eq!(err.dispatch(deps!["21"]).await, ControlFlow::Break(Ok(21)));
+        assert_eq!(
+            err.dispatch(deps!["a"]).await,
+            ControlFlow::Break(Err("invalid digit found in string".to_owned()))
+        );
+        assert_eq!(err.dispatch(deps![""]).await, ControlFlow::Continue(deps![""]));
+    }
+
+    #[tokio::test]
+    async fn test_map_ok_then_map_err() {
+        let handler: Handler<'static, DependencyMap, Result<i32, i32>> =
+            crate::endpoint(|x: i32| async move {
+                if x >= 0 {
+                    Ok(x)
+                } else {
+                    Err(x)
+                }
+            });
+        let handler = handler.map_ok(|x| x as u32).map_err(|x| x.to_string());
+
+        assert_eq!(handler.dispatch(deps![1]).await, ControlFlow::Break(Ok(1u32)));
+        assert_eq!(handler.dispatch(deps![-1]).await, ControlFlow::Break(Err("-1".to_owned())));
+    }
+
+    #[cfg(feature = "std")]
+    #[tokio::test]
+    async fn test_filter_map_collecting_errors() {
+        let sink = ErrorSink::new();
+        let handler = help_inference(crate::entry())
+            .chain(filter_map_collecting_errors(|x: &'static str| x.parse::<u8>(), sink.clone()))
+            .chain(filter_map_collecting_errors_async(
+                |y: &'static str| async move { y.parse::<i32>() },
+                sink.clone(),
+            ))
+            .chain(crate::from_fn(|deps: DependencyMap, _cont| async move {
+                ControlFlow::Break((deps.contains::<u8>(), deps.contains::<i32>()))
+            }));
+
+        assert_eq!(handler.dispatch(deps!["1"]).await, ControlFlow::Break((true, true)));
+        assert!(sink.is_empty());
+
+        // The chain proceeds after each error.
+        assert_eq!(handler.dispatch(deps!["-1"]).await, ControlFlow::Break((false, true)));
+        assert_eq!(handler.dispatch(deps!["x"]).await, ControlFlow::Break((false, false)));
+        assert_eq!(sink.len(), 3);
+
+        let errors: Vec<_> = sink.take().iter().map(ToString::to_string).collect();
+        assert_eq!(
+            errors,
+            [
+                "invalid digit found in string",
+                "invalid digit found in string",
+                "invalid digit found in string"
+            ]
+        );
+        assert!(sink.is_empty());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_filter_map_collecting_errors_description() {
+        use crate::description::Tree;
+
+        let handler: Handler<DependencyMap, (), Tree> =
+            filter_map_collecting_errors(|x: &'static str| x.parse::<u8>(), ErrorSink::new())
+                .endpoint(|_: u8| async {});
+
+        // `u8` may be absent, so the endpoint's dependency is reported.
+        assert_eq!(handler.validate_deps(&deps!["1"]).unwrap_err()[0].type_name, "u8");
+        assert!(handler.description().provided.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_filter_map_logged() {
+        use std::sync::Mutex;
+
+        let reasons = Arc::new(Mutex::new(Vec::new()));
+
+        let handler = help_inference(filter_map_logged(|s: &'static str| s.parse::<i32>(), {
+            let reasons = Arc::clone(&reasons);
+            move |e: ParseIntError| reasons.lock().unwrap().push(e.to_string())
+        }))
+        .endpoint(|x: i32| async move { x });
+
+        assert_eq!(handler.dispatch(deps!["42"]).await, ControlFlow::Break(42));
+        assert!(reasons.lock().unwrap().is_empty());
+
+        assert_eq!(handler.dispatch(deps!["abc"]).await, ControlFlow::Continue(deps!["abc"]));
+        assert_eq!(*reasons.lock().unwrap(), ["invalid digit found in string"]);
+    }
+
+    #[tokio::test]
+    async fn test_filter_map_logged_async() {
+        use std::sync::Mutex;
+
+        let reasons = Arc::new(Mutex::new(Vec::new()));
+
+        let handler = help_inference(filter_map_logged_async(
+            |x: i32| async move {
+                if x > 0 {
+                    Ok(x as u32)
+                } else {
+                    Err("not positive")
+                }
+            },
+            {
+                let reasons = Arc::clone(&reasons);
+                move |reason| reasons.lock().unwrap().push(reason)
+            },
+        ))
+        .endpoint(|x: u32| async move { x });
+
+        assert_eq!(handler.dispatch(deps![1]).await, ControlFlow::Break(1));
+        assert_eq!(handler.dispatch(deps![-1]).await, ControlFlow::Continue(deps![-1]));
+        assert_eq!(*reasons.lock().unwrap(), ["not positive"]);
+    }
+}
@@ -0,0 +1,296 @@
+use crate::{
+    di::{Asyncify, Injectable},
+    from_fn_with_description, Handler, HandlerDescription,
+};
+use std::{collections::HashMap, hash::Hash, mem::Discriminant, ops::ControlFlow, sync::Arc};
+
+impl<'a, Input, Output, Descr> Handler<'a, Input, Output, Descr>
+where
+    Input: Send + 'a,
+    Output: Send + 'a,
+    Descr: HandlerDescription,
+{
+    /// Branch this handler with a handler selected by a key.
+    ///
+    /// `key_fn` has an access to all values that are stored in the input
+    /// container. The handler corresponding to the key it returns is looked up
+    /// in `routes`; if there is no such handler, `default` is used instead.
+    /// The selected handler is then executed like a handler passed to
+    /// [`Handler::branch`]: if it continues, so does the execution after it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use dptree::prelude::*;
+    /// use std::collections::HashMap;
+    ///
+    /// let mut commands: HashMap<&str, Handler<_, _>> = HashMap::new();
+    /// commands.insert("/ping", dptree::endpoint(|| async { "pong".to_owned() }));
+    /// commands.insert("/echo", dptree::endpoint(|text: String| async move { text }));
+    ///
+    /// let router: Handler<_, _> = dptree::entry().branch_from_map(
+    ///     |cmd: &'static str| cmd,
+    ///     commands,
+    ///     dptree::endpoint(|| async { "unknown command".to_owned() }),
+    /// );
+    ///
+    /// let deps = |cmd: &'static str| dptree::deps![cmd, "hello".to_owned()];
+    /// assert_eq!(router.dispatch(deps("/ping")).await, ControlFlow::Break("pong".to_owned()));
+    /// assert_eq!(router.dispatch(deps("/echo")).await, ControlFlow::Break("hello".to_owned()));
+    /// assert_eq!(
+    ///     router.dispatch(deps("/help")).await,
+    ///     ControlFlow::Break("unknown command".to_owned())
+    /// );
+    /// # }
+    /// ```
+    #[must_use]
+    #[track_caller]
+    pub fn branch_from_map<KeyFn, Key, Args>(
+        self,
+        key_fn: KeyFn,
+        routes: HashMap<Key, Self>,
+        default: Self,
+    ) -> Self
+    where
+        Asyncify<KeyFn>: Injectable<Input, Key, Args> + Send + Sync + 'a,
+        Key: Eq + Hash + Send + Sync + 'a,
+    {
+        let description = routes
+            .values()
+            .chain([&default])
+            .fold(Descr::entry(), |acc, route| acc.merge_branch(route.description()));
+        let key_fn = Arc::new(Asyncify(key_fn));
+        let routes = Arc::new(routes);
+
+        let router = from_fn_with_description(description, move |event, cont| {
+            let key_fn = Arc::clone(&key_fn);
+            let routes = Arc::clone(&routes);
+            let default = default.clone();
+
+            async move {
+                let key = {
+                    let key_fn = key_fn.inject(&event);
+                    key_fn().await
+                };
+                let route = routes.get(&key).cloned().unwrap_or(default);
+
+                match route.dispatch(event).await {
+                    ControlFlow::Continue(event) => cont(event).await,
+                    done => done,
+                }
+            }
+        });
+
+        self.chain(router)
+    }
+
+    /// Returns a handler that post-processes the output of this handler by a
+    /// handler selected by the output's enum variant.
+    ///
+    /// The returned handler dispatches this handler with its input. If this
+    /// handler breaks with an output, the arm corresponding to its
+    /// [`Discriminant`] is looked up in `arms` and dispatched with this output
+    /// as its input, as in [`Handler::pipe`]; the returned handler then breaks
+    /// with the output of the arm. If this handler continues, there is no arm
+    /// for the variant, or the arm continues, the returned handler returns
+    /// [`ControlFlow::Continue`] with the original input.
+    ///
+    /// Like [`Handler::pipe`], the returned handler never executes its own
+    /// continuation.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use dptree::prelude::*;
+    /// use std::{collections::HashMap, mem};
+    ///
+    /// enum Reply {
+    ///     Text(String),
+    ///     Silent,
+    /// }
+    ///
+    /// let mut arms: HashMap<_, Handler<_, String>> = HashMap::new();
+    /// arms.insert(
+    ///     mem::discriminant(&Reply::Text(String::new())),
+    ///     dptree::from_fn(|reply, _cont| async move {
+    ///         match reply {
+    ///             Reply::Text(text) => ControlFlow::Break(format!("sent: {text}")),
+    ///             reply => ControlFlow::Continue(reply),
+    ///         }
+    ///     }),
+    /// );
+    /// arms.insert(
+    ///     mem::discriminant(&Reply::Silent),
+    ///     dptree::from_fn(|_, _cont| async { ControlFlow::Break("nothing sent".to_owned()) }),
+    /// );
+    ///
+    /// let handler = dptree::endpoint(|text: &'static str| async move {
+    ///     if text.is_empty() {
+    ///         Reply::Silent
+    ///     } else {
+    ///         Reply::Text(text.to_owned())
+    ///     }
+    /// })
+    /// .match_output(arms);
+    ///
+    /// assert_eq!(
+    ///     handler.dispatch(dptree::deps!["hi"]).await,
+    ///     ControlFlow::Break("sent: hi".to_owned())
+    /// );
+    /// assert_eq!(
+    ///     handler.dispatch(dptree::deps![""]).await,
+    ///     ControlFlow::Break("nothing sent".to_owned())
+    /// );
+    /// # }
+    /// ```
+    #[must_use]
+    #[track_caller]
+    pub fn match_output<FinalOutput>(
+        self,
+        arms: HashMap<Discriminant<Output>, Handler<'a, Output, FinalOutput, Descr>>,
+    ) -> Handler<'a, Input, FinalOutput, Descr>
+    where
+        Input: Clone,
+        FinalOutput: 'a,
+    {
+        let arms_description =
+            arms.values().fold(Descr::entry(), |acc, arm| acc.merge_branch(arm.description()));
+        let description = self.description().merge_chain(&arms_description);
+        let arms = Arc::new(arms);
+
+        from_fn_with_description(description, move |event: Input, _cont| {
+            let this = self.clone();
+            let arms = Arc::clone(&arms);
+
+            async move {
+                let output = match this.dispatch(event.clone()).await {
+                    ControlFlow::Break(output) => output,
+                    ControlFlow::Continue(_) => return ControlFlow::Continue(event),
+                };
+                let arm = match arms.get(&std::mem::discriminant(&output)) {
+                    Some(arm) => arm.clone(),
+                    None => return ControlFlow::Continue(event),
+                };
+
+                match arm.dispatch(output).await {
+                    ControlFlow::Break(result) => ControlFlow::Break(result),
+                    ControlFlow::Continue(_) => ControlFlow::Continue(event),
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{deps, description::Tree, di::DependencyMap, help_inference};
+
+    #[tokio::test]
+    async fn test_branch_from_map() {
+        let mut routes = HashMap::new();
+        routes.insert(1, crate::endpoint(|| async { "one" }));
+        routes.insert(2, crate::filter(|x: i32| x > 0).endpoint(|| async { "two" }));
+
+        let handler = help_inference(crate::entry()).branch_from_map(
+            |key: u8| key,
+            routes,
+            crate::endpoint(|| async { "default" }),
+        );
+
+        assert_eq!(handler.dispatch(deps![1u8]).await, ControlFlow::Break("one"));
+        assert_eq!(handler.dispatch(deps![2u8, 1]).await, ControlFlow::Break("two"));
+        assert_eq!(handler.dispatch(deps![3u8]).await, ControlFlow::Break("default"));
+
+        // The selected handler continues, so does the router.
+        assert!(matches!(handler.dispatch(deps![2u8, -1]).await, ControlFlow::Continue(_)));
+    }
+
+    #[tokio::test]
+    async fn test_branch_from_map_continue() {
+        let handler = help_inference(crate::entry())
+            .branch_from_map(|| (), HashMap::new(), crate::filter(|| false))
+            .endpoint(|| async { "after" });
+
+        assert_eq!(handler.dispatch(deps![]).await, ControlFlow::Break("after"));
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    enum Reply {
+        Text(&'static str),
+        Number(i32),
+    }
+
+    fn match_output_handler() -> Handler<'static, DependencyMap, String> {
+        let mut arms = HashMap::new();
+        arms.insert(
+            std::mem::discriminant(&Reply::Text("")),
+            crate::from_fn(|reply, _cont| async move {
+                match reply {
+                    Reply::Text(text) => ControlFlow::Break(text.to_uppercase()),
+                    reply => ControlFlow::Continue(reply),
+                }
+            }),
+        );
+        arms.insert(
+            std::mem::discriminant(&Reply::Number(0)),
+            crate::from_fn(|reply, _cont| async move {
+                match reply {
+                    Reply::Number(x) if x >= 0 => ControlFlow::Break(format!("#{x}")),
+                    reply => ControlFlow::Continue(reply),
+                }
+            }),
+        );
+
+        crate::entry()
+            .branch(
+                crate::filter(|x: i32| x != 0).endpoint(|x: i32| async move { Reply::Number(x) }),
+            )
+            .branch(crate::endpoint(|s: &'static str| async move { Reply::Text(s) }))
+            .match_output(arms)
+    }
+
+    #[tokio::test]
+    async fn test_match_output() {
+        let handler = match_output_handler();
+
+        assert_eq!(handler.dispatch(deps![0, "abc"]).await, ControlFlow::Break("ABC".to_owned()));
+        assert_eq!(handler.dispatch(deps![42, "abc"]).await, ControlFlow::Break("#42".to_owned()));
+    }
+
+    #[tokio::test]
+    async fn test_match_output_continue() {
+        let handler = match_output_handler();
+
+        // The arm continues.
+        assert_eq!(
+            handler.dispatch(deps![-1, "abc"]).await,
+            ControlFlow::Continue(deps![-1, "abc"])
+        );
+
+        // There is no arm for the variant.
+        let handler = help_inference(crate::endpoint(|| async { Reply::Number(1) }))
+            .match_output::<()>(HashMap::new());
+        assert_eq!(handler.dispatch(deps![]).await, ControlFlow::Continue(deps![]));
+    }
+
+    #[test]
+    fn test_branch_from_map_description() {
+        use crate::description::NodeKind;
+
+        let mut routes = HashMap::new();
+        routes.insert(1, crate::endpoint(|| async {}));
+
+        let handler: Handler<DependencyMap, (), Tree> =
+            crate::filter(|| true).branch_from_map(|| 1, routes, crate::endpoint(|| async {}));
+
+        let description = handler.description();
+        assert_eq!(description.kind, NodeKind::Chain);
+        assert_eq!(description.children[1].kind, NodeKind::Branch);
+        assert_eq!(description.children[1].children.len(), 2);
+    }
+}
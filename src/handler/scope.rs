@@ -0,0 +1,334 @@
+use crate::{di::DependencyMap, from_fn_with_description, Handler, HandlerDescription};
+use alloc::sync::Arc;
+use core::ops::ControlFlow;
+
+impl<'a, Output, Descr> Handler<'a, DependencyMap, Output, Descr>
+where
+    Output: 'a,
+    Descr: HandlerDescription,
+{
+    /// Makes `value` available to this handler only.
+    ///
+    /// Every time the execution reaches the returned handler, a clone of
+    /// `value` is added to the container before executing this handler. When
+    /// this handler passes the execution further, the value is taken out
+    /// again, so the handlers after it see the value of type `T` that was in
+    /// the container before, if any. If the execution continues, the original
+    /// container is returned.
+    ///
+    /// This is a shorthand for a [`map`](crate::map) returning a constant,
+    /// without leaking the constant outside of the subtree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use dptree::prelude::*;
+    ///
+    /// let handler: Handler<_, _> = dptree::entry()
+    ///     .branch(
+    ///         dptree::filter(|lang: &'static str| lang == "de")
+    ///             .endpoint(|greeting: String| async move { greeting })
+    ///             .with_value("Hallo".to_owned()),
+    ///     )
+    ///     .branch(dptree::endpoint(|greeting: String| async move { greeting }));
+    ///
+    /// let deps = dptree::deps!["Hello".to_owned()];
+    ///
+    /// assert_eq!(
+    ///     handler.dispatch(deps.clone().with("de")).await,
+    ///     ControlFlow::Break("Hallo".to_owned())
+    /// );
+    /// assert_eq!(handler.dispatch(deps.with("en")).await, ControlFlow::Break("Hello".to_owned()));
+    /// # }
+    /// ```
+    #[must_use]
+    #[track_caller]
+    pub fn with_value<T>(self, value: T) -> Self
+    where
+        T: Clone + Send + Sync + 'static,
+    {
+        let description = self.wrapper_description();
+
+        from_fn_with_description(description, move |container: DependencyMap, cont| {
+            let this = self.clone();
+            let value = value.clone();
+
+            async move {
+                let mut intermediate = container.clone();
+                intermediate.insert(value);
+
+                let outer = container.clone();
+                let result = this
+                    .execute(intermediate, move |mut event: DependencyMap| {
+                        event.restore::<T>(&outer);
+                        cont(event)
+                    })
+                    .await;
+
+                match result {
+                    ControlFlow::Continue(_) => ControlFlow::Continue(container),
+                    ControlFlow::Break(result) => ControlFlow::Break(result),
+                }
+            }
+        })
+    }
+
+    /// Replaces the whole container for this handler only.
+    ///
+    /// Every time the execution reaches the returned handler, this handler is
+    /// executed with the container returned by `f`, which receives a copy of
+    /// the original container. This allows swapping in a different set of
+    /// dependencies for a subtree, e.g., mocks in tests or a tenant-specific
+    /// container.
+    ///
+    /// The replacement does not leak outside of the subtree:
+    ///
+    ///  - When this handler passes the execution further, `restore` is called
+    ///    with a copy of the original container and the container passed by
+    ///    this handler, and the rest of the chain is executed with its result.
+    ///    Thus, `restore` decides which of the values added inside the subtree,
+    ///    e.g., by [`map`](crate::map), are visible after it; returning the
+    ///    original container discards all of them.
+    ///  - If the execution continues, the original container is returned, as
+    ///    with any other handler, so that the next branches see it unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use dptree::prelude::*;
+    ///
+    /// let greeting = dptree::endpoint(|name: &'static str| async move { format!("Hello, {name}!") });
+    ///
+    /// let handler: Handler<_, _> =
+    ///     dptree::entry()
+    ///         .branch(dptree::filter(|anonymous: bool| anonymous).chain(
+    ///             greeting.clone().map_container(|deps| deps.with("stranger"), |outer, _| outer),
+    ///         ))
+    ///         .branch(greeting);
+    ///
+    /// let deps = dptree::deps!["Alice"];
+    ///
+    /// assert_eq!(
+    ///     handler.dispatch(deps.clone().with(true)).await,
+    ///     ControlFlow::Break("Hello, stranger!".to_owned())
+    /// );
+    /// assert_eq!(
+    ///     handler.dispatch(deps.with(false)).await,
+    ///     ControlFlow::Break("Hello, Alice!".to_owned())
+    /// );
+    /// # }
+    /// ```
+    #[must_use]
+    #[track_caller]
+    pub fn map_container<F, R>(self, f: F, restore: R) -> Self
+    where
+        F: Fn(DependencyMap) -> DependencyMap + Send + Sync + 'a,
+        R: Fn(DependencyMap, DependencyMap) -> DependencyMap + Send + Sync + 'a,
+    {
+        let description = self.wrapper_description();
+        let f = Arc::new(f);
+        let restore = Arc::new(restore);
+
+        from_fn_with_description(description, move |container: DependencyMap, cont| {
+            let this = self.clone();
+            let intermediate = f(container.clone());
+            let restore = Arc::clone(&restore);
+
+            async move {
+                let outer = container.clone();
+                let result = this
+                    .execute(intermediate, move |event: DependencyMap| cont(restore(outer, event)))
+                    .await;
+
+                match result {
+                    ControlFlow::Continue(_) => ControlFlow::Continue(container),
+                    ControlFlow::Break(result) => ControlFlow::Break(result),
+                }
+            }
+        })
+    }
+}
+
+impl<Output, Descr> Handler<'static, DependencyMap, Output, Descr>
+where
+    Output: 'static,
+    Descr: HandlerDescription,
+{
+    /// Makes a clone of this handler available to itself.
+    ///
+    /// This is [`Handler::with_value`] with a clone of this handler as the
+    /// value, so that the handlers inside can request a
+    /// `Handler<'static, DependencyMap, Output, Descr>` (which is cheap to
+    /// clone) and re-dispatch a sub-input through the whole tree, e.g., for
+    /// nested commands.
+    ///
+    /// The reference is only available as long as the sub-input is derived
+    /// from the current container, e.g., with [`DependencyMap::with`]. Beware
+    /// of infinite recursion: make sure that the sub-input is "smaller" than
+    /// the input, or limit the depth of the recursion with a counter in the
+    /// container.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use dptree::{di::WithInput, prelude::*};
+    ///
+    /// type Root = Handler<'static, DependencyMap, String>;
+    ///
+    /// let handler: Root = dptree::entry()
+    ///     .branch(dptree::filter_map(|s: &'static str| s.strip_prefix("/loud ")).endpoint(WithInput(
+    ///         |deps: &DependencyMap, rest: &'static str, root: Root| {
+    ///             let sub_input = deps.clone().with(rest);
+    ///             async move {
+    ///                 match root.dispatch(sub_input).await {
+    ///                     ControlFlow::Break(reply) => reply.to_uppercase(),
+    ///                     ControlFlow::Continue(_) => "unknown command".to_owned(),
+    ///                 }
+    ///             }
+    ///         },
+    ///     )))
+    ///     .branch(dptree::filter(|s: &'static str| s == "/hi").endpoint(|| async { "hi".to_owned() }))
+    ///     .with_self_reference();
+    ///
+    /// assert_eq!(
+    ///     handler.dispatch(dptree::deps!["/loud /hi"]).await,
+    ///     ControlFlow::Break("HI".to_owned())
+    /// );
+    /// # }
+    /// ```
+    #[must_use]
+    #[track_caller]
+    pub fn with_self_reference(self) -> Self {
+        let root = self.clone();
+        self.with_value(root)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{deps, di::WithInput};
+
+    #[tokio::test]
+    async fn test_with_value() {
+        let handler: Handler<DependencyMap, bool> = crate::entry()
+            .inspect(|x: i32| assert_eq!(x, 1))
+            .with_value(1i32)
+            .chain(crate::from_fn(|deps: DependencyMap, _cont| async move {
+                ControlFlow::Break(deps.contains::<i32>())
+            }));
+
+        // The value is injectable inside and absent outside the subtree.
+        assert_eq!(handler.dispatch(deps![]).await, ControlFlow::Break(false));
+    }
+
+    #[tokio::test]
+    async fn test_with_value_restores_previous() {
+        let handler: Handler<DependencyMap, (i32, i32)> = crate::entry()
+            .map(|x: i32| x * 10)
+            .with_value(2i32)
+            .chain(crate::endpoint(|x: i32| async move { (x, 0) }));
+
+        // `map` replaces the scoped value, but the outer value is restored.
+        assert_eq!(handler.dispatch(deps![1i32]).await, ControlFlow::Break((1, 0)));
+    }
+
+    #[tokio::test]
+    async fn test_with_value_continue() {
+        let handler: Handler<DependencyMap, ()> =
+            crate::filter(|x: i32| x > 0).with_value(0i32).endpoint(|| async {});
+
+        assert!(
+            matches!(handler.dispatch(deps![]).await, ControlFlow::Continue(deps) if !deps.contains::<i32>())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_map_container() {
+        let subtree: Handler<DependencyMap, (&str, bool)> = crate::entry()
+            .filter(|db: &'static str| db == "mock")
+            .map(|| 1u8)
+            .map_container(|deps| deps.with("mock"), |outer, _| outer);
+
+        let handler = subtree.chain(crate::from_fn(|deps: DependencyMap, _cont| async move {
+            ControlFlow::Break((*deps.try_get::<&str>().unwrap(), deps.contains::<u8>()))
+        }));
+
+        // The filter passes thanks to the override, which is not visible after
+        // the subtree, and the values added inside are discarded by `restore`.
+        assert_eq!(
+            handler.dispatch(deps!["postgres"]).await,
+            ControlFlow::Break(("postgres", false))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_map_container_restore() {
+        let handler: Handler<DependencyMap, (i32, u8)> = crate::entry()
+            .map(|x: i32| x as u8)
+            .map_container(
+                |deps| deps.with(10i32),
+                |mut outer, inner| {
+                    outer.insert(*inner.try_get::<u8>().unwrap());
+                    outer
+                },
+            )
+            .endpoint(|x: i32, y: u8| async move { (x, y) });
+
+        assert_eq!(handler.dispatch(deps![1i32]).await, ControlFlow::Break((1, 10)));
+    }
+
+    #[tokio::test]
+    async fn test_map_container_continue() {
+        let handler: Handler<DependencyMap, ()> = crate::filter(|x: i32| x > 0)
+            .map_container(|deps| deps.with(0i32).with("inner"), |outer, _| outer)
+            .endpoint(|| async {});
+
+        // The original container is returned, without the inner values.
+        assert!(matches!(
+            handler.dispatch(deps![1i32]).await,
+            ControlFlow::Continue(deps)
+                if !deps.contains::<&str>() && deps.try_get::<i32>().as_deref() == Ok(&1)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_with_self_reference() {
+        type Root = Handler<'static, DependencyMap, Vec<&'static str>>;
+
+        let handler: Root = crate::entry()
+            .branch(crate::filter_map(|s: &'static str| s.strip_prefix("/twice ")).endpoint(
+                WithInput(|deps: &DependencyMap, rest: &'static str, root: Root| {
+                    let sub_input = deps.clone().with(rest);
+                    async move {
+                        let mut replies = Vec::new();
+                        for _ in 0..2 {
+                            if let ControlFlow::Break(reply) =
+                                root.dispatch(sub_input.clone()).await
+                            {
+                                replies.extend(reply);
+                            }
+                        }
+                        replies
+                    }
+                }),
+            ))
+            .branch(
+                crate::filter(|s: &'static str| s == "/ping").endpoint(|| async { vec!["pong"] }),
+            )
+            .with_self_reference();
+
+        assert_eq!(handler.dispatch(deps!["/ping"]).await, ControlFlow::Break(vec!["pong"]));
+        assert_eq!(
+            handler.dispatch(deps!["/twice /twice /ping"]).await,
+            ControlFlow::Break(vec!["pong"; 4])
+        );
+        assert_eq!(handler.dispatch(deps!["/twice /stop"]).await, ControlFlow::Break(vec![]));
+    }
+}
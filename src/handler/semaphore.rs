@@ -0,0 +1,114 @@
+use crate::{from_fn_with_description, Handler, HandlerDescription};
+use alloc::sync::Arc;
+use core::ops::ControlFlow;
+use tokio::sync::Semaphore;
+
+impl<'a, Input, Output, Descr> Handler<'a, Input, Output, Descr>
+where
+    Input: Send + 'a,
+    Output: 'a,
+    Descr: HandlerDescription,
+{
+    /// Returns a handler that executes this one only while holding a permit
+    /// from `semaphore`.
+    ///
+    /// This limits the number of concurrent executions of this handler to the
+    /// number of permits. A permit is acquired before this handler is executed
+    /// and released after it completes along with the rest of the chain. If
+    /// `semaphore` is closed, the returned handler returns
+    /// [`ControlFlow::Continue`] without executing this handler.
+    ///
+    /// The same semaphore can be shared by several handlers to limit their
+    /// total concurrency.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use dptree::prelude::*;
+    /// use std::sync::Arc;
+    /// use tokio::sync::Semaphore;
+    ///
+    /// // At most 4 requests are handled at the same time.
+    /// let handler: Handler<_, _> =
+    ///     dptree::endpoint(|| async { "handled" }).with_semaphore(Arc::new(Semaphore::new(4)));
+    ///
+    /// assert_eq!(handler.dispatch(dptree::deps![]).await, ControlFlow::Break("handled"));
+    /// # }
+    /// ```
+    #[must_use]
+    #[track_caller]
+    pub fn with_semaphore(self, semaphore: Arc<Semaphore>) -> Self {
+        let description = self.wrapper_description();
+
+        from_fn_with_description(description, move |event, cont| {
+            let this = self.clone();
+            let semaphore = Arc::clone(&semaphore);
+
+            async move {
+                match semaphore.acquire().await {
+                    Ok(_permit) => this.execute(event, cont).await,
+                    Err(_closed) => ControlFlow::Continue(event),
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::atomic::{AtomicUsize, Ordering},
+        time::Duration,
+    };
+
+    use super::*;
+    use crate::{deps, help_inference};
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_with_semaphore() {
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        let max_in_flight = Arc::new(AtomicUsize::new(0));
+
+        let handler = help_inference(crate::endpoint({
+            let in_flight = Arc::clone(&in_flight);
+            let max_in_flight = Arc::clone(&max_in_flight);
+            move || {
+                let in_flight = Arc::clone(&in_flight);
+                let max_in_flight = Arc::clone(&max_in_flight);
+                async move {
+                    let now = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                    max_in_flight.fetch_max(now, Ordering::SeqCst);
+                    tokio::time::sleep(Duration::from_millis(10)).await;
+                    in_flight.fetch_sub(1, Ordering::SeqCst);
+                }
+            }
+        }))
+        .with_semaphore(Arc::new(Semaphore::new(3)));
+
+        let tasks: Vec<_> = (0..20)
+            .map(|_| {
+                let handler = handler.clone();
+                tokio::spawn(async move { handler.dispatch(deps![]).await })
+            })
+            .collect();
+
+        for task in tasks {
+            assert_eq!(task.await.unwrap(), ControlFlow::Break(()));
+        }
+
+        assert!(max_in_flight.load(Ordering::SeqCst) <= 3);
+    }
+
+    #[tokio::test]
+    async fn test_with_semaphore_closed() {
+        let semaphore = Arc::new(Semaphore::new(1));
+        let handler = help_inference(crate::endpoint(|| async { "handled" }))
+            .with_semaphore(Arc::clone(&semaphore));
+
+        semaphore.close();
+
+        assert_eq!(handler.dispatch(deps![]).await, ControlFlow::Continue(deps![]));
+    }
+}
@@ -0,0 +1,156 @@
+use crate::{from_fn_with_description, Handler, HandlerDescription};
+use alloc::sync::Arc;
+use core::ops::ControlFlow;
+
+impl<'a, Input, Output, Descr> Handler<'a, Input, Output, Descr>
+where
+    Input: Send + 'a,
+    Output: 'a,
+    Descr: HandlerDescription,
+{
+    /// Returns a handler that breaks only with outputs satisfying `pred`.
+    ///
+    /// If this handler breaks with an output for which `pred` returns `true`,
+    /// the returned handler breaks with this output. Otherwise, the output is
+    /// dropped and the returned handler continues with a copy of the original
+    /// input, so that the next branches can try to handle it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use dptree::prelude::*;
+    ///
+    /// let handler: Handler<_, _> = dptree::entry()
+    ///     .branch(
+    ///         dptree::endpoint(|s: &'static str| async move { s.parse::<i32>() })
+    ///             .short_circuit_on(|result| result.is_ok()),
+    ///     )
+    ///     .branch(dptree::endpoint(|| async { Ok(0) }));
+    ///
+    /// assert_eq!(handler.dispatch(dptree::deps!["42"]).await, ControlFlow::Break(Ok(42)));
+    /// assert_eq!(handler.dispatch(dptree::deps!["abc"]).await, ControlFlow::Break(Ok(0)));
+    /// # }
+    /// ```
+    #[must_use]
+    #[track_caller]
+    pub fn short_circuit_on<Pred>(self, pred: Pred) -> Self
+    where
+        Input: Clone,
+        Pred: Fn(&Output) -> bool + Send + Sync + 'a,
+    {
+        let pred = Arc::new(pred);
+        let description = self.wrapper_description();
+
+        from_fn_with_description(description, move |event: Input, cont| {
+            let this = self.clone();
+            let pred = Arc::clone(&pred);
+
+            async move {
+                match this.execute(event.clone(), cont).await {
+                    ControlFlow::Break(output) if !pred(&output) => ControlFlow::Continue(event),
+                    result => result,
+                }
+            }
+        })
+    }
+
+    /// Returns a handler that always breaks, with `default` if this handler
+    /// continues.
+    ///
+    /// This is the simplest way to make sure that a handler tree always
+    /// produces an output: every [`ControlFlow::Continue`] of this handler
+    /// (including the ones returned by its continuation) is replaced with
+    /// [`ControlFlow::Break`] of a copy of `default`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use dptree::prelude::*;
+    ///
+    /// let handler: Handler<_, _> = dptree::filter(|s: &'static str| s == "/start")
+    ///     .endpoint(|| async { "Welcome!" })
+    ///     .or_default("Unknown command");
+    ///
+    /// assert_eq!(handler.dispatch(dptree::deps!["/start"]).await, ControlFlow::Break("Welcome!"));
+    /// assert_eq!(
+    ///     handler.dispatch(dptree::deps!["/stop"]).await,
+    ///     ControlFlow::Break("Unknown command")
+    /// );
+    /// # }
+    /// ```
+    #[must_use]
+    #[track_caller]
+    pub fn or_default(self, default: Output) -> Self
+    where
+        Output: Clone + Send + Sync,
+    {
+        let description = self.breaking_wrapper_description();
+
+        from_fn_with_description(description, move |event: Input, cont| {
+            let this = self.clone();
+            let default = default.clone();
+
+            async move {
+                match this.execute(event, cont).await {
+                    ControlFlow::Continue(_) => ControlFlow::Break(default),
+                    result => result,
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{deps, help_inference};
+
+    #[tokio::test]
+    async fn test_short_circuit_on() {
+        let handler = help_inference(crate::entry())
+            .branch(crate::endpoint(|x: i32| async move { x * 2 }).short_circuit_on(|x| *x > 10))
+            .branch(crate::endpoint(|| async { 0 }));
+
+        assert_eq!(handler.dispatch(deps![6]).await, ControlFlow::Break(12));
+        assert_eq!(handler.dispatch(deps![5]).await, ControlFlow::Break(0));
+    }
+
+    #[tokio::test]
+    async fn test_short_circuit_on_continue() {
+        let handler = help_inference(crate::filter(|x: i32| x > 0))
+            .endpoint(|| async { "positive" })
+            .short_circuit_on(|_| true);
+
+        assert_eq!(handler.dispatch(deps![1]).await, ControlFlow::Break("positive"));
+        assert_eq!(handler.dispatch(deps![-1]).await, ControlFlow::Continue(deps![-1]));
+    }
+
+    #[tokio::test]
+    async fn test_or_default() {
+        let handler = help_inference(crate::entry())
+            .branch(crate::filter(|x: i32| x > 0).endpoint(|| async { "positive" }))
+            .branch(crate::filter(|x: i32| x < 0).endpoint(|| async { "negative" }))
+            .or_default("zero");
+
+        assert_eq!(handler.dispatch(deps![1]).await, ControlFlow::Break("positive"));
+        assert_eq!(handler.dispatch(deps![-1]).await, ControlFlow::Break("negative"));
+        assert_eq!(handler.dispatch(deps![0]).await, ControlFlow::Break("zero"));
+    }
+
+    #[tokio::test]
+    async fn test_or_default_continuation() {
+        let handler = help_inference(crate::filter(|x: i32| x > 0))
+            .or_default("default")
+            .chain(crate::filter(|x: i32| x > 1).endpoint(|| async { "greater than one" }));
+
+        assert_eq!(handler.dispatch(deps![2]).await, ControlFlow::Break("greater than one"));
+        // The continuation continues.
+        assert_eq!(handler.dispatch(deps![1]).await, ControlFlow::Break("default"));
+        // This handler continues.
+        assert_eq!(handler.dispatch(deps![0]).await, ControlFlow::Break("default"));
+    }
+}
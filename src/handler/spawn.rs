@@ -0,0 +1,144 @@
+use crate::{from_fn_with_description, Handler, HandlerDescription};
+
+impl<'a, Input, Output, Descr> Handler<'a, Input, Output, Descr>
+where
+    Input: Send + 'a,
+    Output: 'a,
+    Descr: HandlerDescription,
+{
+    /// Chain this handler with a handler that dispatches `inner` on a spawned
+    /// task.
+    ///
+    /// The returned handler executes this one; when it passes the input
+    /// further, `inner` is dispatched with a clone of the input on a new task
+    /// spawned by [`tokio::spawn`], and the rest of the chain is executed
+    /// immediately, without awaiting `inner`. The result of `inner` is
+    /// discarded, and so is its panic, if any. This is useful for
+    /// non-critical side effects, such as analytics, that should not delay
+    /// the response.
+    ///
+    /// Since `inner` outlives the dispatch, the input has to be
+    /// `Clone + 'static`. The returned handler panics if it is executed
+    /// outside of a Tokio runtime.
+    ///
+    /// `inner` never affects the result of the dispatch, so it is described
+    /// as a single [`HandlerDescription::inspect`] node rather than as a part
+    /// of the tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use dptree::prelude::*;
+    ///
+    /// let analytics: Handler<_, ()> = dptree::endpoint(|user: u64| async move {
+    ///     println!("user {user} has sent a message");
+    /// });
+    ///
+    /// let handler: Handler<_, _> =
+    ///     dptree::entry().spawn_detached(analytics).endpoint(|| async { "reply" });
+    ///
+    /// assert_eq!(handler.dispatch(dptree::deps![42u64]).await, ControlFlow::Break("reply"));
+    /// # }
+    /// ```
+    #[must_use]
+    #[track_caller]
+    pub fn spawn_detached<InnerOutput>(
+        self,
+        inner: Handler<'static, Input, InnerOutput, Descr>,
+    ) -> Self
+    where
+        Input: Clone + 'static,
+        InnerOutput: Send + 'static,
+        Descr: Send + Sync + 'static,
+    {
+        let description = Descr::inspect();
+
+        self.chain(from_fn_with_description(description, move |event: Input, cont| {
+            let inner = inner.clone();
+            let detached = event.clone();
+            drop(tokio::spawn(async move { inner.dispatch_owned(detached).await }));
+
+            cont(event)
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    };
+
+    use tokio::sync::{mpsc, Notify};
+
+    use super::*;
+    use crate::{deps, di::DependencyMap, help_inference};
+    use core::ops::ControlFlow;
+
+    #[tokio::test]
+    async fn test_spawn_detached() {
+        let release = Arc::new(Notify::new());
+        let completed = Arc::new(AtomicBool::new(false));
+        let (tx, mut rx) = mpsc::unbounded_channel();
+
+        let inner: Handler<DependencyMap, ()> = crate::endpoint({
+            let release = Arc::clone(&release);
+            let completed = Arc::clone(&completed);
+            move |x: i32| {
+                let release = Arc::clone(&release);
+                let completed = Arc::clone(&completed);
+                let tx = tx.clone();
+                async move {
+                    release.notified().await;
+                    completed.store(true, Ordering::SeqCst);
+                    tx.send(x).unwrap();
+                }
+            }
+        });
+
+        let handler = help_inference(crate::entry())
+            .spawn_detached(inner)
+            .endpoint(|x: i32| async move { x + 1 });
+
+        // The main chain proceeds while the detached handler is still waiting.
+        assert_eq!(handler.dispatch(deps![1]).await, ControlFlow::Break(2));
+        assert!(!completed.load(Ordering::SeqCst));
+
+        release.notify_one();
+        assert_eq!(rx.recv().await, Some(1));
+        assert!(completed.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn test_spawn_detached_filtered() {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let inner: Handler<DependencyMap, ()> = crate::endpoint(move |x: i32| {
+            let tx = tx.clone();
+            async move { tx.send(x).unwrap() }
+        });
+
+        let handler = help_inference(crate::filter(|x: i32| x > 0))
+            .spawn_detached(inner)
+            .endpoint(|| async { "handled" });
+
+        // The detached handler is not spawned if this handler continues.
+        assert_eq!(handler.dispatch(deps![-1]).await, ControlFlow::Continue(deps![-1]));
+        assert_eq!(handler.dispatch(deps![1]).await, ControlFlow::Break("handled"));
+        assert_eq!(rx.recv().await, Some(1));
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[test]
+    #[should_panic(expected = "the handler has no endpoints")]
+    fn test_spawn_detached_description() {
+        use crate::description::Tree;
+
+        // The endpoint of the detached handler does not count.
+        let inner: Handler<DependencyMap, (), Tree> = crate::endpoint(|| async {});
+        let handler: Handler<DependencyMap, (), Tree> = crate::entry().spawn_detached(inner);
+        let _ = handler.require_non_empty();
+    }
+}
@@ -0,0 +1,231 @@
+use crate::{di::Insert, from_fn_with_description, Handler, HandlerDescription};
+use std::{
+    future::Future,
+    ops::ControlFlow,
+    sync::{Arc, Mutex, PoisonError},
+};
+
+/// Constructs a handler that filters input with the stateful predicate `pred`.
+///
+/// `pred` receives a mutable reference to the state, which is initially
+/// `initial` and persists across executions, and a reference to the input. If
+/// it returns `true`, a continuation of the handler will be called, otherwise
+/// the handler returns [`ControlFlow::Continue`].
+///
+/// ## Locking
+///
+/// The state is stored in a [`Mutex`] shared by all clones of the handler. The
+/// mutex is locked only while `pred` is running and is released before the
+/// continuation is called, so concurrent executions are serialized only on
+/// `pred` itself. If `pred` panics, the state stays as `pred` left it, and the
+/// next executions use it as usual.
+///
+/// # Examples
+///
+/// ```
+/// # #[tokio::main]
+/// # async fn main() {
+/// use dptree::prelude::*;
+///
+/// // Passes at most 2 inputs.
+/// let handler: Handler<DependencyMap, _> =
+///     dptree::filter_stateful(0, |passed: &mut u32, _: &DependencyMap| {
+///         *passed += 1;
+///         *passed <= 2
+///     })
+///     .endpoint(|| async { "passed" });
+///
+/// assert_eq!(handler.dispatch(dptree::deps![]).await, ControlFlow::Break("passed"));
+/// assert_eq!(handler.dispatch(dptree::deps![]).await, ControlFlow::Break("passed"));
+/// assert_eq!(handler.dispatch(dptree::deps![]).await, ControlFlow::Continue(dptree::deps![]));
+/// # }
+/// ```
+#[must_use]
+#[track_caller]
+pub fn filter_stateful<'a, State, Pred, Input, Output, Descr>(
+    initial: State,
+    pred: Pred,
+) -> Handler<'a, Input, Output, Descr>
+where
+    State: Send + 'a,
+    Pred: Fn(&mut State, &Input) -> bool + Send + Sync + 'a,
+    Input: Send + 'a,
+    Output: 'a,
+    Descr: HandlerDescription,
+{
+    let state = Arc::new(Mutex::new(initial));
+    let pred = Arc::new(pred);
+
+    from_fn_with_description(Descr::filter(), move |event, cont| {
+        let cond = {
+            let mut state = state.lock().unwrap_or_else(PoisonError::into_inner);
+            pred(&mut state, &event)
+        };
+
+        async move {
+            if cond {
+                cont(event).await
+            } else {
+                ControlFlow::Continue(event)
+            }
+        }
+    })
+}
+
+/// Constructs a handler that optionally passes a value of a new type further,
+/// computed by the stateful async projection `f`.
+///
+/// `f` receives the state, which is initially `initial` and persists across
+/// executions, and a reference to the input. If the future returned by `f`
+/// resolves to `Some(v)`, then `v` will be added to the container and passed
+/// further in a handler chain. If it resolves to `None`, then the handler will
+/// return [`ControlFlow::Continue`] with the old container.
+///
+/// ## Locking
+///
+/// Unlike [`filter_stateful`], the state is passed to `f` as a shared
+/// [`Mutex`], and `f` decides when to lock it. The future returned by `f` must
+/// be [`Send`], so it cannot hold a [`MutexGuard`](std::sync::MutexGuard)
+/// across an `.await`: lock the state, update it, and drop the guard before
+/// awaiting anything. If you need to hold the state locked while awaiting, use
+/// an asynchronous mutex (e.g., `tokio::sync::Mutex`) as the state itself.
+///
+/// # Examples
+///
+/// ```
+/// # #[tokio::main]
+/// # async fn main() {
+/// use dptree::{di::DependencySupplier, prelude::*};
+/// use std::{
+///     collections::HashSet,
+///     sync::{Arc, Mutex},
+/// };
+///
+/// // Passes every message ID at most once.
+/// let handler: Handler<DependencyMap, _> = dptree::filter_map_stateful(
+///     HashSet::new(),
+///     |seen: Arc<Mutex<HashSet<u64>>>, deps: &DependencyMap| {
+///         let id: Arc<u64> = deps.get();
+///         async move { seen.lock().unwrap().insert(*id).then(|| *id as i64) }
+///     },
+/// )
+/// .endpoint(|id: i64| async move { id });
+///
+/// assert_eq!(handler.dispatch(dptree::deps![1u64]).await, ControlFlow::Break(1));
+/// assert_eq!(
+///     handler.dispatch(dptree::deps![1u64]).await,
+///     ControlFlow::Continue(dptree::deps![1u64])
+/// );
+/// # }
+/// ```
+#[must_use]
+#[track_caller]
+pub fn filter_map_stateful<'a, State, F, Fut, Input, Output, NewType, Descr>(
+    initial: State,
+    f: F,
+) -> Handler<'a, Input, Output, Descr>
+where
+    State: Send + 'a,
+    F: Fn(Arc<Mutex<State>>, &Input) -> Fut + Send + Sync + 'a,
+    Fut: Future<Output = Option<NewType>> + Send + 'a,
+    Input: Insert<NewType> + Clone + Send + 'a,
+    Output: 'a,
+    NewType: Send,
+    Descr: HandlerDescription,
+{
+    let state = Arc::new(Mutex::new(initial));
+    let f = Arc::new(f);
+    let description =
+        Descr::filter_map_async().with_dependencies(&[], &[std::any::type_name::<NewType>()]);
+
+    from_fn_with_description(description, move |container: Input, cont| {
+        let fut = f(Arc::clone(&state), &container);
+
+        async move {
+            match fut.await {
+                Some(new_type) => {
+                    let mut intermediate = container.clone();
+                    intermediate.insert(new_type);
+                    match cont(intermediate).await {
+                        ControlFlow::Continue(_) => ControlFlow::Continue(container),
+                        ControlFlow::Break(result) => ControlFlow::Break(result),
+                    }
+                }
+                None => ControlFlow::Continue(container),
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{deps, di::DependencySupplier, help_inference, prelude::DependencyMap};
+
+    #[tokio::test]
+    async fn test_filter_stateful() {
+        let handler =
+            help_inference(filter_stateful(false, |odd: &mut bool, _: &DependencyMap| {
+                *odd = !*odd;
+                *odd
+            }))
+            .endpoint(|x: i32| async move { x });
+
+        let mut results = Vec::new();
+        for x in 0..4 {
+            results.push(handler.dispatch(deps![x]).await);
+        }
+
+        assert_eq!(
+            results,
+            [
+                ControlFlow::Break(0),
+                ControlFlow::Continue(deps![1]),
+                ControlFlow::Break(2),
+                ControlFlow::Continue(deps![3])
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_filter_stateful_input() {
+        let handler = help_inference(filter_stateful(
+            Vec::new(),
+            |seen: &mut Vec<i32>, input: &DependencyMap| {
+                let x: Arc<i32> = input.get();
+                let fresh = !seen.contains(&x);
+                seen.push(*x);
+                fresh
+            },
+        ))
+        .endpoint(|| async { "fresh" });
+
+        assert_eq!(handler.dispatch(deps![1]).await, ControlFlow::Break("fresh"));
+        assert_eq!(handler.dispatch(deps![2]).await, ControlFlow::Break("fresh"));
+        assert_eq!(handler.dispatch(deps![1]).await, ControlFlow::Continue(deps![1]));
+    }
+
+    #[tokio::test]
+    async fn test_filter_map_stateful_dedup() {
+        use std::collections::HashSet;
+
+        let handler = help_inference(filter_map_stateful(
+            HashSet::new(),
+            |seen: Arc<Mutex<HashSet<&'static str>>>, input: &DependencyMap| {
+                let text: Arc<&'static str> = input.get();
+                async move {
+                    tokio::task::yield_now().await;
+                    let fresh = seen.lock().unwrap().insert(*text);
+                    fresh.then(|| text.len())
+                }
+            },
+        ))
+        .endpoint(|len: usize| async move { len });
+
+        assert_eq!(handler.dispatch(deps!["abc"]).await, ControlFlow::Break(3));
+        assert_eq!(handler.dispatch(deps!["de"]).await, ControlFlow::Break(2));
+        assert_eq!(handler.dispatch(deps!["abc"]).await, ControlFlow::Continue(deps!["abc"]));
+        // The state is shared by the clones.
+        assert_eq!(handler.clone().dispatch(deps!["de"]).await, ControlFlow::Continue(deps!["de"]));
+    }
+}
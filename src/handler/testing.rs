@@ -0,0 +1,107 @@
+use crate::{Handler, HandlerDescription};
+use core::{fmt::Debug, ops::ControlFlow};
+
+impl<'a, Input, Output, Descr> Handler<'a, Input, Output, Descr>
+where
+    Input: Send + 'a,
+    Output: 'a,
+    Descr: HandlerDescription,
+{
+    /// Dispatches `input` and returns the output.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this handler continues.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use dptree::prelude::*;
+    ///
+    /// let handler: Handler<_, _> = dptree::filter(|x: i32| x > 0).endpoint(|| async { "positive" });
+    ///
+    /// assert_eq!(handler.expect_break(dptree::deps![1]).await, "positive");
+    /// # }
+    /// ```
+    pub async fn expect_break(&self, input: Input) -> Output
+    where
+        Input: Debug,
+    {
+        match self.dispatch(input).await {
+            ControlFlow::Break(output) => output,
+            ControlFlow::Continue(input) => {
+                panic!("expected the handler to break, but it continued with {:?}", input)
+            }
+        }
+    }
+
+    /// Dispatches `input` and returns the input with which this handler
+    /// continues.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this handler breaks.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use dptree::prelude::*;
+    ///
+    /// let handler: Handler<_, &str> =
+    ///     dptree::filter(|x: i32| x > 0).endpoint(|| async { "positive" });
+    ///
+    /// let input = handler.expect_continue(dptree::deps![-1]).await;
+    /// assert!(input.contains::<i32>());
+    /// # }
+    /// ```
+    pub async fn expect_continue(&self, input: Input) -> Input
+    where
+        Output: Debug,
+    {
+        match self.dispatch(input).await {
+            ControlFlow::Continue(input) => input,
+            ControlFlow::Break(output) => {
+                panic!("expected the handler to continue, but it broke with {:?}", output)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{deps, help_inference};
+
+    #[tokio::test]
+    async fn test_expect_break() {
+        let handler = help_inference(crate::endpoint(|x: i32| async move { x + 1 }));
+
+        assert_eq!(handler.expect_break(deps![1]).await, 2);
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "expected the handler to break, but it continued with DependencyMap")]
+    async fn test_expect_break_panics() {
+        let handler = help_inference(crate::filter(|| false)).endpoint(|| async {});
+
+        handler.expect_break(deps![]).await;
+    }
+
+    #[tokio::test]
+    async fn test_expect_continue() {
+        let handler = help_inference(crate::filter(|| false)).endpoint(|| async {});
+
+        assert_eq!(handler.expect_continue(deps![1]).await, deps![1]);
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "expected the handler to continue, but it broke with \"done\"")]
+    async fn test_expect_continue_panics() {
+        let handler = help_inference(crate::endpoint(|| async { "done" }));
+
+        handler.expect_continue(deps![]).await;
+    }
+}
@@ -0,0 +1,245 @@
+use crate::{
+    di::{CompiledFn, Injectable, Insert},
+    filter_map_async_with_description, Handler, HandlerDescription,
+};
+use std::{sync::Arc, time::Duration};
+
+/// Constructs a handler that optionally passes a value of a new type further,
+/// giving up after `duration`.
+///
+/// This is the same as [`filter_map_async`](crate::filter_map_async), except
+/// that if `proj` does not complete within `duration`, it is cancelled and the
+/// handler returns [`ControlFlow::Continue`] without adding anything to the
+/// container.
+///
+/// [`ControlFlow::Continue`]: std::ops::ControlFlow::Continue
+///
+/// # Examples
+///
+/// ```
+/// # #[tokio::main]
+/// # async fn main() {
+/// use dptree::prelude::*;
+/// use std::time::Duration;
+///
+/// let handler: Handler<_, _> = dptree::filter_map_timeout(
+///     |x: u64| async move {
+///         tokio::time::sleep(Duration::from_millis(x)).await;
+///         Some(x.to_string())
+///     },
+///     Duration::from_millis(50),
+/// )
+/// .endpoint(|s: String| async move { s });
+///
+/// assert_eq!(handler.dispatch(dptree::deps![1u64]).await, ControlFlow::Break("1".to_owned()));
+/// assert!(matches!(handler.dispatch(dptree::deps![1000u64]).await, ControlFlow::Continue(_)));
+/// # }
+/// ```
+#[must_use]
+#[track_caller]
+pub fn filter_map_timeout<'a, Projection, Input, Output, NewType, Args, Descr>(
+    proj: Projection,
+    duration: Duration,
+) -> Handler<'a, Input, Output, Descr>
+where
+    Input: Clone,
+    Projection: Injectable<Input, Option<NewType>, Args> + Send + Sync + 'a,
+    Input: Insert<NewType> + Send + 'a,
+    Output: 'a,
+    Descr: HandlerDescription,
+    NewType: Send + 'static,
+{
+    let required = <Projection as Injectable<Input, Option<NewType>, Args>>::dependencies();
+    let provided = [std::any::type_name::<NewType>()];
+    filter_map_async_with_description(
+        Descr::filter_map_async().with_dependencies(&required, &provided),
+        WithTimeout { proj, duration },
+    )
+}
+
+/// Constructs a handler that passes a value of a new type further, falling back
+/// to `default` after `duration`.
+///
+/// This is the same as [`filter_map_timeout`], except that if `proj` does not
+/// complete within `duration`, it is cancelled and `default` is added to the
+/// container instead, so that the next handlers in the chain still get their
+/// dependency. If `proj` completes in time with `None`, the handler returns
+/// [`ControlFlow::Continue`], as usual.
+///
+/// [`ControlFlow::Continue`]: std::ops::ControlFlow::Continue
+///
+/// # Examples
+///
+/// ```
+/// # #[tokio::main]
+/// # async fn main() {
+/// use dptree::prelude::*;
+/// use std::time::Duration;
+///
+/// let handler: Handler<_, _> = dptree::filter_map_timeout_default(
+///     |x: u64| async move {
+///         tokio::time::sleep(Duration::from_millis(x)).await;
+///         Some(x.to_string())
+///     },
+///     Duration::from_millis(50),
+///     "slow".to_owned(),
+/// )
+/// .endpoint(|s: String| async move { s });
+///
+/// assert_eq!(handler.dispatch(dptree::deps![1u64]).await, ControlFlow::Break("1".to_owned()));
+/// assert_eq!(
+///     handler.dispatch(dptree::deps![1000u64]).await,
+///     ControlFlow::Break("slow".to_owned())
+/// );
+/// # }
+/// ```
+#[must_use]
+#[track_caller]
+pub fn filter_map_timeout_default<'a, Projection, Input, Output, NewType, Args, Descr>(
+    proj: Projection,
+    duration: Duration,
+    default: NewType,
+) -> Handler<'a, Input, Output, Descr>
+where
+    Input: Clone,
+    Projection: Injectable<Input, Option<NewType>, Args> + Send + Sync + 'a,
+    Input: Insert<NewType> + Send + 'a,
+    Output: 'a,
+    Descr: HandlerDescription,
+    NewType: Clone + Send + Sync + 'static,
+{
+    let required = <Projection as Injectable<Input, Option<NewType>, Args>>::dependencies();
+    let provided = [std::any::type_name::<NewType>()];
+    filter_map_async_with_description(
+        Descr::filter_map_async().with_dependencies(&required, &provided),
+        WithTimeoutDefault { proj, duration, default },
+    )
+}
+
+/// A projection that results in `None` if it does not complete in time.
+struct WithTimeout<Projection> {
+    proj: Projection,
+    duration: Duration,
+}
+
+impl<Projection, Input, NewType, Args> Injectable<Input, Option<NewType>, Args>
+    for WithTimeout<Projection>
+where
+    Projection: Injectable<Input, Option<NewType>, Args>,
+    NewType: Send + 'static,
+{
+    fn inject<'a>(&'a self, container: &'a Input) -> CompiledFn<'a, Option<NewType>> {
+        let proj = self.proj.inject(container);
+        let duration = self.duration;
+
+        Arc::new(move || {
+            let fut = proj();
+            Box::pin(async move { tokio::time::timeout(duration, fut).await.ok().flatten() })
+        })
+    }
+
+    fn dependencies() -> Vec<&'static str> {
+        Projection::dependencies()
+    }
+}
+
+/// A projection that results in a default value if it does not complete in
+/// time.
+struct WithTimeoutDefault<Projection, NewType> {
+    proj: Projection,
+    duration: Duration,
+    default: NewType,
+}
+
+impl<Projection, Input, NewType, Args> Injectable<Input, Option<NewType>, Args>
+    for WithTimeoutDefault<Projection, NewType>
+where
+    Projection: Injectable<Input, Option<NewType>, Args>,
+    NewType: Clone + Send + Sync + 'static,
+{
+    fn inject<'a>(&'a self, container: &'a Input) -> CompiledFn<'a, Option<NewType>> {
+        let proj = self.proj.inject(container);
+        let duration = self.duration;
+        let default = &self.default;
+
+        Arc::new(move || {
+            let fut = proj();
+            let default = default.clone();
+            Box::pin(
+                async move { tokio::time::timeout(duration, fut).await.unwrap_or(Some(default)) },
+            )
+        })
+    }
+
+    fn dependencies() -> Vec<&'static str> {
+        Projection::dependencies()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{deps, help_inference};
+    use std::ops::ControlFlow;
+
+    fn handler() -> Handler<'static, crate::di::DependencyMap, i32> {
+        help_inference(filter_map_timeout(
+            |delay: u64| async move {
+                tokio::time::sleep(Duration::from_secs(delay)).await;
+                Some(delay as i32)
+            },
+            Duration::from_secs(10),
+        ))
+        .endpoint(|x: i32| async move { x })
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_filter_map_timeout_success() {
+        assert_eq!(handler().dispatch(deps![5u64]).await, ControlFlow::Break(5));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_filter_map_timeout_elapsed() {
+        let started = tokio::time::Instant::now();
+
+        assert_eq!(handler().dispatch(deps![60u64]).await, ControlFlow::Continue(deps![60u64]));
+        assert_eq!(started.elapsed(), Duration::from_secs(10));
+    }
+
+    fn handler_with_default() -> Handler<'static, crate::di::DependencyMap, i32> {
+        help_inference(filter_map_timeout_default(
+            |delay: u64| async move {
+                tokio::time::sleep(Duration::from_secs(delay)).await;
+                if delay < 30 {
+                    Some(delay as i32)
+                } else {
+                    None
+                }
+            },
+            Duration::from_secs(60),
+            -1,
+        ))
+        .endpoint(|x: i32| async move { x })
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_filter_map_timeout_default_success() {
+        assert_eq!(handler_with_default().dispatch(deps![5u64]).await, ControlFlow::Break(5));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_filter_map_timeout_default_none() {
+        assert_eq!(
+            handler_with_default().dispatch(deps![30u64]).await,
+            ControlFlow::Continue(deps![30u64])
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_filter_map_timeout_default_elapsed() {
+        let started = tokio::time::Instant::now();
+
+        assert_eq!(handler_with_default().dispatch(deps![90u64]).await, ControlFlow::Break(-1));
+        assert_eq!(started.elapsed(), Duration::from_secs(60));
+    }
+}
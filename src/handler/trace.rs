@@ -0,0 +1,292 @@
+use crate::{di::Insert, from_fn_with_description, Handler, HandlerDescription};
+use core::{fmt, ops::ControlFlow, sync::atomic::Ordering};
+
+/// A context of a single dispatch.
+///
+/// See [`Handler::with_trace_context`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TraceContext {
+    trace_id: u64,
+}
+
+impl TraceContext {
+    /// Constructs a context with a trace identifier that is unique within this
+    /// process.
+    ///
+    /// On targets without 64-bit atomics, the identifiers wrap around after
+    /// `usize::MAX` contexts.
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self {
+        static NEXT_TRACE_ID: Counter = Counter::new();
+
+        Self::from_trace_id(NEXT_TRACE_ID.next())
+    }
+
+    /// Constructs a context with the given trace identifier, e.g., one received
+    /// from an external system.
+    pub fn from_trace_id(trace_id: u64) -> Self {
+        Self { trace_id }
+    }
+
+    /// Returns the trace identifier.
+    pub fn trace_id(&self) -> u64 {
+        self.trace_id
+    }
+}
+
+/// An identifier of a single dispatch, for correlating log records.
+///
+/// See [`Handler::with_request_id`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct RequestId(u64);
+
+impl RequestId {
+    /// Generates an identifier that is unique within this process.
+    ///
+    /// Identifiers are taken from a monotonic counter, so an identifier
+    /// generated later is greater. On targets without 64-bit atomics, the
+    /// counter is a `usize`, so it wraps around after `usize::MAX`
+    /// identifiers.
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self {
+        static NEXT_REQUEST_ID: Counter = Counter::new();
+
+        Self(NEXT_REQUEST_ID.next())
+    }
+
+    /// Returns the identifier as a number.
+    pub fn get(&self) -> u64 {
+        self.0
+    }
+}
+
+impl fmt::Display for RequestId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+// A process-wide identifier counter. It is 64-bit where the target supports
+// 64-bit atomics, and `usize` otherwise.
+struct Counter(AtomicId);
+
+#[cfg(target_has_atomic = "64")]
+type AtomicId = core::sync::atomic::AtomicU64;
+#[cfg(not(target_has_atomic = "64"))]
+type AtomicId = core::sync::atomic::AtomicUsize;
+
+impl Counter {
+    const fn new() -> Self {
+        Self(AtomicId::new(0))
+    }
+
+    // The cast is only needed for `AtomicUsize`.
+    #[allow(clippy::unnecessary_cast)]
+    fn next(&self) -> u64 {
+        self.0.fetch_add(1, Ordering::Relaxed) as u64
+    }
+}
+
+impl<'a, Input, Output, Descr> Handler<'a, Input, Output, Descr>
+where
+    Input: Send + 'a,
+    Output: 'a,
+    Descr: HandlerDescription,
+{
+    /// Makes a fresh [`TraceContext`] available to this handler and its
+    /// continuation.
+    ///
+    /// Every time the execution reaches the returned handler, a new
+    /// [`TraceContext`] is added to the container, so all handlers executed
+    /// during a single dispatch see the same trace identifier. If the execution
+    /// continues, the original container without the context is returned.
+    ///
+    /// With the `tracing` feature enabled, the execution is also instrumented
+    /// with a `dispatch` span carrying the `trace_id` field.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use dptree::{prelude::*, TraceContext};
+    ///
+    /// let handler: Handler<_, _> = dptree::entry()
+    ///     .endpoint(|ctx: TraceContext| async move { ctx.trace_id() })
+    ///     .with_trace_context();
+    ///
+    /// let first = handler.dispatch(dptree::deps![]).await;
+    /// let second = handler.dispatch(dptree::deps![]).await;
+    /// assert_ne!(first, second);
+    /// # }
+    /// ```
+    #[must_use]
+    #[track_caller]
+    pub fn with_trace_context(self) -> Self
+    where
+        Input: Insert<TraceContext> + Clone,
+    {
+        let description = self.wrapper_description();
+
+        from_fn_with_description(description, move |container: Input, cont| {
+            let this = self.clone();
+            let ctx = TraceContext::new();
+
+            let fut = async move {
+                let mut intermediate = container.clone();
+                intermediate.insert(ctx);
+                match this.execute(intermediate, cont).await {
+                    ControlFlow::Continue(_) => ControlFlow::Continue(container),
+                    ControlFlow::Break(result) => ControlFlow::Break(result),
+                }
+            };
+
+            #[cfg(feature = "tracing")]
+            let fut = tracing::Instrument::instrument(
+                fut,
+                tracing::info_span!("dispatch", trace_id = ctx.trace_id()),
+            );
+
+            fut
+        })
+    }
+
+    /// Makes a freshly generated [`RequestId`] available to this handler and
+    /// its continuation.
+    ///
+    /// Every time the execution reaches the returned handler, a new
+    /// [`RequestId`] is added to the container, so all handlers executed during
+    /// a single dispatch see the same identifier, while concurrent dispatches
+    /// see distinct ones. If the execution continues, the original container
+    /// without the identifier is returned.
+    ///
+    /// Usually, this method is called on the root of a handler tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use dptree::{prelude::*, RequestId};
+    ///
+    /// let handler: Handler<_, _> = dptree::entry()
+    ///     .inspect(|id: RequestId| println!("[{id}] received an update"))
+    ///     .endpoint(|id: RequestId| async move { id })
+    ///     .with_request_id();
+    ///
+    /// let first = handler.dispatch(dptree::deps![]).await;
+    /// let second = handler.dispatch(dptree::deps![]).await;
+    /// assert_ne!(first, second);
+    /// # }
+    /// ```
+    #[must_use]
+    #[track_caller]
+    pub fn with_request_id(self) -> Self
+    where
+        Input: Insert<RequestId> + Clone,
+    {
+        let description = self.wrapper_description();
+
+        from_fn_with_description(description, move |container: Input, cont| {
+            let this = self.clone();
+            let id = RequestId::new();
+
+            async move {
+                let mut intermediate = container.clone();
+                intermediate.insert(id);
+                match this.execute(intermediate, cont).await {
+                    ControlFlow::Continue(_) => ControlFlow::Continue(container),
+                    ControlFlow::Break(result) => ControlFlow::Break(result),
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+    use crate::{deps, help_inference};
+
+    #[tokio::test]
+    async fn test_with_trace_context() {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+
+        let handler = help_inference(crate::entry())
+            .inspect({
+                let seen = Arc::clone(&seen);
+                move |ctx: TraceContext| seen.lock().unwrap().push(ctx.trace_id())
+            })
+            .chain(crate::endpoint({
+                let seen = Arc::clone(&seen);
+                move |ctx: TraceContext| {
+                    seen.lock().unwrap().push(ctx.trace_id());
+                    async {}
+                }
+            }))
+            .with_trace_context();
+
+        assert_eq!(handler.dispatch(deps![]).await, ControlFlow::Break(()));
+        assert_eq!(handler.dispatch(deps![]).await, ControlFlow::Break(()));
+
+        let seen = seen.lock().unwrap();
+        assert_eq!(seen[0], seen[1]);
+        assert_eq!(seen[2], seen[3]);
+        assert_ne!(seen[0], seen[2]);
+    }
+
+    #[tokio::test]
+    async fn test_with_trace_context_continue() {
+        let handler = help_inference(crate::filter(|_: TraceContext| false)).with_trace_context();
+
+        let result: ControlFlow<(), _> = handler.dispatch(deps![1]).await;
+        match result {
+            ControlFlow::Continue(container) => assert!(!container.contains::<TraceContext>()),
+            ControlFlow::Break(()) => panic!("the handler must continue"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_with_request_id() {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+
+        let handler = help_inference(crate::entry())
+            .inspect({
+                let seen = Arc::clone(&seen);
+                move |id: RequestId| seen.lock().unwrap().push(id)
+            })
+            .endpoint(|id: RequestId| async move {
+                tokio::task::yield_now().await;
+                id
+            })
+            .with_request_id();
+
+        let (first, second) = tokio::join!(handler.dispatch(deps![]), handler.dispatch(deps![]));
+        let (first, second) = match (first, second) {
+            (ControlFlow::Break(first), ControlFlow::Break(second)) => (first, second),
+            _ => panic!("the handler must break"),
+        };
+
+        // Concurrent dispatches get distinct identifiers, and all handlers of a
+        // dispatch see the same one.
+        assert_ne!(first, second);
+        let mut seen = seen.lock().unwrap().clone();
+        seen.sort();
+        let mut expected = vec![first, second];
+        expected.sort();
+        assert_eq!(seen, expected);
+    }
+
+    #[tokio::test]
+    async fn test_with_request_id_continue() {
+        let handler = help_inference(crate::filter(|_: RequestId| false)).with_request_id();
+
+        let result: ControlFlow<(), _> = handler.dispatch(deps![1]).await;
+        match result {
+            ControlFlow::Continue(container) => assert!(!container.contains::<RequestId>()),
+            ControlFlow::Break(()) => panic!("the handler must continue"),
+        }
+    }
+}
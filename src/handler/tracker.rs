@@ -0,0 +1,146 @@
+use crate::{description, from_fn_with_description, Handler, HandlerDescription};
+use alloc::{sync::Arc, vec::Vec};
+use core::ops::ControlFlow;
+
+/// A builder of a group of branches that reports which branch was taken.
+///
+/// Branches are added with [`BranchTracker::branch`], just like with
+/// [`Handler::branch`] on [`entry`](crate::entry), and the group is built by
+/// [`BranchTracker::on_branch_taken`], which attaches a callback receiving the
+/// index of the branch that broke, or `None` if all of them continued. This is
+/// useful for analytics, e.g., to count which commands are used.
+///
+/// # Examples
+///
+/// ```
+/// # #[tokio::main]
+/// # async fn main() {
+/// use dptree::{prelude::*, BranchTracker};
+/// use std::sync::{Arc, Mutex};
+///
+/// let taken = Arc::new(Mutex::new(Vec::new()));
+///
+/// let handler: Handler<_, &str> = BranchTracker::new()
+///     .branch(dptree::filter(|s: &'static str| s == "/start").endpoint(|| async { "Welcome!" }))
+///     .branch(dptree::filter(|s: &'static str| s == "/help").endpoint(|| async { "Help" }))
+///     .on_branch_taken({
+///         let taken = Arc::clone(&taken);
+///         move |index| taken.lock().unwrap().push(index)
+///     });
+///
+/// let _ = handler.dispatch(dptree::deps!["/help"]).await;
+/// let _ = handler.dispatch(dptree::deps!["/stop"]).await;
+///
+/// assert_eq!(*taken.lock().unwrap(), [Some(1), None]);
+/// # }
+/// ```
+#[must_use]
+pub struct BranchTracker<'a, Input, Output, Descr = description::Unspecified> {
+    branches: Vec<Handler<'a, Input, Output, Descr>>,
+}
+
+impl<'a, Input, Output, Descr> BranchTracker<'a, Input, Output, Descr>
+where
+    Input: Send + 'a,
+    Output: Send + 'a,
+    Descr: HandlerDescription,
+{
+    /// Constructs a builder without branches.
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self {
+        Self { branches: Vec::new() }
+    }
+
+    /// Adds a branch, which is tried after the previously added ones.
+    pub fn branch(mut self, next: Handler<'a, Input, Output, Descr>) -> Self {
+        self.branches.push(next);
+        self
+    }
+
+    /// Builds the group of branches, calling `callback` on each execution.
+    ///
+    /// The returned handler tries the branches in order, as
+    /// `entry().branch(..).branch(..)` does. If a branch breaks, `callback` is
+    /// called with its index, starting from zero, and the returned handler
+    /// breaks with its output. If all of the branches continue, `callback` is
+    /// called with `None`, and the execution continues further in a handler
+    /// chain.
+    #[must_use]
+    #[track_caller]
+    pub fn on_branch_taken<F>(self, callback: F) -> Handler<'a, Input, Output, Descr>
+    where
+        F: Fn(Option<usize>) + Send + Sync + 'a,
+    {
+        let branches = self.branches;
+        let description = branches.iter().fold(Descr::entry(), |description, branch| {
+            description.merge_branch(branch.description())
+        });
+        let callback = Arc::new(callback);
+
+        from_fn_with_description(description, move |mut event: Input, cont| {
+            let branches = branches.clone();
+            let callback = Arc::clone(&callback);
+
+            async move {
+                for (index, branch) in branches.into_iter().enumerate() {
+                    match branch.dispatch(event).await {
+                        ControlFlow::Continue(next) => event = next,
+                        ControlFlow::Break(output) => {
+                            callback(Some(index));
+                            return ControlFlow::Break(output);
+                        }
+                    }
+                }
+
+                callback(None);
+                cont(event).await
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+    use crate::{deps, di::DependencyMap};
+
+    #[tokio::test]
+    async fn test_on_branch_taken() {
+        let taken = Arc::new(Mutex::new(Vec::new()));
+
+        let handler: Handler<DependencyMap, &str> = BranchTracker::new()
+            .branch(crate::filter(|x: i32| x < 0).endpoint(|| async { "negative" }))
+            .branch(crate::filter(|x: i32| x == 0).endpoint(|| async { "zero" }))
+            .branch(crate::filter(|x: i32| x > 0).endpoint(|| async { "positive" }))
+            .on_branch_taken({
+                let taken = Arc::clone(&taken);
+                move |index| taken.lock().unwrap().push(index)
+            });
+
+        assert_eq!(handler.dispatch(deps![5]).await, ControlFlow::Break("positive"));
+        assert_eq!(handler.dispatch(deps![-5]).await, ControlFlow::Break("negative"));
+        assert_eq!(handler.dispatch(deps![0]).await, ControlFlow::Break("zero"));
+        assert_eq!(*taken.lock().unwrap(), [Some(2), Some(0), Some(1)]);
+    }
+
+    #[tokio::test]
+    async fn test_on_branch_taken_none() {
+        let taken = Arc::new(Mutex::new(Vec::new()));
+
+        let tracked: Handler<DependencyMap, &str> = BranchTracker::new()
+            .branch(crate::filter(|x: i32| x < 0).endpoint(|| async { "negative" }))
+            .branch(crate::filter(|x: i32| x == 0).endpoint(|| async { "zero" }))
+            .branch(crate::filter(|x: i32| x > 0 && x < 10).endpoint(|| async { "small" }))
+            .on_branch_taken({
+                let taken = Arc::clone(&taken);
+                move |index| taken.lock().unwrap().push(index)
+            });
+        let handler = tracked.endpoint(|| async { "large" });
+
+        // The execution continues after the group, like after `entry().branch(..)`.
+        assert_eq!(handler.dispatch(deps![100]).await, ControlFlow::Break("large"));
+        assert_eq!(*taken.lock().unwrap(), [None]);
+    }
+}
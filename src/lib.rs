@@ -52,17 +52,37 @@
 //!
 //! For a high-level overview, please see [`README.md`](https://github.com/teloxide/dptree).
 //!
+//! ## `no_std` support
+//!
+//! The core of `dptree` only requires [`alloc`]. Handlers and descriptions that
+//! need the standard library are available under the `std` feature, which is
+//! enabled by default. To use `dptree` in a `no_std` environment, disable
+//! default features.
+//!
+//! ## Attribute macros
+//!
+//! Under the `macros` feature, an async function annotated with
+//! `#[dptree::endpoint]` gets a companion constructor: for `async fn greet(..)
+//! -> String`, the macro generates `fn greet_handler() -> Endpoint<'static,
+//! DependencyMap, String>` returning `dptree::endpoint(greet)`.
+//!
 //! [chain (tree) of responsibility]: https://en.wikipedia.org/wiki/Chain-of-responsibility_pattern
 
-#![feature(closure_track_caller)]
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+extern crate alloc;
 
 mod handler;
 
 pub mod di;
+pub mod guides;
 pub mod prelude;
 
 pub use handler::*;
 
+#[cfg(feature = "macros")]
+pub use dptree_macros::endpoint;
+
 /// Filters an enumeration, passing its payload forwards.
 ///
 /// This macro expands to a [`crate::Handler`] that acts on your enumeration
@@ -159,7 +179,7 @@ macro_rules! case {
 
 #[cfg(test)]
 mod tests {
-    use std::ops::ControlFlow;
+    use core::ops::ControlFlow;
 
     #[derive(Debug, Copy, Clone, Eq, PartialEq)]
     enum State {
@@ -256,4 +276,18 @@ mod tests {
         assert_eq!(h.dispatch(crate::deps![input]).await, ControlFlow::Break(123));
         assert!(matches!(h.dispatch(crate::deps![State::Other]).await, ControlFlow::Continue(_)));
     }
+
+    // Run with `cargo test --no-default-features` to check that the core works
+    // without `std`.
+    #[cfg(not(feature = "std"))]
+    #[tokio::test]
+    async fn no_std_core() {
+        let h: crate::Handler<_, _, crate::description::Tree> = crate::entry()
+            .branch(case![State::B(x)].map(|x: i32| x * 2).endpoint(|x: i32| async move { x }))
+            .branch(crate::filter(|| false).endpoint(|| async { 0 }));
+
+        assert_eq!(h.dispatch(crate::deps![State::B(21)]).await, ControlFlow::Break(42));
+        assert!(matches!(h.dispatch(crate::deps![State::A]).await, ControlFlow::Continue(_)));
+        assert_eq!(h.description().children.len(), 2);
+    }
 }
@@ -1,4 +1,4 @@
 //! Commonly used items.
 
 pub use crate::{di::DependencyMap, Endpoint, Handler};
-pub use std::ops::ControlFlow;
+pub use core::ops::ControlFlow;